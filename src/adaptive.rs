@@ -0,0 +1,407 @@
+use crate::codec::DEFAULT_LEVEL;
+use crate::{Codec, CodecError};
+
+use serde::{Deserialize, Serialize};
+use std::time::{Duration, Instant};
+
+type Result<T, E = CodecError> = std::result::Result<T, E>;
+
+/// The levels an [`AdaptiveCodec`] tries during its warmup, fastest first.
+const ADAPTIVE_LEVELS: [i32; 6] = [1, 3, 6, 9, 15, 19];
+
+impl Codec {
+    /// Creates an [`AdaptiveCodec`] that tries a range of compression levels on the first
+    /// `warmup` records and then sticks with the one that gave the best ratio per millisecond.
+    #[must_use]
+    pub fn adaptive(warmup: usize) -> AdaptiveCodec {
+        AdaptiveCodec {
+            candidates: ADAPTIVE_LEVELS.map(Codec::new).to_vec(),
+            warmup,
+            profile: AdaptiveProfile {
+                level: None,
+                samples: ADAPTIVE_LEVELS.map(LevelSample::new).to_vec(),
+            },
+            chosen: None,
+        }
+    }
+
+    /// Resumes an [`AdaptiveCodec`] from a profile saved from [`AdaptiveCodec::profile`], so a
+    /// restarted service does not have to warm up again.
+    ///
+    /// A profile with a picked level uses that level straight away. A profile that is still
+    /// warming up keeps measuring the levels it has samples for, until `warmup` records have been
+    /// measured in total, and starts from the default candidates if it has no samples.
+    ///
+    /// # Errors
+    ///
+    /// Return `epoch_archive::CodecError::InvalidLevel` if the picked level or a sampled level is
+    /// outside the range reported by `zstd::compression_level_range`.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use epoch_archive::{AdaptiveProfile, Codec};
+    ///
+    /// let mut codec = Codec::adaptive(6);
+    /// for i in 0..6u32 {
+    ///     codec.encode(&vec![i; 64]).unwrap();
+    /// }
+    ///
+    /// let saved = rmp_serde::to_vec(codec.profile()).unwrap();
+    /// let profile: AdaptiveProfile = rmp_serde::from_slice(&saved).unwrap();
+    /// let resumed = Codec::adaptive_with_profile(6, profile).unwrap();
+    /// assert_eq!(resumed.codec(), codec.codec());
+    /// ```
+    pub fn adaptive_with_profile(warmup: usize, profile: AdaptiveProfile) -> Result<AdaptiveCodec> {
+        let mut profile = profile;
+        if profile.samples.is_empty() {
+            profile.samples = ADAPTIVE_LEVELS.map(LevelSample::new).to_vec();
+        }
+
+        Ok(AdaptiveCodec {
+            candidates: profile
+                .samples
+                .iter()
+                .map(|sample| Codec::try_new(sample.level))
+                .collect::<Result<_>>()?,
+            warmup,
+            chosen: profile.level.map(Codec::try_new).transpose()?,
+            profile,
+        })
+    }
+}
+
+/// A codec that learns which compression level suits the data it is given.
+///
+/// During the warmup, records are compressed with each candidate level in turn while their
+/// compression ratio and time are measured. Once `warmup` records have been encoded, the level
+/// with the highest ratio per millisecond is picked and used for every record after that. The
+/// measurements are timing based, so the level that is picked can differ between machines and
+/// runs.
+///
+/// Every frame is a plain zstd frame, so it decodes with any [`Codec`] regardless of the level
+/// it was compressed at.
+///
+/// # Examples
+///
+/// ```
+/// use epoch_archive::Codec;
+///
+/// let mut codec = Codec::adaptive(12);
+/// for i in 0..20u32 {
+///     let frame = codec.encode(&vec![i; 64]).unwrap();
+///     assert_eq!(codec.decode::<Vec<u32>>(&frame).unwrap(), vec![i; 64]);
+/// }
+///
+/// assert!(codec.profile().level().is_some());
+/// ```
+#[derive(Debug, Clone)]
+pub struct AdaptiveCodec {
+    candidates: Vec<Codec>,
+    warmup: usize,
+    profile: AdaptiveProfile,
+    chosen: Option<Codec>,
+}
+
+impl AdaptiveCodec {
+    /// Serializes and compresses the provided data, measuring the level it used while the codec
+    /// is still warming up.
+    ///
+    /// # Errors
+    ///
+    /// Return `epoch_archive::CodecError` if there is an issue serializing or compressing the data.
+    pub fn encode<T: Serialize>(&mut self, data: &T) -> Result<Vec<u8>> {
+        if let Some(codec) = &self.chosen {
+            return codec.encode(data);
+        }
+
+        let serialized = Codec::serialize(data)?;
+        let records: usize = self.profile.samples.iter().map(|s| s.records).sum();
+        let index = records % self.candidates.len();
+
+        let start = Instant::now();
+        let frame = self.candidates[index].compress(&serialized)?;
+        self.profile.samples[index].record(serialized.len(), frame.len(), start.elapsed());
+
+        if records + 1 >= self.warmup {
+            self.freeze();
+        }
+
+        Ok(frame)
+    }
+
+    /// Deserializes and decompresses the provided data using the `MessagePack` format.
+    ///
+    /// # Errors
+    ///
+    /// Return `epoch_archive::CodecError` if there is an issue deserializing or decompressing the data.
+    pub fn decode<T>(&self, data: &[u8]) -> Result<T>
+    where
+        T: for<'de> Deserialize<'de>,
+    {
+        self.candidates[0].decode(data)
+    }
+
+    /// Returns the measurements taken so far, and the level that was picked once the warmup is
+    /// over.
+    #[must_use]
+    pub fn profile(&self) -> &AdaptiveProfile {
+        &self.profile
+    }
+
+    /// Returns the codec for the picked level, or `None` while the codec is still warming up.
+    #[must_use]
+    pub fn codec(&self) -> Option<&Codec> {
+        self.chosen.as_ref()
+    }
+
+    /// Picks the level with the best score, or the default codec's level if nothing was measured.
+    fn freeze(&mut self) {
+        let level = self
+            .profile
+            .samples
+            .iter()
+            .filter(|sample| sample.records > 0)
+            .max_by(|a, b| a.score().total_cmp(&b.score()))
+            .map_or(DEFAULT_LEVEL, |sample| sample.level);
+
+        self.profile.level = Some(level);
+        self.chosen = Some(Codec::new(level));
+    }
+}
+
+/// The measurements an [`AdaptiveCodec`] has taken, see [`AdaptiveCodec::profile`].
+///
+/// A profile can be serialized and later resumed with [`Codec::adaptive_with_profile`].
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct AdaptiveProfile {
+    level: Option<i32>,
+    samples: Vec<LevelSample>,
+}
+
+impl AdaptiveProfile {
+    /// Returns the picked level, or `None` while the codec is still warming up.
+    #[must_use]
+    pub fn level(&self) -> Option<i32> {
+        self.level
+    }
+
+    /// Returns the measurements of every candidate level.
+    #[must_use]
+    pub fn samples(&self) -> &[LevelSample] {
+        &self.samples
+    }
+}
+
+/// The totals measured for one compression level during the warmup of an [`AdaptiveCodec`].
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct LevelSample {
+    level: i32,
+    records: usize,
+    input_bytes: usize,
+    output_bytes: usize,
+    elapsed: Duration,
+}
+
+impl LevelSample {
+    fn new(level: i32) -> Self {
+        Self {
+            level,
+            records: 0,
+            input_bytes: 0,
+            output_bytes: 0,
+            elapsed: Duration::ZERO,
+        }
+    }
+
+    fn record(&mut self, input_bytes: usize, output_bytes: usize, elapsed: Duration) {
+        self.records += 1;
+        self.input_bytes += input_bytes;
+        self.output_bytes += output_bytes;
+        self.elapsed += elapsed;
+    }
+
+    /// Returns the compression level that was measured.
+    #[must_use]
+    pub fn level(&self) -> i32 {
+        self.level
+    }
+
+    /// Returns how many records were compressed at this level.
+    #[must_use]
+    pub fn records(&self) -> usize {
+        self.records
+    }
+
+    /// Returns the total time spent compressing at this level.
+    #[must_use]
+    pub fn elapsed(&self) -> Duration {
+        self.elapsed
+    }
+
+    /// Returns the serialized size divided by the compressed size, or 0 if nothing was measured.
+    #[must_use]
+    #[allow(clippy::cast_precision_loss)]
+    pub fn ratio(&self) -> f64 {
+        if self.output_bytes == 0 {
+            return 0.0;
+        }
+        self.input_bytes as f64 / self.output_bytes as f64
+    }
+
+    /// Returns the compression ratio per millisecond spent compressing, higher is better.
+    #[must_use]
+    pub fn score(&self) -> f64 {
+        // Small records can compress faster than the clock resolution.
+        let millis = (self.elapsed.as_secs_f64() * 1_000.0).max(1e-6);
+        self.ratio() / millis
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn record(i: u32) -> Vec<(u32, String)> {
+        (0..200)
+            .map(|j| (i + j, format!("sensor-{}/reading", j % 13)))
+            .collect()
+    }
+
+    #[test]
+    fn test_adaptive_converges() {
+        let warmup = ADAPTIVE_LEVELS.len() * 3;
+        let mut codec = Codec::adaptive(warmup);
+
+        for i in 0..warmup {
+            assert_eq!(codec.profile().level(), None);
+            assert!(codec.codec().is_none());
+            codec.encode(&record(u32::try_from(i).unwrap())).unwrap();
+        }
+
+        let level = codec.profile().level().unwrap();
+        assert!(ADAPTIVE_LEVELS.contains(&level));
+        assert_eq!(codec.codec().unwrap(), &Codec::new(level));
+        for sample in codec.profile().samples() {
+            assert_eq!(sample.records(), 3);
+            assert!(sample.ratio() > 1.0);
+        }
+
+        for i in 0..50 {
+            let frame = codec.encode(&record(i)).unwrap();
+            assert_eq!(frame, Codec::new(level).encode(&record(i)).unwrap());
+            assert_eq!(codec.profile().level(), Some(level));
+        }
+    }
+
+    #[test]
+    fn test_adaptive_frames_decode() {
+        let mut codec = Codec::adaptive(ADAPTIVE_LEVELS.len());
+
+        for i in 0..20 {
+            let frame = codec.encode(&record(i)).unwrap();
+            assert_eq!(
+                codec.decode::<Vec<(u32, String)>>(&frame).unwrap(),
+                record(i)
+            );
+            assert_eq!(
+                Codec::default()
+                    .decode::<Vec<(u32, String)>>(&frame)
+                    .unwrap(),
+                record(i)
+            );
+        }
+    }
+
+    #[test]
+    fn test_adaptive_short_warmup() {
+        let mut codec = Codec::adaptive(2);
+        codec.encode(&record(0)).unwrap();
+        codec.encode(&record(1)).unwrap();
+
+        let level = codec.profile().level().unwrap();
+        assert!(ADAPTIVE_LEVELS[..2].contains(&level));
+        assert_eq!(codec.profile().samples()[2].records(), 0);
+    }
+
+    #[test]
+    fn test_adaptive_no_warmup() {
+        let mut codec = Codec::adaptive(0);
+        codec.encode(&record(0)).unwrap();
+        assert!(codec.profile().level().is_some());
+    }
+
+    #[test]
+    fn test_adaptive_profile_round_trip() {
+        let warmup = ADAPTIVE_LEVELS.len() * 2;
+        let mut codec = Codec::adaptive(warmup);
+        for i in 0..warmup {
+            codec.encode(&record(u32::try_from(i).unwrap())).unwrap();
+        }
+
+        let saved = rmp_serde::to_vec(codec.profile()).unwrap();
+        let profile: AdaptiveProfile = rmp_serde::from_slice(&saved).unwrap();
+        assert_eq!(&profile, codec.profile());
+
+        let mut resumed = Codec::adaptive_with_profile(warmup, profile).unwrap();
+        assert_eq!(resumed.profile(), codec.profile());
+        assert_eq!(resumed.codec(), codec.codec());
+        for i in 0..5 {
+            assert_eq!(
+                resumed.encode(&record(i)).unwrap(),
+                codec.encode(&record(i)).unwrap()
+            );
+        }
+    }
+
+    #[test]
+    fn test_adaptive_profile_resume_warmup() {
+        let warmup = ADAPTIVE_LEVELS.len() * 2;
+        let mut codec = Codec::adaptive(warmup);
+        for i in 0..ADAPTIVE_LEVELS.len() {
+            codec.encode(&record(u32::try_from(i).unwrap())).unwrap();
+        }
+
+        let saved = rmp_serde::to_vec(codec.profile()).unwrap();
+        let profile = rmp_serde::from_slice(&saved).unwrap();
+        let mut resumed = Codec::adaptive_with_profile(warmup, profile).unwrap();
+        for i in 0..ADAPTIVE_LEVELS.len() {
+            assert!(resumed.codec().is_none());
+            resumed.encode(&record(u32::try_from(i).unwrap())).unwrap();
+        }
+
+        let level = resumed.profile().level().unwrap();
+        assert_eq!(resumed.codec(), Some(&Codec::new(level)));
+        for sample in resumed.profile().samples() {
+            assert_eq!(sample.records(), 2);
+        }
+
+        let empty = AdaptiveProfile {
+            level: None,
+            samples: Vec::new(),
+        };
+        let resumed = Codec::adaptive_with_profile(warmup, empty).unwrap();
+        assert_eq!(resumed.profile().samples().len(), ADAPTIVE_LEVELS.len());
+    }
+
+    #[test]
+    fn test_adaptive_profile_invalid_level() {
+        let picked = AdaptiveProfile {
+            level: Some(23),
+            samples: Vec::new(),
+        };
+        assert!(matches!(
+            Codec::adaptive_with_profile(1, picked),
+            Err(CodecError::InvalidLevel(23))
+        ));
+
+        let sampled = AdaptiveProfile {
+            level: None,
+            samples: vec![LevelSample::new(3), LevelSample::new(-1_000_000)],
+        };
+        assert!(matches!(
+            Codec::adaptive_with_profile(1, sampled),
+            Err(CodecError::InvalidLevel(-1_000_000))
+        ));
+    }
+}
@@ -1,8 +1,220 @@
-#[derive(Debug, Clone, PartialEq, Eq, PartialOrd, Ord)]
-pub struct Archive {
+use crate::{Codec, CodecError};
 
+use std::collections::{HashMap, HashSet};
+use std::ops::Range;
+
+type Result<T, E = CodecError> = std::result::Result<T, E>;
+
+impl Codec {
+    /// Writes named blobs into a single archive, compressing each payload separately so it can be
+    /// read back on its own with [`ArchiveReader::get`].
+    ///
+    /// Every entry is stored as a little-endian `u16` name length, the UTF-8 name, a little-endian
+    /// `u64` payload length and the compressed payload.
+    ///
+    /// # Errors
+    ///
+    /// Return `epoch_archive::CodecError::DuplicateEntry` if two entries share a name,
+    /// `epoch_archive::CodecError::InvalidEntryName` if a name is longer than `u16::MAX` bytes, or
+    /// `epoch_archive::CodecError` if there is an issue compressing a payload.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use epoch_archive::{ArchiveReader, Codec};
+    ///
+    /// let codec = Codec::default();
+    /// let entries = [("a", b"first".as_slice()), ("b", b"second".as_slice())];
+    /// let archive = codec.archive_entries(entries.into_iter()).unwrap();
+    ///
+    /// let reader = ArchiveReader::new(&codec, &archive).unwrap();
+    /// assert_eq!(reader.get("b").unwrap(), Some(b"second".to_vec()));
+    /// ```
+    pub fn archive_entries<'a>(
+        &self,
+        entries: impl Iterator<Item = (&'a str, &'a [u8])>,
+    ) -> Result<Vec<u8>> {
+        let mut names = HashSet::new();
+        let mut archive = Vec::new();
+
+        for (name, data) in entries {
+            if !names.insert(name) {
+                return Err(CodecError::DuplicateEntry(name.to_string()));
+            }
+            let name_len = u16::try_from(name.len())
+                .map_err(|_| CodecError::InvalidEntryName(name.to_string()))?;
+            let compressed = self.compress(data)?;
+
+            archive.extend_from_slice(&name_len.to_le_bytes());
+            archive.extend_from_slice(name.as_bytes());
+            archive.extend_from_slice(&(compressed.len() as u64).to_le_bytes());
+            archive.extend_from_slice(&compressed);
+        }
+
+        Ok(archive)
+    }
+}
+
+/// Looks up entries by name in an archive written by [`Codec::archive_entries`].
+///
+/// The entry table is read once when the reader is created, payloads are only decompressed when
+/// they are requested.
+#[derive(Debug, Clone)]
+pub struct ArchiveReader<'a> {
+    codec: &'a Codec,
+    data: &'a [u8],
+    entries: HashMap<&'a str, Range<usize>>,
+}
+
+impl<'a> ArchiveReader<'a> {
+    /// Reads the entry table of `data`, which has to be decompressed with `codec`.
+    ///
+    /// # Errors
+    ///
+    /// Return `epoch_archive::CodecError::TruncatedFrame` if the archive ends in the middle of an
+    /// entry, `epoch_archive::CodecError::InvalidEntryName` if a name is not valid UTF-8, or
+    /// `epoch_archive::CodecError::DuplicateEntry` if two entries share a name.
+    pub fn new(codec: &'a Codec, data: &'a [u8]) -> Result<Self> {
+        let mut entries = HashMap::new();
+        let mut offset = 0;
+
+        while offset < data.len() {
+            let name_len = u16::from_le_bytes(read_array(data, &mut offset)?);
+            let name = read_slice(data, &mut offset, usize::from(name_len))?;
+            let name = std::str::from_utf8(name)
+                .map_err(|_| CodecError::InvalidEntryName(String::from_utf8_lossy(name).into()))?;
+
+            let len = u64::from_le_bytes(read_array(data, &mut offset)?);
+            let len = usize::try_from(len).map_err(|_| CodecError::TruncatedFrame)?;
+            let start = offset;
+            read_slice(data, &mut offset, len)?;
+
+            if entries.insert(name, start..offset).is_some() {
+                return Err(CodecError::DuplicateEntry(name.to_string()));
+            }
+        }
+
+        Ok(Self {
+            codec,
+            data,
+            entries,
+        })
+    }
+
+    /// Returns the decompressed payload of the entry called `name`, or `None` if there is no such entry.
+    ///
+    /// # Errors
+    ///
+    /// Return `epoch_archive::CodecError` if there is an issue decompressing the payload.
+    pub fn get(&self, name: &str) -> Result<Option<Vec<u8>>> {
+        self.entries
+            .get(name)
+            .map(|range| self.codec.decompress(&self.data[range.clone()]))
+            .transpose()
+    }
+
+    /// Returns the names of all entries, in no particular order.
+    pub fn names(&self) -> impl Iterator<Item = &'a str> + '_ {
+        self.entries.keys().copied()
+    }
+
+    /// Returns the number of entries in the archive.
+    #[must_use]
+    pub fn len(&self) -> usize {
+        self.entries.len()
+    }
+
+    /// Returns true if the archive has no entries.
+    #[must_use]
+    pub fn is_empty(&self) -> bool {
+        self.entries.is_empty()
+    }
 }
 
-impl Archive {
+/// Reads the next `len` bytes of `data`, advancing `offset` past them.
+fn read_slice<'a>(data: &'a [u8], offset: &mut usize, len: usize) -> Result<&'a [u8]> {
+    let end = offset.checked_add(len).ok_or(CodecError::TruncatedFrame)?;
+    let slice = data.get(*offset..end).ok_or(CodecError::TruncatedFrame)?;
+    *offset = end;
 
-}
\ No newline at end of file
+    Ok(slice)
+}
+
+/// Reads the next `N` bytes of `data` as an array, advancing `offset` past them.
+fn read_array<const N: usize>(data: &[u8], offset: &mut usize) -> Result<[u8; N]> {
+    let slice = read_slice(data, offset, N)?;
+    Ok(slice.try_into().expect("slice has exactly N bytes"))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_archive_entries_layout() {
+        let codec = Codec::new(1);
+        let archive = codec
+            .archive_entries([("ab", [1, 2, 3].as_slice())].into_iter())
+            .unwrap();
+        let compressed = codec.compress(&[1, 2, 3]).unwrap();
+
+        assert_eq!(archive[..2], [2, 0]);
+        assert_eq!(&archive[2..4], b"ab");
+        assert_eq!(archive[4..12], (compressed.len() as u64).to_le_bytes());
+        assert_eq!(archive[12..], compressed);
+    }
+
+    #[test]
+    fn test_archive_entries_duplicate() {
+        let codec = Codec::new(1);
+        let entries = [
+            ("a", [1].as_slice()),
+            ("b", [2].as_slice()),
+            ("a", [3].as_slice()),
+        ];
+
+        let archive = codec.archive_entries(entries.into_iter());
+        assert!(matches!(archive, Err(CodecError::DuplicateEntry(name)) if name == "a"));
+    }
+
+    #[test]
+    fn test_archive_entries_name_too_long() {
+        let codec = Codec::new(1);
+        let name = "a".repeat(usize::from(u16::MAX) + 1);
+
+        let archive = codec.archive_entries([(name.as_str(), [1].as_slice())].into_iter());
+        assert!(matches!(archive, Err(CodecError::InvalidEntryName(_))));
+    }
+
+    #[test]
+    fn test_archive_reader_empty() {
+        let codec = Codec::new(1);
+        let archive = codec.archive_entries(std::iter::empty()).unwrap();
+
+        let reader = ArchiveReader::new(&codec, &archive).unwrap();
+        assert!(reader.is_empty());
+        assert_eq!(reader.get("a").unwrap(), None);
+    }
+
+    #[test]
+    fn test_archive_reader_truncated() {
+        let codec = Codec::new(1);
+        let archive = codec
+            .archive_entries([("a", [1, 2, 3].as_slice())].into_iter())
+            .unwrap();
+
+        for len in 1..archive.len() {
+            let reader = ArchiveReader::new(&codec, &archive[..len]);
+            assert!(matches!(reader, Err(CodecError::TruncatedFrame)));
+        }
+    }
+
+    #[test]
+    fn test_archive_reader_invalid_name() {
+        let codec = Codec::new(1);
+        let archive = [1, 0, 0xFF, 0, 0, 0, 0, 0, 0, 0, 0];
+
+        let reader = ArchiveReader::new(&codec, &archive);
+        assert!(matches!(reader, Err(CodecError::InvalidEntryName(_))));
+    }
+}
@@ -0,0 +1,144 @@
+use crate::{Epoch, EpochError};
+
+use std::time::{Instant, SystemTime};
+
+/// A wall clock calibrated from a single correlated ([`Epoch`], `Instant`) pair, so events
+/// measured with the monotonic `Instant` can be stamped with wall-clock epochs.
+///
+/// The calibration is taken once and applied to every instant afterwards, so stamped epochs
+/// follow the monotonic clock: they never go backwards, and do not follow later adjustments of
+/// the system time.
+///
+/// # Examples
+///
+/// ```
+/// use epoch_archive::{Clock, Epoch};
+/// use std::time::{Duration, Instant};
+///
+/// let start = Instant::now();
+/// let clock = Clock::new(Epoch::new(1_700_000_000), start);
+///
+/// let stamped = clock.epoch_at(start + Duration::from_millis(1_500));
+/// assert!(stamped.value_eq(&Epoch::new(1_700_000_001).with_millis(500)));
+/// ```
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Clock {
+    epoch: Epoch,
+    instant: Instant,
+}
+
+impl Clock {
+    /// Creates a clock on which `instant` happened at `epoch`.
+    #[must_use]
+    pub fn new(epoch: Epoch, instant: Instant) -> Self {
+        Self { epoch, instant }
+    }
+
+    /// Creates a clock calibrated against the current system time.
+    ///
+    /// # Errors
+    ///
+    /// Return `epoch_archive::EpochError::Overflow` if the system time does not fit in an
+    /// [`Epoch`].
+    pub fn calibrate() -> Result<Self, EpochError> {
+        let instant = Instant::now();
+        let epoch = Epoch::try_from(SystemTime::now())?;
+
+        Ok(Self::new(epoch, instant))
+    }
+
+    /// Returns the epoch the calibration pair was taken at.
+    #[must_use]
+    pub fn epoch(&self) -> &Epoch {
+        &self.epoch
+    }
+
+    /// Returns the instant the calibration pair was taken at.
+    #[must_use]
+    pub fn instant(&self) -> Instant {
+        self.instant
+    }
+
+    /// Returns the wall-clock epoch `instant` happened at, with `SubSecond::Nano` precision.
+    /// Instants before the calibration are supported as well. The result is clamped to the
+    /// representable range if it falls outside of it.
+    #[must_use]
+    pub fn epoch_at(&self, instant: Instant) -> Epoch {
+        let base = self.epoch.as_nanos();
+        let total = match instant.checked_duration_since(self.instant) {
+            Some(elapsed) => base.saturating_add_unsigned(elapsed.as_nanos()),
+            None => base.saturating_sub_unsigned((self.instant - instant).as_nanos()),
+        };
+
+        Epoch::saturating_from_nanos(total)
+    }
+
+    /// Returns the wall-clock epoch of the current instant.
+    #[must_use]
+    pub fn now_epoch(&self) -> Epoch {
+        self.epoch_at(Instant::now())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use std::time::Duration;
+
+    #[test]
+    fn test_epoch_at() {
+        let start = Instant::now();
+        let clock = Clock::new(Epoch::new(1_700_000_000).with_millis(250), start);
+
+        let expected = [
+            (
+                Duration::ZERO,
+                Epoch::new(1_700_000_000).with_nanos(250_000_000),
+            ),
+            (
+                Duration::from_nanos(1),
+                Epoch::new(1_700_000_000).with_nanos(250_000_001),
+            ),
+            (
+                Duration::from_millis(750),
+                Epoch::new(1_700_000_001).with_nanos(0),
+            ),
+            (
+                Duration::from_secs(7200),
+                Epoch::new(1_700_007_200).with_nanos(250_000_000),
+            ),
+        ];
+        for (elapsed, epoch) in expected {
+            assert_eq!(clock.epoch_at(start + elapsed), epoch, "{elapsed:?}");
+        }
+
+        let later = Clock::new(Epoch::new(0), start + Duration::from_millis(1_500));
+        assert_eq!(
+            later.epoch_at(start),
+            Epoch::new(-2).with_nanos(500_000_000)
+        );
+    }
+
+    #[test]
+    fn test_epoch_at_monotonic() {
+        let clock = Clock::calibrate().unwrap();
+
+        let mut previous = clock.epoch_at(clock.instant());
+        assert!(previous.value_eq(clock.epoch()));
+        for _ in 0..1_000 {
+            let stamped = clock.now_epoch();
+            assert!(stamped.total_cmp(&previous).is_ge());
+            previous = stamped;
+        }
+
+        let stamped: Vec<_> = (1..=10u64)
+            .map(|i| clock.epoch_at(clock.instant() + Duration::from_micros(i)))
+            .collect();
+        assert!(
+            stamped
+                .windows(2)
+                .all(|pair| pair[0].total_cmp(&pair[1]).is_lt())
+        );
+    }
+}
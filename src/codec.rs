@@ -1,12 +1,158 @@
-use crate::CodecError;
+use crate::msgpack::{check_collection_lengths, sort_maps};
+use crate::{CodecError, Epoch, Precision};
 
+use serde::de::{DeserializeSeed, Deserializer as _, SeqAccess, Visitor};
 use serde::{Deserialize, Serialize};
+use std::io::{BufRead, BufReader, Read, Write};
+use std::marker::PhantomData;
+#[cfg(feature = "sha256")]
+use std::path::{Path, PathBuf};
+use std::sync::Arc;
+use std::time::{Duration, Instant};
+use zstd::dict::{DecoderDictionary, EncoderDictionary};
 
 type Result<T, E = CodecError> = std::result::Result<T, E>;
 
-#[derive(Debug, Clone, PartialEq, Eq, PartialOrd, Ord)]
+#[cfg(feature = "xxhash")]
+const DIGEST_SEED: u64 = 0;
+
+/// The compression level used by `Codec::default`.
+pub(crate) const DEFAULT_LEVEL: i32 = 9;
+
+/// The algorithm id written by [`Codec::with_algorithm_tag`] for zstd frames.
+const ZSTD_ALGORITHM_ID: u8 = 0;
+
+/// The length of the checksum written by [`Codec::with_checksum`] at the end of every frame.
+const CHECKSUM_LEN: usize = 8;
+
+/// The magic bytes written by [`Codec::with_header`] at the start of every frame.
+const HEADER_MAGIC: [u8; 4] = *b"EPAR";
+
+/// The version of the frame layout written by [`Codec::with_header`] after the magic bytes.
+const HEADER_VERSION: u8 = 1;
+
+/// The header followed by the algorithm tag, as written in front of a frame when both are enabled.
+const FRAME_PREFIX: [u8; 6] = [
+    HEADER_MAGIC[0],
+    HEADER_MAGIC[1],
+    HEADER_MAGIC[2],
+    HEADER_MAGIC[3],
+    HEADER_VERSION,
+    ZSTD_ALGORITHM_ID,
+];
+
+/// How many leading bytes of the input are included in `CodecError::DecodeContext`.
+const DECODE_PREVIEW_LEN: usize = 16;
+
+/// The length of every epoch written by [`Codec::encode_epochs`], a little-endian `i128`.
+const EPOCH_NANOS_LEN: usize = 16;
+
+/// The length of the header written by [`Codec::encode_stamped`].
+const STAMP_HEADER_LEN: usize = 13;
+
+/// How many serialized bytes [`Codec::encode_within_budget`] compresses to estimate the time
+/// the whole payload takes.
+const BUDGET_SAMPLE_LEN: usize = 16 * 1024;
+
+/// The faster levels [`Codec::encode_within_budget`] falls back to, fastest last.
+const BUDGET_FALLBACK_LEVELS: [i32; 2] = [3, 1];
+
+/// The length of the header written by [`Codec::encode_paged`] at the start of every page.
+const PAGE_HEADER_LEN: usize = 9;
+
+/// The longest LEB128 varint a `u64` can be written as.
+const MAX_VARINT_LEN: usize = 10;
+
+/// The longest LEB128 varint a `u128` can be written as.
+const MAX_VARINT_128_LEN: usize = 19;
+
+/// The precisions [`Codec::encode_epochs_delta`] and [`Codec::encode_stamped`] tag epochs with,
+/// at the index of their tag byte.
+const DELTA_PRECISIONS: [Precision; 4] = [
+    Precision::Second,
+    Precision::Milli,
+    Precision::Micro,
+    Precision::Nano,
+];
+
+/// The `MessagePack` extension type reserved for timestamps, `-1` as a byte.
+const TIMESTAMP_EXT_TYPE: u8 = 0xff;
+
+/// The serialization format a [`Codec`] writes before compressing, selected with
+/// [`Codec::with_format`].
+///
+/// `MessagePack` is always available and the default. JSON and CBOR are behind the `json` and
+/// `cbor` features.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Default, Serialize, Deserialize)]
+pub enum Format {
+    /// `MessagePack`, through `rmp_serde`.
+    #[default]
+    MessagePack,
+    /// JSON, through `serde_json`.
+    #[cfg(feature = "json")]
+    Json,
+    /// CBOR, through `ciborium`.
+    #[cfg(feature = "cbor")]
+    Cbor,
+}
+
+/// Serializes data with `MessagePack`, or another [`Format`], and compresses it with zstd.
+///
+/// # Evolving enums
+///
+/// `MessagePack` writes enum variants by name, so reordering variants is safe but renaming one
+/// breaks every archive written before. For enums whose variants change over time, pin a short
+/// stable tag to every variant and tag them adjacently:
+///
+/// ```
+/// use epoch_archive::Codec;
+/// use serde::{Deserialize, Serialize};
+///
+/// #[derive(Debug, PartialEq, Serialize, Deserialize)]
+/// #[serde(tag = "t", content = "c")]
+/// enum Event {
+///     #[serde(rename = "0")]
+///     Start(i64),
+///     #[serde(rename = "1")]
+///     Stop(u32),
+/// }
+///
+/// let codec = Codec::default();
+/// let encoded = codec.encode(&Event::Stop(7)).unwrap();
+/// assert_eq!(codec.decode::<Event>(&encoded).unwrap(), Event::Stop(7));
+/// ```
+///
+/// Each value is then written as a two element array of the tag and the variant's data, and
+/// the Rust names can change freely as long as the tags stay. New variants get new tags, older
+/// archives keep decoding, but older code fails on the new tags. Give every variant its data as
+/// a newtype or tuple variant: unit and struct variants do not round-trip through adjacent
+/// tagging with `MessagePack` arrays.
+///
+/// # Persisting settings
+///
+/// A codec serializes as its [`CodecBuilder`], so its level and options can be stored in a
+/// config file. Deserializing goes through [`CodecBuilder::build`] and rejects an out of range
+/// level. Missing options keep their defaults, and a dictionary is stored as its raw bytes.
+///
+/// ```
+/// use epoch_archive::Codec;
+///
+/// let codec = Codec::new(3).with_header(true);
+/// let config = rmp_serde::to_vec(&codec).unwrap();
+/// assert_eq!(rmp_serde::from_slice::<Codec>(&config).unwrap(), codec);
+/// ```
+#[derive(Debug, Clone, PartialEq, Eq, PartialOrd, Ord, Serialize, Deserialize)]
+#[serde(into = "CodecBuilder", try_from = "CodecBuilder")]
+#[allow(clippy::struct_excessive_bools)]
 pub struct Codec {
     level: i32,
+    algorithm_tag: bool,
+    header: bool,
+    checksum: bool,
+    sorted_maps: bool,
+    format: Format,
+    workers: u32,
+    dictionary: Option<Dictionary>,
 }
 
 impl Codec {
@@ -14,38 +160,398 @@ impl Codec {
     ///
     /// # Arguments
     ///
-    /// * `level` - The level of compression to use. 0 is no compression, 1 is fastest, 22 is slowest.
-    ///   Check the [zstd documentation](https://github.com/facebook/zstd) for more information.
+    /// * `level` - The level of compression to use. 1 is fastest, 22 is slowest, and 0 selects
+    ///   zstd's own default. Negative levels, down to the minimum reported by
+    ///   `zstd::compression_level_range`, are faster still and give up compression ratio for
+    ///   throughput. Check the [zstd documentation](https://github.com/facebook/zstd) for more information.
     ///   Defaults to `9`, which in my testing of 30mb json files was a compromise between speed/compression.
     ///
     /// # Panics
     ///
-    /// This function will panic if the compression level is outside the range 0-22.
+    /// This function will panic if the compression level is outside the range zstd supports, see
+    /// [`Codec::try_new`].
     #[must_use]
     pub fn new(level: i32) -> Self {
-        assert!(level <= 22, "level should be >= 0 and <= 22");
-        Self { level }
+        Self::try_new(level).unwrap()
+    }
+
+    /// Creates a new Codec struct like [`Codec::new`], but returns an error instead of panicking
+    /// when the level is out of range, for levels read from a config file or the command line.
+    ///
+    /// # Errors
+    ///
+    /// Return `epoch_archive::CodecError::InvalidLevel` if `level` is outside the range reported
+    /// by `zstd::compression_level_range`.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use epoch_archive::Codec;
+    ///
+    /// assert!(Codec::try_new(3).is_ok());
+    /// assert!(Codec::try_new(23).is_err());
+    /// ```
+    pub fn try_new(level: i32) -> Result<Self> {
+        if !zstd::compression_level_range().contains(&level) {
+            return Err(CodecError::InvalidLevel(level));
+        }
+
+        Ok(Self {
+            level,
+            algorithm_tag: false,
+            header: false,
+            checksum: false,
+            sorted_maps: false,
+            format: Format::MessagePack,
+            workers: 0,
+            dictionary: None,
+        })
+    }
+
+    /// Returns a [`CodecBuilder`] to configure every option of a codec in one place, starting
+    /// from the defaults of [`Codec::default`].
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use epoch_archive::{Codec, Format};
+    ///
+    /// let codec = Codec::builder()
+    ///     .level(3)
+    ///     .format(Format::MessagePack)
+    ///     .header(true)
+    ///     .build()
+    ///     .unwrap();
+    /// let encoded = codec.encode(&"built").unwrap();
+    /// assert_eq!(codec.decode::<String>(&encoded).unwrap(), "built");
+    /// ```
+    #[must_use]
+    pub fn builder() -> CodecBuilder {
+        CodecBuilder::default()
+    }
+
+    /// Creates a new Codec that spreads compression across `workers` zstd worker threads, which
+    /// pays off for payloads of several MiB. With 0 or 1 workers it compresses on the calling
+    /// thread, the same as [`Codec::new`].
+    ///
+    /// zstd splits the input into jobs of at least a few hundred KiB, so small payloads still
+    /// compress on a single worker. The frames decompress like any other.
+    ///
+    /// # Panics
+    ///
+    /// This function will panic if the compression level is outside the range zstd supports, see
+    /// [`Codec::try_new`].
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use epoch_archive::Codec;
+    ///
+    /// let codec = Codec::with_workers(3, 4);
+    /// let encoded = codec.encode(&vec![7u64; 100_000]).unwrap();
+    /// assert_eq!(codec.decode::<Vec<u64>>(&encoded).unwrap(), vec![7u64; 100_000]);
+    /// ```
+    #[cfg(feature = "zstdmt")]
+    #[must_use]
+    pub fn with_workers(level: i32, workers: u32) -> Self {
+        Self {
+            workers,
+            ..Self::new(level)
+        }
+    }
+
+    /// Creates a new Codec that compresses and decompresses with a zstd dictionary.
+    ///
+    /// The dictionary is digested into zstd's compression and decompression forms once, here,
+    /// and shared by every call and every clone of the codec afterwards. The same dictionary has
+    /// to be used to decompress the frames again.
+    ///
+    /// # Arguments
+    ///
+    /// * `level` - The level of compression to use, see [`Codec::new`].
+    /// * `dictionary` - The raw dictionary, e.g. trained with `zstd::dict::from_samples`.
+    ///
+    /// # Panics
+    ///
+    /// This function will panic if the compression level is outside the range zstd supports.
+    #[must_use]
+    pub fn with_dictionary_ref(level: i32, dictionary: Arc<[u8]>) -> Self {
+        Self {
+            dictionary: Some(Dictionary::new(dictionary, level)),
+            ..Self::new(level)
+        }
+    }
+
+    /// Creates a new Codec that compresses and decompresses with a zstd dictionary, like
+    /// [`Codec::with_dictionary_ref`] but taking ownership of the dictionary bytes.
+    ///
+    /// # Panics
+    ///
+    /// This function will panic if the compression level is outside the range zstd supports.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use epoch_archive::Codec;
+    ///
+    /// let samples: Vec<Vec<u8>> = (0..1000u32)
+    ///     .map(|i| Codec::serialize(&(i, "sensor-reading", i % 7)).unwrap())
+    ///     .collect();
+    /// let samples: Vec<&[u8]> = samples.iter().map(Vec::as_slice).collect();
+    /// let dictionary = Codec::train_dictionary(&samples, 4096).unwrap();
+    ///
+    /// let codec = Codec::with_dictionary(3, dictionary);
+    /// let encoded = codec.encode(&(7u32, "sensor-reading", 0u32)).unwrap();
+    /// assert_eq!(codec.decode::<(u32, String, u32)>(&encoded).unwrap().0, 7);
+    /// ```
+    #[must_use]
+    pub fn with_dictionary(level: i32, dictionary: Vec<u8>) -> Self {
+        Self::with_dictionary_ref(level, dictionary.into())
+    }
+
+    /// Trains a zstd dictionary of at most `max_size` bytes from sample records, to be used with
+    /// [`Codec::with_dictionary`]. The samples should be serialized the way they will be encoded,
+    /// e.g. with [`Codec::serialize`].
+    ///
+    /// zstd needs a reasonable number of samples to train from, a few hundred at least.
+    ///
+    /// # Errors
+    ///
+    /// Return `epoch_archive::CodecError::IOError` if zstd fails to train a dictionary, for
+    /// example because there are too few samples.
+    pub fn train_dictionary(samples: &[&[u8]], max_size: usize) -> Result<Vec<u8>> {
+        Ok(zstd::dict::from_samples(samples, max_size)?)
+    }
+
+    /// Prefixes every compressed frame with a one byte algorithm id, so frames compressed by
+    /// other algorithms in the future can be told apart. Disabled by default, which produces
+    /// plain zstd frames.
+    ///
+    /// A codec with the tag enabled can only decompress tagged frames.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use epoch_archive::Codec;
+    ///
+    /// let codec = Codec::default().with_algorithm_tag(true);
+    /// let encoded = codec.encode(&"tagged").unwrap();
+    /// assert_eq!(codec.decode::<String>(&encoded).unwrap(), "tagged");
+    /// ```
+    #[must_use]
+    pub fn with_algorithm_tag(self, algorithm_tag: bool) -> Self {
+        Self {
+            algorithm_tag,
+            ..self
+        }
+    }
+
+    /// Prefixes every compressed frame with the magic bytes `EPAR` and a version byte, so archives
+    /// written with a different frame layout are rejected instead of silently misread. Disabled
+    /// by default, which produces plain zstd frames. The header comes before the algorithm tag
+    /// when both are enabled.
+    ///
+    /// A codec with the header enabled can only decompress frames that start with it.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use epoch_archive::{Codec, CodecError};
+    ///
+    /// let codec = Codec::default().with_header(true);
+    /// let encoded = codec.encode(&"versioned").unwrap();
+    /// assert!(encoded.starts_with(b"EPAR"));
+    /// assert_eq!(codec.decode::<String>(&encoded).unwrap(), "versioned");
+    ///
+    /// let plain = Codec::default().encode(&"versioned").unwrap();
+    /// assert!(matches!(codec.decode::<String>(&plain), Err(CodecError::UnknownFormat)));
+    /// ```
+    #[must_use]
+    pub fn with_header(self, header: bool) -> Self {
+        Self { header, ..self }
+    }
+
+    /// Appends an xxHash64 checksum of the compressed frame to every frame, so corruption in
+    /// storage is caught on decompression even when the bytes still decompress. Disabled by
+    /// default, which produces plain zstd frames.
+    ///
+    /// The checksum covers everything written before it, the header and algorithm tag included,
+    /// and is stored as 8 little-endian bytes. A codec with the checksum enabled can only
+    /// decompress frames that end with one.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use epoch_archive::{Codec, CodecError};
+    ///
+    /// let codec = Codec::default().with_checksum(true);
+    /// let mut encoded = codec.encode(&"checked").unwrap();
+    /// assert_eq!(codec.decode::<String>(&encoded).unwrap(), "checked");
+    ///
+    /// encoded[4] ^= 1;
+    /// assert!(matches!(
+    ///     codec.decode::<String>(&encoded),
+    ///     Err(CodecError::ChecksumMismatch { .. })
+    /// ));
+    /// ```
+    #[cfg(feature = "xxhash")]
+    #[must_use]
+    pub fn with_checksum(self, checksum: bool) -> Self {
+        Self { checksum, ..self }
+    }
+
+    /// Writes the entries of every map sorted by their encoded keys, so logically equal maps
+    /// always produce the same bytes, whatever order a `HashMap` happens to iterate in. Disabled
+    /// by default, which writes entries in iteration order.
+    ///
+    /// Sorting rewrites the serialized bytes once more before compressing them: every map entry
+    /// is copied into its own buffer and sorted, so expect roughly one extra allocation per map
+    /// entry and an extra pass over the payload. Values without maps are only copied.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use epoch_archive::Codec;
+    /// use std::collections::HashMap;
+    ///
+    /// let codec = Codec::default().with_sorted_maps(true);
+    /// let map: HashMap<u32, &str> = [(2, "b"), (1, "a")].into();
+    /// let reversed: HashMap<u32, &str> = [(1, "a"), (2, "b")].into();
+    /// assert_eq!(codec.encode(&map).unwrap(), codec.encode(&reversed).unwrap());
+    /// ```
+    #[must_use]
+    pub fn with_sorted_maps(self, sorted_maps: bool) -> Self {
+        Self {
+            sorted_maps,
+            ..self
+        }
+    }
+
+    /// Serializes the data with another [`Format`] instead of `MessagePack`. Data has to be
+    /// decoded with the same format it was encoded with, the frame does not record it.
+    ///
+    /// The format applies to [`Codec::encode`], [`Codec::decode`] and the methods built on
+    /// them, such as the paged and length-prefixed ones. The associated [`Codec::serialize`] and
+    /// the timestamp extension methods always write `MessagePack`. The chunked, continuous,
+    /// sequence visiting and seed methods only support `MessagePack` and return
+    /// `epoch_archive::CodecError::UnsupportedFormat` for any other format.
+    /// [`Codec::with_sorted_maps`] and the limit of [`Codec::decode_bounded`] only apply to
+    /// `MessagePack`.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use epoch_archive::{Codec, Format};
+    ///
+    /// let codec = Codec::new(3).with_format(Format::MessagePack);
+    /// let encoded = codec.encode(&vec![1u32, 2, 3]).unwrap();
+    /// assert_eq!(codec.decode::<Vec<u32>>(&encoded).unwrap(), [1, 2, 3]);
+    /// ```
+    #[must_use]
+    pub fn with_format(self, format: Format) -> Self {
+        Self { format, ..self }
     }
 
-    /// Serializes and compresses the provided data using the `MessagePack` format.
+    /// Returns the compression level the codec was created with.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use epoch_archive::Codec;
+    ///
+    /// assert_eq!(Codec::new(7).level(), 7);
+    /// ```
+    #[must_use]
+    pub fn level(&self) -> i32 {
+        self.level
+    }
+
+    /// Returns the serialization format, [`Format::MessagePack`] unless set with
+    /// [`Codec::with_format`].
+    #[must_use]
+    pub fn format(&self) -> Format {
+        self.format
+    }
+
+    /// Serializes the provided data with the configured [`Format`], `MessagePack` by default, and
+    /// compresses it.
     /// This will reduce the size of the data and make it easier to compress.
     /// From testing I found that a level of 1 was a good balance between compression and size.
     /// The average reduction is around 85% of the original, whilst being slightly faster to compress
     /// and decompress.
     ///
+    /// `None` is written as the format's null value (nil in `MessagePack`, `null` in JSON and
+    /// CBOR), so it is never confused with an empty payload. Nested options collapse though:
+    /// `Some(None)` is also written as null and decodes as `None`.
+    ///
     /// # Errors
     ///
     /// Return `epoch_archive::CodecError` if there is an issue serializing or compressing the data.
     pub fn encode<T: Serialize>(&self, data: &T) -> Result<Vec<u8>> {
-        let serialized = Self::serialize(data)?;
-        self.compress(&serialized)
+        self.encode_with_stats(data).map(|(frame, _)| frame)
+    }
+
+    /// Encodes the provided data like [`Codec::encode`], also returning how well it compressed.
+    ///
+    /// # Errors
+    ///
+    /// Return `epoch_archive::CodecError` if there is an issue serializing or compressing the data.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use epoch_archive::Codec;
+    ///
+    /// let codec = Codec::default();
+    /// let (frame, stats) = codec.encode_with_stats(&vec![1u32; 1000]).unwrap();
+    ///
+    /// assert_eq!(stats.compressed_len(), frame.len());
+    /// assert!(stats.ratio() > 1.0);
+    /// ```
+    pub fn encode_with_stats<T: Serialize>(&self, data: &T) -> Result<(Vec<u8>, CompressionStats)> {
+        let serialized = self.serialize_format(data)?;
+        let frame = self.compress(&serialized)?;
+        let stats = CompressionStats {
+            serialized_len: serialized.len(),
+            compressed_len: frame.len(),
+        };
+
+        Ok((frame, stats))
+    }
+
+    /// Encodes the provided data like [`Codec::encode`], returning the frame as an `Arc<[u8]>`
+    /// that can be cloned cheaply and handed to many consumers.
+    ///
+    /// The shared frame derefs to `&[u8]`, so it can be passed straight to [`Codec::decode`].
+    ///
+    /// # Errors
+    ///
+    /// Return `epoch_archive::CodecError` if there is an issue serializing or compressing the data.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use epoch_archive::Codec;
+    ///
+    /// let codec = Codec::default();
+    /// let frame = codec.encode_shared(&42u32).unwrap();
+    ///
+    /// let consumer = frame.clone();
+    /// assert_eq!(codec.decode::<u32>(&consumer).unwrap(), 42);
+    /// ```
+    pub fn encode_shared<T: Serialize>(&self, data: &T) -> Result<Arc<[u8]>> {
+        Ok(self.encode(data)?.into())
     }
 
-    /// Deserializes and decompresses the provided data using the `MessagePack` format.
+    /// Decompresses the provided data and deserializes it with the configured [`Format`],
+    /// `MessagePack` by default.
     ///
     /// # Errors
     ///
-    /// Return `epoch_archive::CodecError` if there is an issue deserializing or decompressing the data.
+    /// Return `epoch_archive::CodecError::EmptyInput` if `data` is empty,
+    /// `epoch_archive::CodecError::UnknownFormat` or `epoch_archive::CodecError::VersionMismatch`
+    /// if [`Codec::with_header`] is enabled and the header is missing or from another version, or
+    /// `epoch_archive::CodecError` if there is an issue deserializing or decompressing the data.
     pub fn decode<T>(&self, data: &[u8]) -> Result<T>
     where
         T: for<'de> Deserialize<'de>,
@@ -56,138 +562,3719 @@ impl Codec {
         Ok(deserialized)
     }
 
-    /// Compresses the provided data using the zstd algorithm.
+    /// Encodes the provided data and writes it to `writer` as a length-delimited message: the
+    /// frame length as an unsigned LEB128 varint, followed by the frame, the same framing as
+    /// protobuf's `writeDelimitedTo`.
     ///
-    /// # Arguments
+    /// # Errors
     ///
-    /// * `data` - The data to be compressed.
+    /// Return `epoch_archive::CodecError` if there is an issue encoding the data or writing it.
     ///
-    /// # Errors
+    /// # Examples
     ///
-    /// Return `epoch_archive::CodecError` if there is an issue compressing the data.
-    pub fn compress(&self, data: &[u8]) -> Result<Vec<u8>> {
-        Ok(zstd::encode_all(data, self.level)?)
-    }
-
-    /// Decompresses the provided data using the zstd algorithm.
+    /// ```
+    /// use epoch_archive::Codec;
     ///
-    /// # Arguments
+    /// let codec = Codec::default();
+    /// let mut stream = Vec::new();
+    /// codec.encode_delimited(&"first", &mut stream).unwrap();
+    /// codec.encode_delimited(&"second", &mut stream).unwrap();
     ///
-    /// * `data` - The data to be decompressed.
+    /// let mut reader = stream.as_slice();
+    /// assert_eq!(codec.decode_delimited::<String, _>(&mut reader).unwrap(), "first");
+    /// assert_eq!(codec.decode_delimited::<String, _>(&mut reader).unwrap(), "second");
+    /// ```
+    #[allow(clippy::cast_possible_truncation)]
+    pub fn encode_delimited<T, W>(&self, data: &T, mut writer: W) -> Result<()>
+    where
+        T: Serialize,
+        W: Write,
+    {
+        let frame = self.encode(data)?;
+
+        let mut len = frame.len() as u64;
+        let mut varint = Vec::with_capacity(MAX_VARINT_LEN);
+        while len >= 0x80 {
+            varint.push((len as u8) | 0x80);
+            len >>= 7;
+        }
+        varint.push(len as u8);
+
+        writer.write_all(&varint)?;
+        writer.write_all(&frame)?;
+
+        Ok(())
+    }
+
+    /// Reads one length-delimited message written by [`Codec::encode_delimited`] from `reader`,
+    /// leaving the reader positioned at the start of the next message.
     ///
     /// # Errors
     ///
-    /// Return `epoch_archive::CodecError` if there is an issue decompressing the data.
-    #[allow(clippy::unused_self)]
-    pub fn decompress(&self, data: &[u8]) -> Result<Vec<u8>> {
-        Ok(zstd::decode_all(data)?)
+    /// Return `epoch_archive::CodecError::InvalidVarint` if the length prefix is longer than 10
+    /// bytes or does not fit in a `usize`, or `epoch_archive::CodecError` if there is an issue
+    /// reading, decompressing or deserializing the message. Running out of input gives an
+    /// `std::io::ErrorKind::UnexpectedEof` I/O error.
+    pub fn decode_delimited<T, R>(&self, mut reader: R) -> Result<T>
+    where
+        T: for<'de> Deserialize<'de>,
+        R: Read,
+    {
+        let mut len = 0u64;
+        for shift in (0..MAX_VARINT_LEN * 7).step_by(7) {
+            let mut byte = [0];
+            reader.read_exact(&mut byte)?;
+
+            let bits = u64::from(byte[0] & 0x7f);
+            if shift == 63 && bits > 1 {
+                return Err(CodecError::InvalidVarint);
+            }
+            len |= bits << shift;
+
+            if byte[0] & 0x80 == 0 {
+                let len = usize::try_from(len).map_err(|_| CodecError::InvalidVarint)?;
+
+                // Grow with the input rather than trusting the prefix with one large allocation.
+                let mut frame = Vec::new();
+                reader.take(len as u64).read_to_end(&mut frame)?;
+                if frame.len() != len {
+                    return Err(std::io::Error::from(std::io::ErrorKind::UnexpectedEof).into());
+                }
+
+                return self.decode(&frame);
+            }
+        }
+
+        Err(CodecError::InvalidVarint)
     }
 
-    /// Serializes the provided data using the `MessagePack` format.
+    /// Encodes every item with [`Codec::encode`] into one buffer of independently decodable
+    /// frames, each prefixed with its length as a little-endian `u64`.
+    ///
+    /// The fixed-width prefix means a frame cut short is always detected, where a partial varint
+    /// could still be read as a shorter length.
     ///
     /// # Errors
     ///
-    /// Return `epoch_archive::CodecError` if there is an issue serializing the data.
-    pub fn serialize<T: Serialize>(data: &T) -> Result<Vec<u8>> {
-        let mut buf = Vec::new();
-        let mut ser = rmp_serde::Serializer::new(&mut buf);
-        data.serialize(&mut ser)?;
+    /// Return `epoch_archive::CodecError` if there is an issue serializing or compressing any of
+    /// the items.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use epoch_archive::Codec;
+    ///
+    /// let codec = Codec::default();
+    /// let frames = codec.encode_frames(&["first", "second"]).unwrap();
+    /// assert_eq!(codec.decode_frames::<String>(&frames).unwrap(), ["first", "second"]);
+    /// ```
+    pub fn encode_frames<T: Serialize>(&self, items: &[T]) -> Result<Vec<u8>> {
+        let mut frames = Vec::new();
+        for item in items {
+            let frame = self.encode(item)?;
+            frames.extend((frame.len() as u64).to_le_bytes());
+            frames.extend(frame);
+        }
 
-        Ok(buf)
+        Ok(frames)
     }
 
-    /// Deserializes the provided data using the `MessagePack` format.
+    /// Decodes every frame written by [`Codec::encode_frames`], returning the items in their
+    /// original order.
     ///
     /// # Errors
     ///
-    /// Return `rmp_serde::decode::Error` if there is an issue deserializing the data.
-    #[allow(clippy::unused_self)]
-    pub fn deserialize<'a, T>(&self, data: &'a [u8]) -> Result<T>
+    /// Return `epoch_archive::CodecError::TruncatedFrame` if `data` ends in the middle of a length
+    /// prefix or a frame, or `epoch_archive::CodecError` if there is an issue decompressing or
+    /// deserializing any of the frames.
+    pub fn decode_frames<T>(&self, data: &[u8]) -> Result<Vec<T>>
     where
-        T: Deserialize<'a>,
+        T: for<'de> Deserialize<'de>,
     {
-        Ok(rmp_serde::from_slice(data)?)
+        self.decode_frame_iter(data).collect()
     }
-}
 
-impl Default for Codec {
-    fn default() -> Self {
-        Self { level: 9 }
-    }
-}
+    /// Lazily decodes the frames written by [`Codec::encode_frames`], one item per frame as the
+    /// iterator is advanced, so only the item being handled is held in memory.
+    ///
+    /// An error is yielded in the position of the frame that failed, after which the iterator is
+    /// fused: a bad length prefix leaves no way to find the next frame, so every later frame is
+    /// skipped as well.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use epoch_archive::Codec;
+    ///
+    /// let codec = Codec::default();
+    /// let frames = codec.encode_frames(&[1u32, 2, 3]).unwrap();
+    ///
+    /// let mut items = codec.decode_frame_iter::<u32>(&frames);
+    /// assert_eq!(items.next().unwrap().unwrap(), 1);
+    /// assert_eq!(items.map(Result::unwrap).sum::<u32>(), 5);
+    /// ```
+    pub fn decode_frame_iter<'a, T>(
+        &'a self,
+        data: &'a [u8],
+    ) -> impl Iterator<Item = Result<T>> + 'a
+    where
+        T: for<'de> Deserialize<'de>,
+    {
+        let mut rest = data;
+        let mut failed = false;
 
-#[cfg(test)]
-mod tests {
-    use super::*;
+        std::iter::from_fn(move || {
+            if failed || rest.is_empty() {
+                return None;
+            }
 
-    #[test]
-    fn test_new() {
-        let codec = Codec::new(3);
-        assert_eq!(codec.level, 3);
+            let item = split_length_prefixed(&mut rest).and_then(|frame| self.decode(frame));
+            failed = item.is_err();
+            Some(item)
+        })
     }
 
-    #[test]
-    fn test_default() {
-        let codec = Codec::default();
-        assert_eq!(codec.level, 9);
-    }
+    /// Decodes the provided data like [`Codec::decode`], but first checks that it decompresses
+    /// to exactly `expected_len` bytes, as a cheap guard against corruption that still yields a
+    /// valid frame.
+    ///
+    /// # Errors
+    ///
+    /// Return `epoch_archive::CodecError::LengthMismatch` if the decompressed length differs from
+    /// `expected_len`, or `epoch_archive::CodecError` if there is an issue decompressing or
+    /// deserializing the data.
+    pub fn decode_expecting_len<T>(&self, data: &[u8], expected_len: usize) -> Result<T>
+    where
+        T: for<'de> Deserialize<'de>,
+    {
+        let decompressed = self.decompress(data)?;
+        if decompressed.len() != expected_len {
+            return Err(CodecError::LengthMismatch {
+                expected: expected_len,
+                got: decompressed.len(),
+            });
+        }
 
-    #[test]
-    #[should_panic(expected = "level should be >= 0 and <= 22")]
-    fn test_new_too_high_level() {
-        #[allow(unused_must_use)]
-        Codec::new(23);
+        self.deserialize(&decompressed)
     }
 
-    #[test]
-    fn test_compress() {
-        let data = vec![1, 2, 3, 4, 5];
+    /// Encodes every item with [`Codec::encode`] in parallel on the rayon thread pool, returning
+    /// the frames in the same order as `items`.
+    ///
+    /// # Errors
+    ///
+    /// Return `epoch_archive::CodecError` if there is an issue serializing or compressing any of
+    /// the items. When several items fail, which of their errors is returned is unspecified.
+    #[cfg(feature = "rayon")]
+    pub fn encode_batch_par<T: Serialize + Sync>(&self, items: &[T]) -> Result<Vec<Vec<u8>>> {
+        use rayon::prelude::*;
 
-        for i in 0..22 {
-            let codec = Codec::new(i);
-            let compressed = codec.compress(&data).unwrap();
-            assert_ne!(data, compressed);
-        }
+        items.par_iter().map(|item| self.encode(item)).collect()
     }
 
-    #[test]
-    fn test_decompress() {
-        let expected = vec![1, 2, 3, 4, 5];
-        let compressed = [40, 181, 47, 253, 0, 72, 41, 0, 0, 1, 2, 3, 4, 5];
-        let codec = Codec::new(1);
-
-        let decompressed = codec.decompress(&compressed).unwrap();
-        assert_eq!(decompressed, expected);
+    /// Encodes the provided data like [`Codec::encode`] on tokio's blocking thread pool, so the
+    /// serialization and compression don't stall the async runtime.
+    ///
+    /// The data is moved to the blocking thread, and the codec is cloned along with it.
+    ///
+    /// # Errors
+    ///
+    /// Return `epoch_archive::CodecError` if there is an issue serializing or compressing the data.
+    ///
+    /// # Panics
+    ///
+    /// This function will panic if it is not called within a tokio runtime, and resumes the panic
+    /// if encoding panicked.
+    #[cfg(feature = "tokio")]
+    pub async fn encode_async<T>(&self, data: T) -> Result<Vec<u8>>
+    where
+        T: Serialize + Send + 'static,
+    {
+        let codec = self.clone();
+        run_blocking(move || codec.encode(&data)).await
     }
 
-    #[test]
+    /// Decodes the provided data like [`Codec::decode`] on tokio's blocking thread pool, so the
+    /// decompression and deserialization don't stall the async runtime.
+    ///
+    /// # Errors
+    ///
+    /// Return `epoch_archive::CodecError::EmptyInput` if `data` is empty, or
+    /// `epoch_archive::CodecError` if there is an issue deserializing or decompressing the data.
+    ///
+    /// # Panics
+    ///
+    /// This function will panic if it is not called within a tokio runtime, and resumes the panic
+    /// if decoding panicked.
+    #[cfg(feature = "tokio")]
+    pub async fn decode_async<T>(&self, data: Vec<u8>) -> Result<T>
+    where
+        T: for<'de> Deserialize<'de> + Send + 'static,
+    {
+        let codec = self.clone();
+        run_blocking(move || codec.decode(&data)).await
+    }
+
+    /// Decodes the provided data like [`Codec::decode`], but first checks that no array or map in
+    /// the `MessagePack` payload declares more than `max_elements` entries.
+    ///
+    /// A payload can declare a huge collection in a few bytes, and serde may preallocate for the
+    /// declared length before noticing the data is missing. The check walks the payload without
+    /// allocating, so such payloads are rejected before any deserialization happens. Payloads in
+    /// any other [`Format`] are decoded without the check.
+    ///
+    /// # Errors
+    ///
+    /// Return `epoch_archive::CodecError::TooManyElements` if a collection is longer than
+    /// `max_elements`, `epoch_archive::CodecError::TruncatedFrame` if the payload ends in the
+    /// middle of a value, or `epoch_archive::CodecError` if there is an issue decompressing or
+    /// deserializing the data.
+    pub fn decode_bounded<T>(&self, data: &[u8], max_elements: usize) -> Result<T>
+    where
+        T: for<'de> Deserialize<'de>,
+    {
+        let decompressed = self.decompress(data)?;
+        if self.format == Format::MessagePack {
+            check_collection_lengths(&decompressed, max_elements)?;
+        }
+
+        self.deserialize(&decompressed)
+    }
+
+    /// Decodes the provided data like [`Codec::decode`], but stops decompressing as soon as the
+    /// output grows past `max_decompressed` bytes.
+    ///
+    /// A few bytes of zstd can expand to gigabytes. The frame is decompressed as a stream and the
+    /// output buffer only grows as bytes come out of it, so at most `max_decompressed + 1` bytes
+    /// are ever held in memory, however large the frame claims or turns out to be.
+    ///
+    /// # Errors
+    ///
+    /// Return `epoch_archive::CodecError::SizeLimitExceeded` if the data decompresses to more
+    /// than `max_decompressed` bytes, `epoch_archive::CodecError::EmptyInput` if `data` is empty,
+    /// or `epoch_archive::CodecError` if there is an issue decompressing or deserializing the
+    /// data.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use epoch_archive::{Codec, CodecError};
+    ///
+    /// let codec = Codec::default();
+    /// let encoded = codec.encode(&vec![0u8; 1_000_000]).unwrap();
+    ///
+    /// let decoded = codec.decode_limited::<Vec<u8>>(&encoded, 1_000);
+    /// assert!(matches!(decoded, Err(CodecError::SizeLimitExceeded { max: 1_000 })));
+    /// assert!(codec.decode_limited::<Vec<u8>>(&encoded, 2_000_000).is_ok());
+    /// ```
+    pub fn decode_limited<T>(&self, data: &[u8], max_decompressed: usize) -> Result<T>
+    where
+        T: for<'de> Deserialize<'de>,
+    {
+        let decompressed = self.decompress_limited(data, max_decompressed)?;
+        self.deserialize(&decompressed)
+    }
+
+    /// Decodes the provided data as `New`, falling back to decoding it as `Old` and converting it
+    /// with `migrate` if that fails, so archives written before a schema change stay readable.
+    ///
+    /// The data is decompressed once for both attempts. Only a failure to deserialize `New`
+    /// triggers the fallback, errors decompressing the data are returned straight away.
+    ///
+    /// # Errors
+    ///
+    /// Return `epoch_archive::CodecError` if there is an issue decompressing the data, or the
+    /// error from deserializing `New` if the data deserializes as neither type.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use epoch_archive::Codec;
+    ///
+    /// let codec = Codec::default();
+    /// let old = codec.encode(&(1u32, 2u32)).unwrap();
+    ///
+    /// let sum = codec.decode_or_migrate::<u64, (u32, u32), _>(&old, |(a, b)| u64::from(a + b));
+    /// assert_eq!(sum.unwrap(), 3);
+    /// ```
+    pub fn decode_or_migrate<New, Old, F>(&self, data: &[u8], migrate: F) -> Result<New>
+    where
+        New: for<'de> Deserialize<'de>,
+        Old: for<'de> Deserialize<'de>,
+        F: FnOnce(Old) -> New,
+    {
+        let decompressed = self.decompress(data)?;
+
+        self.deserialize(&decompressed).or_else(|err| {
+            self.deserialize::<Old>(&decompressed)
+                .map(migrate)
+                .map_err(|_| err)
+        })
+    }
+
+    /// Decompresses the provided data and streams a top-level `MessagePack` array through `f`,
+    /// one element at a time, without collecting the elements into a `Vec`.
+    ///
+    /// The decompressed bytes are still held in memory, but the deserialized elements are not.
+    ///
+    /// # Errors
+    ///
+    /// Return `epoch_archive::CodecError::UnsupportedFormat` if the codec is not configured for
+    /// `MessagePack`, or `epoch_archive::CodecError` if there is an issue decompressing the data,
+    /// if the data is not an array, or if an element fails to deserialize as `T`.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use epoch_archive::Codec;
+    ///
+    /// let codec = Codec::default();
+    /// let encoded = codec.encode(&vec![1u32, 2, 3]).unwrap();
+    ///
+    /// let mut sum = 0;
+    /// codec.decode_seq_visit(&encoded, |n: u32| sum += n).unwrap();
+    /// assert_eq!(sum, 6);
+    /// ```
+    pub fn decode_seq_visit<T, F>(&self, data: &[u8], f: F) -> Result<()>
+    where
+        T: for<'de> Deserialize<'de>,
+        F: FnMut(T),
+    {
+        self.require_message_pack()?;
+        let decompressed = self.decompress(data)?;
+        let mut deserializer = rmp_serde::Deserializer::from_read_ref(&decompressed);
+        deserializer.deserialize_seq(SeqVisitor {
+            f,
+            marker: PhantomData,
+        })?;
+
+        Ok(())
+    }
+
+    /// Decompresses the provided data and deserializes it by driving `seed`, so the decoding can
+    /// use runtime context such as a string pool, which [`Codec::decode`] can't express.
+    ///
+    /// # Errors
+    ///
+    /// Return `epoch_archive::CodecError::UnsupportedFormat` if the codec is not configured for
+    /// `MessagePack`, or `epoch_archive::CodecError` if there is an issue decompressing the data
+    /// or the seed fails to deserialize it.
+    pub fn decode_seed<'de, S>(&self, data: &[u8], seed: S) -> Result<S::Value>
+    where
+        S: DeserializeSeed<'de>,
+    {
+        self.require_message_pack()?;
+        let decompressed = self.decompress(data)?;
+        let mut deserializer = rmp_serde::Deserializer::new(decompressed.as_slice());
+
+        Ok(seed.deserialize(&mut deserializer)?)
+    }
+
+    /// Encodes the provided data like [`Codec::encode`], but into caller provided buffers so they
+    /// can be reused across calls.
+    ///
+    /// `scratch` receives the serialized bytes and `out` the compressed frame. Both are cleared
+    /// first, and only grow when a value needs more room than any value before it, so once they
+    /// are warmed up the encode path performs no further Rust heap allocations. Without a
+    /// dictionary the zstd compression context is kept per thread and reused as well; with one,
+    /// a context is still created per call by the C library. [`Codec::with_sorted_maps`]
+    /// allocates while sorting. The frame is the same as the one [`Codec::encode`] produces.
+    ///
+    /// # Errors
+    ///
+    /// Return `epoch_archive::CodecError` if there is an issue serializing or compressing the data.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use epoch_archive::Codec;
+    ///
+    /// let codec = Codec::default();
+    /// let (mut scratch, mut out) = (Vec::new(), Vec::new());
+    ///
+    /// for i in 0..10u32 {
+    ///     codec.encode_using(&i, &mut scratch, &mut out).unwrap();
+    ///     assert_eq!(codec.decode::<u32>(&out).unwrap(), i);
+    /// }
+    /// ```
+    pub fn encode_using<T: Serialize>(
+        &self,
+        data: &T,
+        scratch: &mut Vec<u8>,
+        out: &mut Vec<u8>,
+    ) -> Result<()> {
+        self.serialize_format_into(data, scratch)?;
+
+        // The prefix is written first and the frame compressed straight after it, so neither
+        // has to be moved into place afterwards.
+        let prefix = self.frame_prefix();
+        out.clear();
+        out.reserve(
+            prefix.len()
+                + zstd::zstd_safe::compress_bound(scratch.len())
+                + if self.checksum { CHECKSUM_LEN } else { 0 },
+        );
+        out.extend_from_slice(prefix);
+        out.resize(
+            prefix.len() + zstd::zstd_safe::compress_bound(scratch.len()),
+            0,
+        );
+
+        let written = if let Some(dictionary) = &self.dictionary {
+            let mut compressor =
+                zstd::bulk::Compressor::with_prepared_dictionary(&dictionary.encoder)?;
+            self.set_compressor_workers(&mut compressor)?;
+            compressor.compress_to_buffer(scratch.as_slice(), &mut out[prefix.len()..])
+        } else {
+            // Taken out rather than borrowed, like the serialization buffer in
+            // `Codec::encode_into`, and put back once the frame is compressed.
+            let mut compressor = match ENCODE_COMPRESSOR.take() {
+                Some(mut compressor) => {
+                    compressor.set_compression_level(self.level)?;
+                    compressor
+                }
+                None => zstd::bulk::Compressor::new(self.level)?,
+            };
+            self.set_compressor_workers(&mut compressor)?;
+            let written =
+                compressor.compress_to_buffer(scratch.as_slice(), &mut out[prefix.len()..]);
+            ENCODE_COMPRESSOR.set(Some(compressor));
+            written
+        };
+        out.truncate(prefix.len() + written?);
+        self.append_checksum(out);
+
+        Ok(())
+    }
+
+    /// Encodes the provided data like [`Codec::encode_using`] into `out`, which is cleared first,
+    /// with a serialization buffer kept per thread instead of one passed in.
+    ///
+    /// # Errors
+    ///
+    /// Return `epoch_archive::CodecError` if there is an issue serializing or compressing the data.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use epoch_archive::Codec;
+    ///
+    /// let codec = Codec::default();
+    /// let mut out = Vec::new();
+    ///
+    /// for i in 0..10u32 {
+    ///     codec.encode_into(&i, &mut out).unwrap();
+    ///     assert_eq!(codec.decode::<u32>(&out).unwrap(), i);
+    /// }
+    /// ```
+    pub fn encode_into<T: Serialize>(&self, data: &T, out: &mut Vec<u8>) -> Result<()> {
+        // Taken out rather than borrowed, so a `Serialize` impl that encodes again still works.
+        let mut scratch = ENCODE_SCRATCH.take();
+        let encoded = self.encode_using(data, &mut scratch, out);
+        ENCODE_SCRATCH.set(scratch);
+
+        encoded
+    }
+
+    /// Encodes the provided data like [`Codec::encode`], dropping to a faster compression level
+    /// when the configured one looks like it would take longer than `budget`.
+    ///
+    /// The estimate is rough: the first few KiB of the serialized data are compressed at each
+    /// candidate level, and the time it took is scaled up to the full size. The configured level
+    /// is tried first, then levels 3 and 1 if they are faster than it, and the first level that
+    /// fits is used. If none fits, level 1 is used anyway, so the frame is always produced even
+    /// when it overshoots the budget. The time spent on the estimate is not counted.
+    ///
+    /// The frame decodes with [`Codec::decode`] as usual, whichever level was picked.
+    ///
+    /// # Errors
+    ///
+    /// Return `epoch_archive::CodecError` if there is an issue serializing or compressing the data.
+    pub fn encode_within_budget<T: Serialize>(
+        &self,
+        data: &T,
+        budget: Duration,
+    ) -> Result<Vec<u8>> {
+        let serialized = self.serialize_format(data)?;
+        let sample = &serialized[..serialized.len().min(BUDGET_SAMPLE_LEN)];
+        #[allow(clippy::cast_precision_loss)]
+        let scale = serialized.len() as f64 / sample.len().max(1) as f64;
+
+        // Every candidate but the last is timed on the sample; the last one is used regardless.
+        let mut level = self.level;
+        for fallback in BUDGET_FALLBACK_LEVELS
+            .into_iter()
+            .filter(|fallback| *fallback < self.level)
+        {
+            let start = Instant::now();
+            self.compress_at_level(sample, level)?;
+            if start.elapsed().mul_f64(scale) <= budget {
+                break;
+            }
+            level = fallback;
+        }
+
+        self.compress_at_level(&serialized, level)
+    }
+
+    /// Lazily encodes a stream of records into frames, closing each frame as soon as its
+    /// serialized size reaches `budget` bytes. Records are never split across frames, so a frame
+    /// overshoots the budget by at most one record, and the final frame may fall short of it.
+    ///
+    /// The frames can be decoded back into the original records with [`Codec::decode_chunked`].
+    /// The iterator stops after yielding the first error, which is
+    /// `epoch_archive::CodecError::UnsupportedFormat` straight away if the codec is not
+    /// configured for `MessagePack`.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use epoch_archive::Codec;
+    ///
+    /// let codec = Codec::default();
+    /// let frames: Vec<Vec<u8>> = codec
+    ///     .encode_chunked_by_bytes(0..1000u32, 256)
+    ///     .collect::<Result<_, _>>()
+    ///     .unwrap();
+    ///
+    /// let records: Vec<u32> = codec.decode_chunked(&frames).unwrap();
+    /// assert_eq!(records, (0..1000).collect::<Vec<_>>());
+    /// ```
+    pub fn encode_chunked_by_bytes<'a, T, I>(
+        &'a self,
+        items: I,
+        budget: usize,
+    ) -> impl Iterator<Item = Result<Vec<u8>>> + 'a
+    where
+        T: Serialize,
+        I: Iterator<Item = T> + 'a,
+    {
+        let mut items = items.fuse();
+        let mut failed = false;
+
+        std::iter::from_fn(move || {
+            if failed {
+                return None;
+            }
+            if let Err(err) = self.require_message_pack() {
+                failed = true;
+                return Some(Err(err));
+            }
+
+            let mut buf = Vec::new();
+            for item in items.by_ref() {
+                if let Err(err) = item.serialize(&mut rmp_serde::Serializer::new(&mut buf)) {
+                    failed = true;
+                    return Some(Err(err.into()));
+                }
+                if buf.len() >= budget {
+                    break;
+                }
+            }
+
+            if buf.is_empty() {
+                return None;
+            }
+
+            let frame = self.compress(&buf);
+            failed = frame.is_err();
+            Some(frame)
+        })
+    }
+
+    /// Decodes the frames produced by [`Codec::encode_chunked_by_bytes`], returning the records
+    /// from every frame in their original order.
+    ///
+    /// # Errors
+    ///
+    /// Return `epoch_archive::CodecError::UnsupportedFormat` if the codec is not configured for
+    /// `MessagePack`, or `epoch_archive::CodecError` if there is an issue decompressing or
+    /// deserializing any of the frames.
+    pub fn decode_chunked<T, I>(&self, frames: I) -> Result<Vec<T>>
+    where
+        T: for<'de> Deserialize<'de>,
+        I: IntoIterator,
+        I::Item: AsRef<[u8]>,
+    {
+        self.require_message_pack()?;
+
+        let mut records = Vec::new();
+        for frame in frames {
+            let decompressed = self.decompress(frame.as_ref())?;
+
+            let mut remaining = decompressed.as_slice();
+            while !remaining.is_empty() {
+                records.push(rmp_serde::from_read(&mut remaining)?);
+            }
+        }
+
+        Ok(records)
+    }
+
+    /// Encodes the provided data like [`Codec::encode`] and splits the frame across fixed-size
+    /// pages of `PAGE` bytes, for storage engines that work at page granularity.
+    ///
+    /// Every page starts with a header: its sequence number as a little-endian `u32`, a
+    /// continuation flag that is `1` when more pages follow and `0` on the last page, and the
+    /// number of frame bytes the page holds as a little-endian `u32`. The rest of the last page
+    /// is padded with zeros.
+    ///
+    /// # Errors
+    ///
+    /// Return `epoch_archive::CodecError` if there is an issue serializing or compressing the data.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `PAGE` is not larger than the page header.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use epoch_archive::Codec;
+    ///
+    /// let codec = Codec::default();
+    /// let mut pages = codec.encode_paged::<64, _>(&"paged ".repeat(100)).unwrap();
+    /// pages.reverse();
+    ///
+    /// let decoded = codec.decode_paged::<64, String>(&pages).unwrap();
+    /// assert_eq!(decoded, "paged ".repeat(100));
+    /// ```
+    #[allow(clippy::cast_possible_truncation)]
+    pub fn encode_paged<const PAGE: usize, T: Serialize>(
+        &self,
+        data: &T,
+    ) -> Result<Vec<[u8; PAGE]>> {
+        assert!(
+            PAGE > PAGE_HEADER_LEN,
+            "page should be larger than the {PAGE_HEADER_LEN} byte header"
+        );
+
+        let frame = self.encode(data)?;
+        let chunks = frame.chunks(PAGE - PAGE_HEADER_LEN);
+        let last = chunks.len() - 1;
+
+        // A frame long enough to need more than `u32::MAX` pages does not fit in memory, and a
+        // chunk is never longer than the page it goes into.
+        let pages = chunks
+            .enumerate()
+            .map(|(sequence, chunk)| {
+                let mut page = [0; PAGE];
+                page[..4].copy_from_slice(&(sequence as u32).to_le_bytes());
+                page[4] = u8::from(sequence != last);
+                page[5..PAGE_HEADER_LEN].copy_from_slice(&(chunk.len() as u32).to_le_bytes());
+                page[PAGE_HEADER_LEN..][..chunk.len()].copy_from_slice(chunk);
+                page
+            })
+            .collect();
+
+        Ok(pages)
+    }
+
+    /// Reassembles and decodes the pages produced by [`Codec::encode_paged`]. The pages may be
+    /// passed in any order, they are put back in sequence using their headers.
+    ///
+    /// # Errors
+    ///
+    /// Return `epoch_archive::CodecError::EmptyInput` if there are no pages,
+    /// `epoch_archive::CodecError::InvalidPageSequence` if a page is missing, duplicated, or has
+    /// a continuation flag that does not match its position,
+    /// `epoch_archive::CodecError::TruncatedFrame` if a page claims more bytes than it holds, or
+    /// `epoch_archive::CodecError` if there is an issue decompressing or deserializing the data.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `PAGE` is not larger than the page header.
+    pub fn decode_paged<const PAGE: usize, T>(&self, pages: &[[u8; PAGE]]) -> Result<T>
+    where
+        T: for<'de> Deserialize<'de>,
+    {
+        assert!(
+            PAGE > PAGE_HEADER_LEN,
+            "page should be larger than the {PAGE_HEADER_LEN} byte header"
+        );
+        if pages.is_empty() {
+            return Err(CodecError::EmptyInput);
+        }
+
+        let read_u32 = |b: &[u8]| u32::from_le_bytes([b[0], b[1], b[2], b[3]]);
+        let mut ordered: Vec<_> = pages.iter().collect();
+        ordered.sort_unstable_by_key(|page| read_u32(&page[..4]));
+
+        let last = ordered.len() - 1;
+        let mut frame = Vec::with_capacity(ordered.len() * (PAGE - PAGE_HEADER_LEN));
+        for (index, page) in ordered.into_iter().enumerate() {
+            let sequence = read_u32(&page[..4]);
+            if usize::try_from(sequence) != Ok(index) || page[4] != u8::from(index != last) {
+                return Err(CodecError::InvalidPageSequence(sequence));
+            }
+
+            let len = usize::try_from(read_u32(&page[5..PAGE_HEADER_LEN]))
+                .map_err(|_| CodecError::TruncatedFrame)?;
+            let payload = page[PAGE_HEADER_LEN..]
+                .get(..len)
+                .ok_or(CodecError::TruncatedFrame)?;
+            frame.extend_from_slice(payload);
+        }
+
+        self.decode(&frame)
+    }
+
+    /// Encodes a slice of epochs as the difference from each epoch to the one before it and
+    /// compresses them, which suits time series that move forward in small, regular steps.
+    ///
+    /// Every epoch is written as a one byte tag of its precision followed by the signed
+    /// difference in nanoseconds, zig-zag encoded as a LEB128 varint. The first epoch is written
+    /// as its difference from the Unix epoch, i.e. its absolute value. A sensor reporting every
+    /// millisecond turns into the same few bytes repeated, which zstd compresses to almost
+    /// nothing. Sequences that go backward or repeat work too, they just compress less.
+    ///
+    /// # Errors
+    ///
+    /// Return `epoch_archive::CodecError` if there is an issue compressing the data.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use epoch_archive::{Codec, Epoch};
+    ///
+    /// let codec = Codec::default();
+    /// let epochs: Vec<Epoch> = (0..10_000)
+    ///     .map(|i| Epoch::from_total_millis(1_700_000_000_000 + i))
+    ///     .collect();
+    ///
+    /// let encoded = codec.encode_epochs_delta(&epochs).unwrap();
+    /// assert!(encoded.len() < 100);
+    /// assert_eq!(codec.decode_epochs_delta(&encoded).unwrap(), epochs);
+    /// ```
+    pub fn encode_epochs_delta(&self, epochs: &[Epoch]) -> Result<Vec<u8>> {
+        let mut raw = Vec::with_capacity(epochs.len() * 2);
+        let mut previous = 0;
+        for epoch in epochs {
+            let precision = epoch.subsecond().precision().unwrap_or(Precision::Second);
+            raw.push(precision_tag(precision));
+
+            let nanos = epoch.as_nanos();
+            write_zigzag_varint(&mut raw, nanos - previous);
+            previous = nanos;
+        }
+
+        self.compress(&raw)
+    }
+
+    /// Decodes epochs encoded by [`Codec::encode_epochs_delta`] by summing the differences back
+    /// up. Every epoch comes back exactly as it was encoded, precision included.
+    ///
+    /// # Errors
+    ///
+    /// Return `epoch_archive::CodecError::TruncatedFrame` if the data ends in the middle of an
+    /// epoch, `epoch_archive::CodecError::InvalidVarint` if a difference is not a valid varint,
+    /// `epoch_archive::CodecError::InvalidTimestamp` if a precision tag is unknown or an epoch is
+    /// outside the representable range, or `epoch_archive::CodecError` if there is an issue
+    /// decompressing the data.
+    pub fn decode_epochs_delta(&self, data: &[u8]) -> Result<Vec<Epoch>> {
+        let raw = self.decompress(data)?;
+        let mut rest = raw.as_slice();
+
+        let mut epochs = Vec::new();
+        let mut nanos: i128 = 0;
+        while let Some((&tag, remaining)) = rest.split_first() {
+            let precision = DELTA_PRECISIONS
+                .get(usize::from(tag))
+                .ok_or(CodecError::InvalidTimestamp)?;
+            rest = remaining;
+
+            nanos = nanos
+                .checked_add(read_zigzag_varint(&mut rest)?)
+                .ok_or(CodecError::InvalidTimestamp)?;
+            let exact = Epoch::checked_from_nanos(nanos).ok_or(CodecError::InvalidTimestamp)?;
+            let epoch = exact.truncate_to(*precision);
+            if epoch != exact {
+                return Err(CodecError::InvalidTimestamp);
+            }
+            epochs.push(epoch);
+        }
+
+        Ok(epochs)
+    }
+
+    /// Encodes an [`Epoch`] as the standard `MessagePack` timestamp extension (type -1) and
+    /// compresses it, so other `MessagePack` libraries decode it as a native timestamp.
+    ///
+    /// The smallest of the three timestamp layouts that fits the epoch is used: 32-bit when there
+    /// is no subsecond and the seconds fit in a `u32`, 64-bit when the seconds fit in 34 bits,
+    /// and 96-bit otherwise.
+    ///
+    /// # Errors
+    ///
+    /// Return `epoch_archive::CodecError` if there is an issue compressing the data.
+    pub fn encode_epoch_ext(&self, epoch: &Epoch) -> Result<Vec<u8>> {
+        self.compress(&timestamp_ext(epoch))
+    }
+
+    /// Decodes an [`Epoch`] encoded by [`Codec::encode_epoch_ext`] or any other `MessagePack`
+    /// timestamp extension.
+    ///
+    /// The 32-bit layout carries no subsecond and is decoded with `SubSecond::None`, the other
+    /// layouts are decoded with `SubSecond::Nano`.
+    ///
+    /// # Errors
+    ///
+    /// Return `epoch_archive::CodecError::InvalidTimestamp` if the data is not a valid timestamp
+    /// extension, or `epoch_archive::CodecError` if there is an issue decompressing the data.
+    pub fn decode_epoch_ext(&self, data: &[u8]) -> Result<Epoch> {
+        epoch_from_timestamp_ext(&self.decompress(data)?)
+    }
+
+    /// Encodes a slice of epochs as their total nanoseconds, each a fixed 16 byte little-endian
+    /// `i128` written one after another, and compresses them, skipping `MessagePack` altogether.
+    ///
+    /// The decompressed data is exactly `16 * epochs.len()` bytes, so other readers can pick it
+    /// apart without this crate. The precision of each epoch is not stored. For sorted epochs,
+    /// [`Codec::encode_epochs_delta`] is usually much smaller.
+    ///
+    /// # Errors
+    ///
+    /// Return `epoch_archive::CodecError` if there is an issue compressing the data.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use epoch_archive::{Codec, Epoch};
+    ///
+    /// let codec = Codec::default();
+    /// let epochs: Vec<Epoch> = (0..1_000)
+    ///     .map(|i| Epoch::new(1_700_000_000 + i).with_millis(250))
+    ///     .collect();
+    ///
+    /// let encoded = codec.encode_epochs(&epochs).unwrap();
+    /// assert_eq!(codec.decode_epochs(&encoded).unwrap(), epochs);
+    /// ```
+    pub fn encode_epochs(&self, epochs: &[Epoch]) -> Result<Vec<u8>> {
+        let nanos: Vec<u8> = epochs
+            .iter()
+            .flat_map(|epoch| epoch.as_nanos().to_le_bytes())
+            .collect();
+
+        self.compress(&nanos)
+    }
+
+    /// Decodes epochs encoded by [`Codec::encode_epochs`]. They compare equal to the encoded
+    /// epochs, but always have `SubSecond::Nano` precision.
+    ///
+    /// # Errors
+    ///
+    /// Return `epoch_archive::CodecError::TruncatedFrame` if the data does not decompress to a
+    /// whole number of epochs, `epoch_archive::CodecError::InvalidTimestamp` if an epoch is
+    /// outside the representable range, or `epoch_archive::CodecError` if there is an issue
+    /// decompressing the data.
+    pub fn decode_epochs(&self, data: &[u8]) -> Result<Vec<Epoch>> {
+        let decompressed = self.decompress(data)?;
+        let (nanos, rest) = decompressed.as_chunks::<EPOCH_NANOS_LEN>();
+        if !rest.is_empty() {
+            return Err(CodecError::TruncatedFrame);
+        }
+
+        nanos
+            .iter()
+            .map(|nanos| {
+                Epoch::checked_from_nanos(i128::from_le_bytes(*nanos))
+                    .ok_or(CodecError::InvalidTimestamp)
+            })
+            .collect()
+    }
+
+    /// Encodes the provided data and prefixes the frame with a header recording when it was
+    /// created, so archived blobs date themselves.
+    ///
+    /// The header is the little-endian `i64` seconds of `created`, its subsecond as a
+    /// little-endian `u32` count of nanoseconds, and a one byte tag of its precision, so the
+    /// stamp decodes exactly as it was given.
+    ///
+    /// # Errors
+    ///
+    /// Return `epoch_archive::CodecError::InvalidTimestamp` if the subsecond of `created` is a
+    /// second or more, or `epoch_archive::CodecError` if there is an issue serializing or
+    /// compressing the data.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use epoch_archive::{Codec, Epoch, SubSecond};
+    ///
+    /// let codec = Codec::default();
+    /// let created = Epoch::new(1_700_000_000).with_millis(250);
+    /// let frame = codec.encode_stamped(&"hello", &created).unwrap();
+    ///
+    /// let (data, stamp) = codec.decode_stamped::<String>(&frame).unwrap();
+    /// assert_eq!(data, "hello");
+    /// assert_eq!(stamp, created);
+    /// assert!(matches!(stamp.subsecond(), SubSecond::Milli(250)));
+    /// ```
+    pub fn encode_stamped<T: Serialize>(&self, data: &T, created: &Epoch) -> Result<Vec<u8>> {
+        let nanos = u32::try_from(created.subsecond().as_nanos())
+            .ok()
+            .filter(|nanos| *nanos < 1_000_000_000)
+            .ok_or(CodecError::InvalidTimestamp)?;
+
+        let mut frame = Vec::with_capacity(STAMP_HEADER_LEN);
+        frame.extend_from_slice(&created.epoch().to_le_bytes());
+        frame.extend_from_slice(&nanos.to_le_bytes());
+        frame.push(precision_tag(
+            created.subsecond().precision().unwrap_or(Precision::Second),
+        ));
+        frame.extend(self.encode(data)?);
+
+        Ok(frame)
+    }
+
+    /// Decodes a frame produced by [`Codec::encode_stamped`], returning the data together with
+    /// its creation stamp in the precision it was encoded with.
+    ///
+    /// # Errors
+    ///
+    /// Return `epoch_archive::CodecError::TruncatedFrame` if the header is incomplete,
+    /// `epoch_archive::CodecError::InvalidTimestamp` if the stamp is not a valid epoch or its
+    /// precision tag is unknown or finer than its subsecond, or
+    /// `epoch_archive::CodecError` if there is an issue decompressing or deserializing the data.
+    pub fn decode_stamped<T>(&self, data: &[u8]) -> Result<(T, Epoch)>
+    where
+        T: for<'de> Deserialize<'de>,
+    {
+        let (seconds, rest) = data
+            .split_first_chunk::<8>()
+            .ok_or(CodecError::TruncatedFrame)?;
+        let (nanos, rest) = rest
+            .split_first_chunk::<4>()
+            .ok_or(CodecError::TruncatedFrame)?;
+        let (&tag, frame) = rest.split_first().ok_or(CodecError::TruncatedFrame)?;
+
+        let nanos = u64::from(u32::from_le_bytes(*nanos));
+        let precision = DELTA_PRECISIONS
+            .get(usize::from(tag))
+            .ok_or(CodecError::InvalidTimestamp)?;
+        if nanos >= 1_000_000_000 {
+            return Err(CodecError::InvalidTimestamp);
+        }
+        let exact = Epoch::new(i64::from_le_bytes(*seconds)).with_nanos(nanos);
+        let created = exact.truncate_to(*precision);
+        if created != exact {
+            return Err(CodecError::InvalidTimestamp);
+        }
+
+        Ok((self.decode(frame)?, created))
+    }
+
+    /// Encodes the provided data and prefixes the frame with an xxHash64 digest of the
+    /// serialized bytes. Two records with the same digest can be treated as unchanged
+    /// without comparing the frames byte by byte.
+    ///
+    /// Returns the frame together with the digest.
+    ///
+    /// # Errors
+    ///
+    /// Return `epoch_archive::CodecError` if there is an issue serializing or compressing the data.
+    #[cfg(feature = "xxhash")]
+    pub fn encode_with_digest<T: Serialize>(&self, data: &T) -> Result<(Vec<u8>, u64)> {
+        let serialized = self.serialize_format(data)?;
+        let digest = xxhash_rust::xxh64::xxh64(&serialized, DIGEST_SEED);
+
+        let mut frame = digest.to_le_bytes().to_vec();
+        frame.extend(self.compress(&serialized)?);
+
+        Ok((frame, digest))
+    }
+
+    /// Decodes a frame produced by [`Codec::encode_with_digest`], recomputing the digest of the
+    /// decompressed bytes and comparing it against the one stored in the frame.
+    ///
+    /// Returns the decoded data together with the verified digest.
+    ///
+    /// # Errors
+    ///
+    /// Return `epoch_archive::CodecError::DigestMismatch` if the digests differ, or
+    /// `epoch_archive::CodecError` if there is an issue decompressing or deserializing the data.
+    #[cfg(feature = "xxhash")]
+    pub fn decode_with_digest<T>(&self, data: &[u8]) -> Result<(T, u64)>
+    where
+        T: for<'de> Deserialize<'de>,
+    {
+        let (digest, frame) = data
+            .split_first_chunk::<8>()
+            .ok_or(CodecError::TruncatedFrame)?;
+        let expected = u64::from_le_bytes(*digest);
+
+        let decompressed = self.decompress(frame)?;
+        let actual = xxhash_rust::xxh64::xxh64(&decompressed, DIGEST_SEED);
+        if actual != expected {
+            return Err(CodecError::DigestMismatch { expected, actual });
+        }
+
+        Ok((self.deserialize(&decompressed)?, actual))
+    }
+
+    /// Encodes the provided data into the file at `path`, and writes the hex encoded SHA-256 of the
+    /// file's contents to a sidecar file next to it, named `path` with `.sha256` appended.
+    ///
+    /// # Errors
+    ///
+    /// Return `epoch_archive::CodecError` if there is an issue encoding the data or writing either file.
+    #[cfg(feature = "sha256")]
+    pub fn encode_to_files<T, P>(&self, data: &T, path: P) -> Result<()>
+    where
+        T: Serialize,
+        P: AsRef<Path>,
+    {
+        let path = path.as_ref();
+        let frame = self.encode(data)?;
+
+        std::fs::write(path, &frame)?;
+        std::fs::write(sidecar_path(path), sha256_hex(&frame))?;
+
+        Ok(())
+    }
+
+    /// Decodes the file at `path` written by [`Codec::encode_to_files`], verifying its contents
+    /// against the `.sha256` sidecar before decoding.
+    ///
+    /// # Errors
+    ///
+    /// Return `epoch_archive::CodecError::ChecksumMismatch` if the file does not match the sidecar,
+    /// or `epoch_archive::CodecError` if there is an issue reading either file or decoding the data.
+    #[cfg(feature = "sha256")]
+    pub fn decode_from_files<T, P>(&self, path: P) -> Result<T>
+    where
+        T: for<'de> Deserialize<'de>,
+        P: AsRef<Path>,
+    {
+        let path = path.as_ref();
+        let frame = std::fs::read(path)?;
+        let expected = std::fs::read_to_string(sidecar_path(path))?
+            .trim()
+            .to_ascii_lowercase();
+
+        let actual = sha256_hex(&frame);
+        if actual != expected {
+            return Err(CodecError::ChecksumMismatch { expected, actual });
+        }
+
+        self.decode(&frame)
+    }
+
+    /// Compresses the provided data using the zstd algorithm.
+    ///
+    /// The frame records the length of `data` as its content size, which
+    /// [`Codec::decompressed_size`] and other zstd tools can read without decompressing it.
+    ///
+    /// # Arguments
+    ///
+    /// * `data` - The data to be compressed.
+    ///
+    /// # Errors
+    ///
+    /// Return `epoch_archive::CodecError` if there is an issue compressing the data.
+    pub fn compress(&self, data: &[u8]) -> Result<Vec<u8>> {
+        let mut frame = self.frame_prefix().to_vec();
+
+        let mut encoder = self.stream_encoder(&mut frame)?;
+        encoder.set_pledged_src_size(Some(data.len() as u64))?;
+        encoder.write_all(data)?;
+        encoder.finish()?;
+        self.append_checksum(&mut frame);
+
+        Ok(frame)
+    }
+
+    /// Compresses the UTF-8 bytes of `s` as they are, without `MessagePack` framing, which suits
+    /// raw text such as logs.
+    ///
+    /// # Errors
+    ///
+    /// Return `epoch_archive::CodecError` if there is an issue compressing the data.
+    pub fn compress_str(&self, s: &str) -> Result<Vec<u8>> {
+        self.compress(s.as_bytes())
+    }
+
+    /// Decompresses data produced by [`Codec::compress_str`] back into a `String`.
+    ///
+    /// # Errors
+    ///
+    /// Return `epoch_archive::CodecError::InvalidUtf8` if the decompressed bytes are not valid
+    /// UTF-8, or `epoch_archive::CodecError` if there is an issue decompressing the data.
+    pub fn decompress_str(&self, data: &[u8]) -> Result<String> {
+        Ok(String::from_utf8(self.decompress(data)?)?)
+    }
+
+    /// Compresses everything read from `reader` into `writer` as a single frame, without holding
+    /// either side in memory, and returns the number of compressed bytes written.
+    ///
+    /// The frame can be read back by [`Codec::decompress`] as well as [`Codec::decompress_stream`].
+    ///
+    /// # Errors
+    ///
+    /// Return `epoch_archive::CodecError` if there is an issue reading, compressing or writing the
+    /// data.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use epoch_archive::Codec;
+    ///
+    /// let codec = Codec::default();
+    /// let mut frame = Vec::new();
+    /// let written = codec.compress_stream(&b"streamed"[..], &mut frame).unwrap();
+    /// assert_eq!(written, frame.len() as u64);
+    ///
+    /// let mut data = Vec::new();
+    /// codec.decompress_stream(frame.as_slice(), &mut data).unwrap();
+    /// assert_eq!(data, b"streamed");
+    /// ```
+    pub fn compress_stream<R: Read, W: Write>(&self, mut reader: R, writer: W) -> Result<u64> {
+        let mut writer = CountingWriter {
+            inner: writer,
+            written: 0,
+            #[cfg(feature = "xxhash")]
+            checksum: self
+                .checksum
+                .then(|| xxhash_rust::xxh64::Xxh64::new(DIGEST_SEED)),
+        };
+        writer.write_all(self.frame_prefix())?;
+
+        let mut encoder = self.stream_encoder(&mut writer)?;
+        std::io::copy(&mut reader, &mut encoder)?;
+        encoder.finish()?;
+        #[cfg(feature = "xxhash")]
+        if let Some(checksum) = writer.checksum.take() {
+            writer.write_all(&checksum.digest().to_le_bytes())?;
+        }
+        writer.flush()?;
+
+        Ok(writer.written)
+    }
+
+    /// Decompresses a frame read from `reader` into `writer`, without holding either side in
+    /// memory, and returns the number of decompressed bytes written.
+    ///
+    /// With [`Codec::with_checksum`] enabled, the checksum can only be compared once the whole
+    /// frame is read, so the decompressed bytes are already written when a mismatch is reported.
+    ///
+    /// # Errors
+    ///
+    /// Return `epoch_archive::CodecError::EmptyInput` if `reader` is empty,
+    /// `epoch_archive::CodecError::UnknownFormat` or `epoch_archive::CodecError::VersionMismatch`
+    /// if the header is missing or from another version,
+    /// `epoch_archive::CodecError::ChecksumMismatch` if the checksum does not match the frame,
+    /// `epoch_archive::CodecError::UnknownAlgorithm` if the frame is tagged with an unknown
+    /// algorithm id, or `epoch_archive::CodecError` if there is an issue reading, decompressing or
+    /// writing the data.
+    pub fn decompress_stream<R: Read, W: Write>(&self, reader: R, mut writer: W) -> Result<u64> {
+        let mut reader = BufReader::new(reader);
+        if reader.fill_buf()?.is_empty() {
+            return Err(CodecError::EmptyInput);
+        }
+
+        let mut prefix = Vec::new();
+        (&mut reader)
+            .take(self.frame_prefix().len() as u64)
+            .read_to_end(&mut prefix)?;
+        self.strip_frame_prefix(&prefix)?;
+
+        #[cfg(feature = "xxhash")]
+        if self.checksum {
+            let mut reader = ChecksumReader {
+                inner: reader,
+                checksum: xxhash_rust::xxh64::Xxh64::new(DIGEST_SEED),
+            };
+            reader.checksum.update(&prefix);
+
+            let (written, reader) = self.decompress_frames_from(reader, &mut writer, true)?;
+            let ChecksumReader {
+                mut inner,
+                checksum,
+            } = reader;
+            let mut stored = [0; CHECKSUM_LEN];
+            inner
+                .read_exact(&mut stored)
+                .map_err(|err| match err.kind() {
+                    std::io::ErrorKind::UnexpectedEof => CodecError::TruncatedFrame,
+                    _ => CodecError::IOError(err),
+                })?;
+            verify_checksum(u64::from_le_bytes(stored), checksum.digest())?;
+            writer.flush()?;
+
+            return Ok(written);
+        }
+
+        let (written, _) = self.decompress_frames_from(reader, &mut writer, false)?;
+        writer.flush()?;
+
+        Ok(written)
+    }
+
+    /// Decompresses the frames read from `reader` into `writer`, or only the first one if
+    /// `single_frame` is set, returning the number of bytes written and the reader positioned
+    /// after the last frame it read.
+    fn decompress_frames_from<B: BufRead, W: Write>(
+        &self,
+        reader: B,
+        writer: &mut W,
+        single_frame: bool,
+    ) -> Result<(u64, B)> {
+        let mut decoder = match &self.dictionary {
+            Some(dictionary) => {
+                zstd::stream::Decoder::with_prepared_dictionary(reader, &dictionary.decoder)?
+            }
+            None => zstd::stream::Decoder::with_buffer(reader)?,
+        };
+        if single_frame {
+            decoder = decoder.single_frame();
+        }
+        let written = std::io::copy(&mut decoder, writer)?;
+
+        Ok((written, decoder.finish()))
+    }
+
+    /// Creates a streaming zstd encoder writing to `writer`, using the dictionary when there is one
+    /// and the configured workers.
+    pub(crate) fn stream_encoder<W: Write>(
+        &self,
+        writer: W,
+    ) -> std::io::Result<zstd::stream::Encoder<'_, W>> {
+        let mut encoder = match &self.dictionary {
+            Some(dictionary) => {
+                zstd::stream::Encoder::with_prepared_dictionary(writer, &dictionary.encoder)?
+            }
+            None => zstd::stream::Encoder::new(writer, self.level)?,
+        };
+        self.set_workers(&mut encoder)?;
+
+        Ok(encoder)
+    }
+
+    /// Spreads the work of `encoder` across the configured workers, see [`Codec::with_workers`].
+    #[cfg_attr(
+        not(feature = "zstdmt"),
+        allow(unused_variables, clippy::unused_self, clippy::unnecessary_wraps)
+    )]
+    fn set_workers<W: Write>(
+        &self,
+        encoder: &mut zstd::stream::Encoder<'_, W>,
+    ) -> std::io::Result<()> {
+        #[cfg(feature = "zstdmt")]
+        if self.workers > 1 {
+            encoder.multithread(self.workers)?;
+        }
+
+        Ok(())
+    }
+
+    /// Spreads the work of `compressor` across the configured workers like
+    /// [`Codec::set_workers`], also turning them off again on a reused compressor that had them.
+    #[cfg_attr(
+        not(feature = "zstdmt"),
+        allow(unused_variables, clippy::unused_self, clippy::unnecessary_wraps)
+    )]
+    fn set_compressor_workers(
+        &self,
+        compressor: &mut zstd::bulk::Compressor<'_>,
+    ) -> std::io::Result<()> {
+        #[cfg(feature = "zstdmt")]
+        compressor.set_parameter(zstd::zstd_safe::CParameter::NbWorkers(
+            if self.workers > 1 { self.workers } else { 0 },
+        ))?;
+
+        Ok(())
+    }
+
+    /// Compresses `data` like [`Codec::compress`], but at `level` instead of the configured level.
+    fn compress_at_level(&self, data: &[u8], level: i32) -> Result<Vec<u8>> {
+        if level == self.level {
+            return self.compress(data);
+        }
+
+        let mut frame = self.frame_prefix().to_vec();
+
+        let mut encoder = match &self.dictionary {
+            Some(dictionary) => {
+                zstd::stream::Encoder::with_dictionary(&mut frame, level, &dictionary.raw)?
+            }
+            None => zstd::stream::Encoder::new(&mut frame, level)?,
+        };
+        self.set_workers(&mut encoder)?;
+        encoder.set_pledged_src_size(Some(data.len() as u64))?;
+        encoder.write_all(data)?;
+        encoder.finish()?;
+        self.append_checksum(&mut frame);
+
+        Ok(frame)
+    }
+
+    /// Decompresses the provided data using the zstd algorithm.
+    ///
+    /// # Arguments
+    ///
+    /// * `data` - The data to be decompressed.
+    ///
+    /// # Errors
+    ///
+    /// Return `epoch_archive::CodecError::EmptyInput` if `data` is empty,
+    /// `epoch_archive::CodecError::UnknownFormat` or `epoch_archive::CodecError::VersionMismatch`
+    /// if the header is missing or from another version,
+    /// `epoch_archive::CodecError::ChecksumMismatch` if the checksum does not match the frame,
+    /// `epoch_archive::CodecError::UnknownAlgorithm` if the frame is tagged with an unknown
+    /// algorithm id, or `epoch_archive::CodecError` if there is an issue decompressing the data.
+    pub fn decompress(&self, data: &[u8]) -> Result<Vec<u8>> {
+        let mut decompressed = Vec::new();
+        self.decompress_into(data, &mut decompressed)?;
+
+        Ok(decompressed)
+    }
+
+    /// Decompresses the provided data like [`Codec::decompress`] into `out`, which is cleared
+    /// first, so its allocation can be reused across calls. `out` only grows when a frame
+    /// decompresses to more bytes than it can hold.
+    ///
+    /// If decompression fails, `out` holds whatever was decompressed before the error.
+    ///
+    /// # Errors
+    ///
+    /// Return `epoch_archive::CodecError` for the same reasons as [`Codec::decompress`].
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use epoch_archive::Codec;
+    ///
+    /// let codec = Codec::default();
+    /// let mut out = Vec::new();
+    ///
+    /// for data in [&b"first frame"[..], b"second"] {
+    ///     codec.decompress_into(&codec.compress(data).unwrap(), &mut out).unwrap();
+    ///     assert_eq!(out, data);
+    /// }
+    /// ```
+    pub fn decompress_into(&self, data: &[u8], out: &mut Vec<u8>) -> Result<()> {
+        out.clear();
+        if data.is_empty() {
+            return Err(CodecError::EmptyInput);
+        }
+
+        self.frame_decoder(data)?.read_to_end(out)?;
+
+        Ok(())
+    }
+
+    /// Checks the checksum and prefix of `data` and returns a streaming decoder over the zstd
+    /// frame after them, using the dictionary when there is one.
+    fn frame_decoder<'a>(&'a self, data: &'a [u8]) -> Result<zstd::stream::Decoder<'a, &'a [u8]>> {
+        let frame = self.strip_checksum(data)?;
+        let frame = self.strip_frame_prefix(frame)?;
+        let decoder = match &self.dictionary {
+            Some(dictionary) => {
+                zstd::stream::Decoder::with_prepared_dictionary(frame, &dictionary.decoder)?
+            }
+            None => zstd::stream::Decoder::with_buffer(frame)?,
+        };
+
+        Ok(decoder)
+    }
+
+    /// Returns the decompressed size recorded in the header of the zstd frame in `data`, without
+    /// decompressing it.
+    ///
+    /// Returns `None` if the frame does not record its size, which is the case for frames written
+    /// by [`Codec::compress_stream`], or if `data` does not start with a frame this codec can
+    /// read. The size is whatever the frame claims, so it should only be trusted as far as the
+    /// data is: [`Codec::decode_limited`] enforces a limit while decompressing.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use epoch_archive::Codec;
+    ///
+    /// let codec = Codec::default();
+    /// let (mut scratch, mut frame) = (Vec::new(), Vec::new());
+    /// codec.encode_using(&vec![0u8; 1_000], &mut scratch, &mut frame).unwrap();
+    ///
+    /// assert_eq!(codec.decompressed_size(&frame), Some(scratch.len() as u64));
+    /// assert_eq!(codec.decompressed_size(b"not a frame"), None);
+    /// ```
+    #[must_use]
+    pub fn decompressed_size(&self, data: &[u8]) -> Option<u64> {
+        let frame = self.strip_frame_prefix(data).ok()?;
+        zstd::zstd_safe::get_frame_content_size(frame).ok()?
+    }
+
+    /// Decompresses the provided data like [`Codec::decompress`], failing as soon as more than
+    /// `max` bytes come out instead of buffering the whole output.
+    fn decompress_limited(&self, data: &[u8], max: usize) -> Result<Vec<u8>> {
+        if data.is_empty() {
+            return Err(CodecError::EmptyInput);
+        }
+
+        let mut decompressed = Vec::new();
+        self.frame_decoder(data)?
+            .take(u64::try_from(max).unwrap_or(u64::MAX).saturating_add(1))
+            .read_to_end(&mut decompressed)?;
+        if decompressed.len() > max {
+            return Err(CodecError::SizeLimitExceeded { max });
+        }
+
+        Ok(decompressed)
+    }
+
+    /// Fails for the methods that only speak `MessagePack` when another [`Format`] is configured.
+    pub(crate) fn require_message_pack(&self) -> Result<()> {
+        if self.format == Format::MessagePack {
+            Ok(())
+        } else {
+            Err(CodecError::UnsupportedFormat(self.format))
+        }
+    }
+
+    /// Fails for the methods that write bare zstd frames when a header, algorithm tag or
+    /// checksum is enabled, since [`Codec::decompress`] would expect them on every frame.
+    pub(crate) fn require_bare_frames(&self) -> Result<()> {
+        if self.header || self.algorithm_tag || self.checksum {
+            Err(CodecError::UnsupportedFraming)
+        } else {
+            Ok(())
+        }
+    }
+
+    /// Returns the header and algorithm tag written in front of every frame, as enabled by
+    /// [`Codec::with_header`] and [`Codec::with_algorithm_tag`].
+    fn frame_prefix(&self) -> &'static [u8] {
+        match (self.header, self.algorithm_tag) {
+            (false, false) => &[],
+            (false, true) => &FRAME_PREFIX[5..],
+            (true, false) => &FRAME_PREFIX[..5],
+            (true, true) => &FRAME_PREFIX,
+        }
+    }
+
+    /// Checks the prefix written by [`Codec::frame_prefix`] and returns the frame after it.
+    fn strip_frame_prefix<'a>(&self, data: &'a [u8]) -> Result<&'a [u8]> {
+        let mut frame = data;
+        if self.header {
+            let Some((magic, rest)) = frame.split_first_chunk::<4>() else {
+                return Err(CodecError::UnknownFormat);
+            };
+            if *magic != HEADER_MAGIC {
+                return Err(CodecError::UnknownFormat);
+            }
+            frame = match rest.split_first() {
+                Some((&HEADER_VERSION, rest)) => rest,
+                Some((&found, _)) => {
+                    return Err(CodecError::VersionMismatch {
+                        expected: HEADER_VERSION,
+                        found,
+                    });
+                }
+                None => return Err(CodecError::TruncatedFrame),
+            };
+        }
+        if self.algorithm_tag {
+            frame = match frame.split_first() {
+                Some((&ZSTD_ALGORITHM_ID, rest)) => rest,
+                Some((&id, _)) => return Err(CodecError::UnknownAlgorithm(id)),
+                None => return Err(CodecError::TruncatedFrame),
+            };
+        }
+
+        Ok(frame)
+    }
+
+    /// Appends the checksum of `frame` to it if [`Codec::with_checksum`] is enabled.
+    #[cfg_attr(
+        not(feature = "xxhash"),
+        allow(unused_variables, clippy::unused_self, clippy::ptr_arg)
+    )]
+    fn append_checksum(&self, frame: &mut Vec<u8>) {
+        #[cfg(feature = "xxhash")]
+        if self.checksum {
+            let checksum = xxhash_rust::xxh64::xxh64(frame, DIGEST_SEED);
+            frame.extend(checksum.to_le_bytes());
+        }
+    }
+
+    /// Checks the checksum appended by [`Codec::append_checksum`] and returns the frame before
+    /// it.
+    #[cfg_attr(
+        not(feature = "xxhash"),
+        allow(clippy::unused_self, clippy::unnecessary_wraps)
+    )]
+    fn strip_checksum<'a>(&self, data: &'a [u8]) -> Result<&'a [u8]> {
+        #[cfg(feature = "xxhash")]
+        if self.checksum {
+            let (frame, stored) = data
+                .split_last_chunk::<CHECKSUM_LEN>()
+                .ok_or(CodecError::TruncatedFrame)?;
+            verify_checksum(
+                u64::from_le_bytes(*stored),
+                xxhash_rust::xxh64::xxh64(frame, DIGEST_SEED),
+            )?;
+
+            return Ok(frame);
+        }
+
+        Ok(data)
+    }
+
+    /// Serializes the provided data using the `MessagePack` format.
+    ///
+    /// This always writes `MessagePack`, whatever [`Format`] a codec is configured with, so its
+    /// output only reads back with [`Codec::deserialize`] on a `MessagePack` codec. Use
+    /// [`Codec::serialize_format`] to serialize with a codec's own format.
+    ///
+    /// # Errors
+    ///
+    /// Return `epoch_archive::CodecError` if there is an issue serializing the data.
+    pub fn serialize<T: Serialize>(data: &T) -> Result<Vec<u8>> {
+        let mut buf = Vec::new();
+        Self::serialize_into(data, &mut buf)?;
+
+        Ok(buf)
+    }
+
+    /// Serializes the provided data like [`Codec::serialize`] into `buf`, which is cleared first,
+    /// so its allocation can be reused across calls. Like [`Codec::serialize`], this always
+    /// writes `MessagePack`.
+    ///
+    /// # Errors
+    ///
+    /// Return `epoch_archive::CodecError` if there is an issue serializing the data.
+    pub fn serialize_into<T: Serialize>(data: &T, buf: &mut Vec<u8>) -> Result<()> {
+        buf.clear();
+        let mut ser = rmp_serde::Serializer::new(buf);
+        data.serialize(&mut ser)?;
+
+        Ok(())
+    }
+
+    /// Serializes the provided data with the configured [`Format`], sorting its maps if
+    /// [`Codec::with_sorted_maps`] is enabled. This is the counterpart of [`Codec::deserialize`],
+    /// and the same bytes [`Codec::encode`] compresses.
+    ///
+    /// # Errors
+    ///
+    /// Return `epoch_archive::CodecError` if there is an issue serializing the data, or
+    /// `epoch_archive::CodecError::FormatError` for a format other than `MessagePack`.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use epoch_archive::Codec;
+    ///
+    /// let codec = Codec::default();
+    /// let serialized = codec.serialize_format(&(1u32, "one")).unwrap();
+    ///
+    /// let value: (u32, String) = codec.deserialize(&serialized).unwrap();
+    /// assert_eq!(value, (1, "one".to_string()));
+    /// ```
+    pub fn serialize_format<T: Serialize>(&self, data: &T) -> Result<Vec<u8>> {
+        let mut buf = Vec::new();
+        self.serialize_format_into(data, &mut buf)?;
+
+        Ok(buf)
+    }
+
+    /// Serializes the provided data like [`Codec::serialize_format`] into `buf`, which is
+    /// cleared first.
+    pub(crate) fn serialize_format_into<T: Serialize>(
+        &self,
+        data: &T,
+        buf: &mut Vec<u8>,
+    ) -> Result<()> {
+        match self.format {
+            Format::MessagePack => {
+                Self::serialize_into(data, buf)?;
+                if self.sorted_maps {
+                    *buf = sort_maps(buf)?;
+                }
+            }
+            #[cfg(feature = "json")]
+            Format::Json => {
+                buf.clear();
+                serde_json::to_writer(&mut *buf, data)
+                    .map_err(|err| CodecError::FormatError(err.to_string()))?;
+            }
+            #[cfg(feature = "cbor")]
+            Format::Cbor => {
+                buf.clear();
+                ciborium::into_writer(data, &mut *buf)
+                    .map_err(|err| CodecError::FormatError(err.to_string()))?;
+            }
+        }
+
+        Ok(())
+    }
+
+    /// Deserializes the provided data using the configured [`Format`], `MessagePack` by default.
+    ///
+    /// The data is expected in the configured format, as written by [`Codec::serialize_format`].
+    /// [`Codec::serialize`] always writes `MessagePack`, so its output does not read back on a
+    /// JSON or CBOR codec.
+    ///
+    /// CBOR is read into an owned value first, so types borrowing from `data` only deserialize
+    /// from `MessagePack` and JSON.
+    ///
+    /// # Errors
+    ///
+    /// Return `epoch_archive::CodecError::DecodeContext` if there is an issue deserializing
+    /// `MessagePack` data. Its message names the type that was being decoded and the first bytes
+    /// of `data`. Return `epoch_archive::CodecError::FormatError` if there is an issue
+    /// deserializing any other format.
+    pub fn deserialize<'a, T>(&self, data: &'a [u8]) -> Result<T>
+    where
+        T: Deserialize<'a>,
+    {
+        match self.format {
+            Format::MessagePack => {
+                rmp_serde::from_slice(data).map_err(|source| CodecError::DecodeContext {
+                    type_name: std::any::type_name::<T>(),
+                    preview: data[..data.len().min(DECODE_PREVIEW_LEN)].to_vec(),
+                    source,
+                })
+            }
+            #[cfg(feature = "json")]
+            Format::Json => {
+                serde_json::from_slice(data).map_err(|err| CodecError::FormatError(err.to_string()))
+            }
+            #[cfg(feature = "cbor")]
+            Format::Cbor => ciborium::from_reader::<ciborium::Value, _>(data)
+                .map_err(|err| CodecError::FormatError(err.to_string()))?
+                .deserialized()
+                .map_err(|err| CodecError::FormatError(err.to_string())),
+        }
+    }
+}
+
+/// The sizes measured by [`Codec::encode_with_stats`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct CompressionStats {
+    serialized_len: usize,
+    compressed_len: usize,
+}
+
+impl CompressionStats {
+    /// Returns the length of the serialized data before compression.
+    #[must_use]
+    pub fn serialized_len(&self) -> usize {
+        self.serialized_len
+    }
+
+    /// Returns the length of the encoded frame, including any algorithm tag.
+    #[must_use]
+    pub fn compressed_len(&self) -> usize {
+        self.compressed_len
+    }
+
+    /// Returns the serialized length divided by the compressed length, higher is better.
+    #[must_use]
+    #[allow(clippy::cast_precision_loss)]
+    pub fn ratio(&self) -> f64 {
+        self.serialized_len as f64 / self.compressed_len as f64
+    }
+}
+
+/// Collects the options of a [`Codec`] and validates them together in [`CodecBuilder::build`],
+/// created with [`Codec::builder`].
+///
+/// Every option starts out like [`Codec::default`], and the setters mirror the `with_`
+/// constructors and methods of [`Codec`].
+///
+/// The builder is also the serialized form of a [`Codec`], options missing when deserializing
+/// keep their defaults.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(default)]
+#[allow(clippy::struct_excessive_bools)]
+pub struct CodecBuilder {
+    level: i32,
+    algorithm_tag: bool,
+    header: bool,
+    checksum: bool,
+    sorted_maps: bool,
+    format: Format,
+    workers: u32,
+    dictionary: Option<Arc<[u8]>>,
+}
+
+impl CodecBuilder {
+    /// Sets the compression level, see [`Codec::new`]. It is only checked by
+    /// [`CodecBuilder::build`].
+    #[must_use]
+    pub fn level(self, level: i32) -> Self {
+        Self { level, ..self }
+    }
+
+    /// Sets the [`Format`] the data is serialized with, see [`Codec::with_format`].
+    #[must_use]
+    pub fn format(self, format: Format) -> Self {
+        Self { format, ..self }
+    }
+
+    /// Enables the xxHash64 checksum trailer, see [`Codec::with_checksum`].
+    #[cfg(feature = "xxhash")]
+    #[must_use]
+    pub fn checksum(self, checksum: bool) -> Self {
+        Self { checksum, ..self }
+    }
+
+    /// Enables the `EPAR` frame header, see [`Codec::with_header`].
+    #[must_use]
+    pub fn header(self, header: bool) -> Self {
+        Self { header, ..self }
+    }
+
+    /// Enables the algorithm tag, see [`Codec::with_algorithm_tag`].
+    #[must_use]
+    pub fn algorithm_tag(self, algorithm_tag: bool) -> Self {
+        Self {
+            algorithm_tag,
+            ..self
+        }
+    }
+
+    /// Enables sorting map entries by their encoded keys, see [`Codec::with_sorted_maps`].
+    #[must_use]
+    pub fn sorted_maps(self, sorted_maps: bool) -> Self {
+        Self {
+            sorted_maps,
+            ..self
+        }
+    }
+
+    /// Sets the number of zstd worker threads, see [`Codec::with_workers`].
+    #[cfg(feature = "zstdmt")]
+    #[must_use]
+    pub fn workers(self, workers: u32) -> Self {
+        Self { workers, ..self }
+    }
+
+    /// Sets the zstd dictionary, see [`Codec::with_dictionary_ref`]. It is digested by
+    /// [`CodecBuilder::build`], at the level set by then.
+    #[must_use]
+    pub fn dictionary<D: Into<Arc<[u8]>>>(self, dictionary: D) -> Self {
+        Self {
+            dictionary: Some(dictionary.into()),
+            ..self
+        }
+    }
+
+    /// Creates the configured [`Codec`].
+    ///
+    /// # Errors
+    ///
+    /// Return `epoch_archive::CodecError::InvalidLevel` if the level is outside the range
+    /// reported by `zstd::compression_level_range`.
+    pub fn build(self) -> Result<Codec> {
+        let codec = Codec::try_new(self.level)?;
+
+        Ok(Codec {
+            algorithm_tag: self.algorithm_tag,
+            header: self.header,
+            checksum: self.checksum,
+            sorted_maps: self.sorted_maps,
+            format: self.format,
+            workers: self.workers,
+            dictionary: self
+                .dictionary
+                .map(|dictionary| Dictionary::new(dictionary, self.level)),
+            ..codec
+        })
+    }
+}
+
+impl Default for CodecBuilder {
+    fn default() -> Self {
+        Self {
+            level: DEFAULT_LEVEL,
+            algorithm_tag: false,
+            header: false,
+            checksum: false,
+            sorted_maps: false,
+            format: Format::MessagePack,
+            workers: 0,
+            dictionary: None,
+        }
+    }
+}
+
+impl From<Codec> for CodecBuilder {
+    fn from(codec: Codec) -> Self {
+        Self {
+            level: codec.level,
+            algorithm_tag: codec.algorithm_tag,
+            header: codec.header,
+            checksum: codec.checksum,
+            sorted_maps: codec.sorted_maps,
+            format: codec.format,
+            workers: codec.workers,
+            dictionary: codec.dictionary.map(|dictionary| dictionary.raw),
+        }
+    }
+}
+
+impl TryFrom<CodecBuilder> for Codec {
+    type Error = CodecError;
+
+    /// Builds the codec like [`CodecBuilder::build`].
+    fn try_from(builder: CodecBuilder) -> Result<Self> {
+        builder.build()
+    }
+}
+
+impl Default for Codec {
+    fn default() -> Self {
+        Self {
+            level: DEFAULT_LEVEL,
+            algorithm_tag: false,
+            header: false,
+            checksum: false,
+            sorted_maps: false,
+            format: Format::MessagePack,
+            workers: 0,
+            dictionary: None,
+        }
+    }
+}
+
+thread_local! {
+    /// The serialization buffer [`Codec::encode_into`] reuses on the current thread.
+    static ENCODE_SCRATCH: std::cell::Cell<Vec<u8>> = const { std::cell::Cell::new(Vec::new()) };
+
+    /// The compressor [`Codec::encode_using`] reuses on the current thread for codecs without a
+    /// dictionary.
+    static ENCODE_COMPRESSOR: std::cell::Cell<Option<zstd::bulk::Compressor<'static>>> =
+        const { std::cell::Cell::new(None) };
+}
+
+#[cfg(test)]
+thread_local! {
+    /// Counts how many times a dictionary was digested on the current thread.
+    static DICTIONARY_LOADS: std::cell::Cell<usize> = const { std::cell::Cell::new(0) };
+}
+
+/// A zstd dictionary, digested once for compression and once for decompression.
+///
+/// Equality and ordering only consider the raw dictionary bytes.
+#[derive(Clone)]
+struct Dictionary {
+    raw: Arc<[u8]>,
+    encoder: Arc<EncoderDictionary<'static>>,
+    decoder: Arc<DecoderDictionary<'static>>,
+}
+
+impl Dictionary {
+    fn new(raw: Arc<[u8]>, level: i32) -> Self {
+        #[cfg(test)]
+        DICTIONARY_LOADS.with(|loads| loads.set(loads.get() + 1));
+
+        Self {
+            encoder: Arc::new(EncoderDictionary::copy(&raw, level)),
+            decoder: Arc::new(DecoderDictionary::copy(&raw)),
+            raw,
+        }
+    }
+}
+
+impl std::fmt::Debug for Dictionary {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("Dictionary")
+            .field("len", &self.raw.len())
+            .finish_non_exhaustive()
+    }
+}
+
+impl PartialEq for Dictionary {
+    fn eq(&self, other: &Self) -> bool {
+        self.raw == other.raw
+    }
+}
+
+impl Eq for Dictionary {}
+
+impl PartialOrd for Dictionary {
+    fn partial_cmp(&self, other: &Self) -> Option<std::cmp::Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+impl Ord for Dictionary {
+    fn cmp(&self, other: &Self) -> std::cmp::Ordering {
+        self.raw.cmp(&other.raw)
+    }
+}
+
+/// An object-safe view of a codec that operates on already serialized bytes.
+///
+/// [`Codec::encode`] and [`Codec::decode`] are generic and can't be called through a trait object,
+/// so this trait only covers the compression step. This allows the codec to be chosen at runtime,
+/// e.g. by storing a `Box<dyn DynCodec>`.
+pub trait DynCodec {
+    /// Compresses already serialized bytes.
+    ///
+    /// # Errors
+    ///
+    /// Return `epoch_archive::CodecError` if there is an issue compressing the data.
+    fn encode_bytes(&self, data: &[u8]) -> Result<Vec<u8>>;
+
+    /// Decompresses bytes produced by [`DynCodec::encode_bytes`].
+    ///
+    /// # Errors
+    ///
+    /// Return `epoch_archive::CodecError` if there is an issue decompressing the data.
+    fn decode_bytes(&self, data: &[u8]) -> Result<Vec<u8>>;
+}
+
+/// Writes `epoch` as a `MessagePack` timestamp extension.
+fn timestamp_ext(epoch: &Epoch) -> Vec<u8> {
+    let seconds = epoch.epoch();
+    let nanos = epoch.subsecond().as_nanos();
+
+    let mut ext = Vec::with_capacity(15);
+    match u64::try_from(seconds) {
+        Ok(seconds) if nanos == 0 && u32::try_from(seconds).is_ok() => {
+            ext.extend([0xd6, TIMESTAMP_EXT_TYPE]);
+            ext.extend_from_slice(&seconds.to_be_bytes()[4..]);
+        }
+        Ok(seconds) if seconds >> 34 == 0 => {
+            ext.extend([0xd7, TIMESTAMP_EXT_TYPE]);
+            ext.extend_from_slice(&(nanos << 34 | seconds).to_be_bytes());
+        }
+        _ => {
+            ext.extend([0xc7, 12, TIMESTAMP_EXT_TYPE]);
+            ext.extend_from_slice(&nanos.to_be_bytes()[4..]);
+            ext.extend_from_slice(&seconds.to_be_bytes());
+        }
+    }
+
+    ext
+}
+
+/// Reads a `MessagePack` timestamp extension written in any of its three layouts.
+fn epoch_from_timestamp_ext(ext: &[u8]) -> Result<Epoch> {
+    let (seconds, nanos) = match ext {
+        [0xd6, TIMESTAMP_EXT_TYPE, data @ ..] => {
+            let data = data.try_into().map_err(|_| CodecError::InvalidTimestamp)?;
+            return Ok(Epoch::new(i64::from(u32::from_be_bytes(data))));
+        }
+        [0xd7, TIMESTAMP_EXT_TYPE, data @ ..] => {
+            let data = data.try_into().map_err(|_| CodecError::InvalidTimestamp)?;
+            let value = u64::from_be_bytes(data);
+            ((value & 0x3_ffff_ffff).cast_signed(), value >> 34)
+        }
+        [
+            0xc7,
+            12,
+            TIMESTAMP_EXT_TYPE,
+            nanos @ ..,
+            s0,
+            s1,
+            s2,
+            s3,
+            s4,
+            s5,
+            s6,
+            s7,
+        ] => {
+            let nanos: [u8; 4] = nanos.try_into().map_err(|_| CodecError::InvalidTimestamp)?;
+            (
+                i64::from_be_bytes([*s0, *s1, *s2, *s3, *s4, *s5, *s6, *s7]),
+                u64::from(u32::from_be_bytes(nanos)),
+            )
+        }
+        _ => return Err(CodecError::InvalidTimestamp),
+    };
+
+    if nanos >= 1_000_000_000 {
+        return Err(CodecError::InvalidTimestamp);
+    }
+
+    Ok(Epoch::new(seconds).with_nanos(nanos))
+}
+
+/// Returns the tag byte of `precision`, its index in [`DELTA_PRECISIONS`].
+fn precision_tag(precision: Precision) -> u8 {
+    match precision {
+        Precision::Second => 0,
+        Precision::Milli => 1,
+        Precision::Micro => 2,
+        Precision::Nano => 3,
+    }
+}
+
+/// Appends `value` to `out` zig-zag encoded as a LEB128 varint, so small differences in either
+/// direction take few bytes.
+#[allow(clippy::cast_possible_truncation)]
+fn write_zigzag_varint(out: &mut Vec<u8>, value: i128) {
+    let mut zigzag = ((value << 1) ^ (value >> 127)).cast_unsigned();
+    while zigzag >= 0x80 {
+        out.push((zigzag as u8) | 0x80);
+        zigzag >>= 7;
+    }
+    out.push(zigzag as u8);
+}
+
+/// Reads a varint written by [`write_zigzag_varint`] off the front of `rest`.
+fn read_zigzag_varint(rest: &mut &[u8]) -> Result<i128> {
+    let mut zigzag: u128 = 0;
+    for shift in (0..MAX_VARINT_128_LEN * 7).step_by(7) {
+        let (&byte, remaining) = rest.split_first().ok_or(CodecError::TruncatedFrame)?;
+        *rest = remaining;
+
+        let bits = u128::from(byte & 0x7f);
+        if shift == 126 && bits > 3 {
+            return Err(CodecError::InvalidVarint);
+        }
+        zigzag |= bits << shift;
+
+        if byte & 0x80 == 0 {
+            return Ok((zigzag >> 1).cast_signed() ^ -(zigzag & 1).cast_signed());
+        }
+    }
+
+    Err(CodecError::InvalidVarint)
+}
+
+/// Runs `f` on tokio's blocking thread pool, resuming its panic on the calling task if it
+/// panicked.
+#[cfg(feature = "tokio")]
+async fn run_blocking<T, F>(f: F) -> Result<T>
+where
+    T: Send + 'static,
+    F: FnOnce() -> Result<T> + Send + 'static,
+{
+    match tokio::task::spawn_blocking(f).await {
+        Ok(result) => result,
+        Err(err) => std::panic::resume_unwind(err.into_panic()),
+    }
+}
+
+/// Splits the next frame written by [`Codec::encode_frames`] off the front of `rest`, without
+/// its length prefix.
+fn split_length_prefixed<'a>(rest: &mut &'a [u8]) -> Result<&'a [u8]> {
+    let (len, remaining) = rest
+        .split_first_chunk::<8>()
+        .ok_or(CodecError::TruncatedFrame)?;
+    let len = usize::try_from(u64::from_le_bytes(*len)).map_err(|_| CodecError::TruncatedFrame)?;
+    let (frame, remaining) = remaining
+        .split_at_checked(len)
+        .ok_or(CodecError::TruncatedFrame)?;
+    *rest = remaining;
+
+    Ok(frame)
+}
+
+/// Passes writes through to `inner`, counting the bytes written and feeding them to `checksum`
+/// when there is one.
+struct CountingWriter<W> {
+    inner: W,
+    written: u64,
+    #[cfg(feature = "xxhash")]
+    checksum: Option<xxhash_rust::xxh64::Xxh64>,
+}
+
+impl<W: Write> Write for CountingWriter<W> {
+    fn write(&mut self, buf: &[u8]) -> std::io::Result<usize> {
+        let len = self.inner.write(buf)?;
+        self.written += len as u64;
+        #[cfg(feature = "xxhash")]
+        if let Some(checksum) = &mut self.checksum {
+            checksum.update(&buf[..len]);
+        }
+        Ok(len)
+    }
+
+    fn flush(&mut self) -> std::io::Result<()> {
+        self.inner.flush()
+    }
+}
+
+/// Passes reads through from `inner`, feeding the bytes that are consumed to `checksum`.
+#[cfg(feature = "xxhash")]
+struct ChecksumReader<R> {
+    inner: R,
+    checksum: xxhash_rust::xxh64::Xxh64,
+}
+
+#[cfg(feature = "xxhash")]
+impl<R: BufRead> Read for ChecksumReader<R> {
+    fn read(&mut self, buf: &mut [u8]) -> std::io::Result<usize> {
+        let len = self.inner.read(buf)?;
+        self.checksum.update(&buf[..len]);
+        Ok(len)
+    }
+}
+
+#[cfg(feature = "xxhash")]
+impl<R: BufRead> BufRead for ChecksumReader<R> {
+    fn fill_buf(&mut self) -> std::io::Result<&[u8]> {
+        self.inner.fill_buf()
+    }
+
+    fn consume(&mut self, amt: usize) {
+        // The buffer is already filled, so this only borrows it again.
+        if let Ok(buf) = self.inner.fill_buf() {
+            self.checksum.update(&buf[..amt]);
+        }
+        self.inner.consume(amt);
+    }
+}
+
+/// Compares the checksum stored in a frame against the one computed over it.
+#[cfg(feature = "xxhash")]
+fn verify_checksum(expected: u64, actual: u64) -> Result<()> {
+    if expected != actual {
+        return Err(CodecError::ChecksumMismatch {
+            expected: format!("{expected:016x}"),
+            actual: format!("{actual:016x}"),
+        });
+    }
+
+    Ok(())
+}
+
+/// Returns the path of the `.sha256` sidecar belonging to `path`.
+#[cfg(feature = "sha256")]
+fn sidecar_path(path: &Path) -> PathBuf {
+    let mut sidecar = path.as_os_str().to_owned();
+    sidecar.push(".sha256");
+    PathBuf::from(sidecar)
+}
+
+/// Returns the lowercase hex encoded SHA-256 digest of `data`.
+#[cfg(feature = "sha256")]
+fn sha256_hex(data: &[u8]) -> String {
+    use sha2::{Digest, Sha256};
+    use std::fmt::Write;
+
+    Sha256::digest(data)
+        .iter()
+        .fold(String::with_capacity(64), |mut hex, byte| {
+            write!(hex, "{byte:02x}").expect("writing to a String cannot fail");
+            hex
+        })
+}
+
+/// Drives a sequence element by element, handing each one to `f`.
+struct SeqVisitor<T, F> {
+    f: F,
+    marker: PhantomData<T>,
+}
+
+impl<'de, T, F> Visitor<'de> for SeqVisitor<T, F>
+where
+    T: Deserialize<'de>,
+    F: FnMut(T),
+{
+    type Value = ();
+
+    fn expecting(&self, formatter: &mut std::fmt::Formatter) -> std::fmt::Result {
+        formatter.write_str("a sequence")
+    }
+
+    fn visit_seq<A: SeqAccess<'de>>(mut self, mut seq: A) -> Result<(), A::Error> {
+        while let Some(element) = seq.next_element()? {
+            (self.f)(element);
+        }
+
+        Ok(())
+    }
+}
+
+impl DynCodec for Codec {
+    fn encode_bytes(&self, data: &[u8]) -> Result<Vec<u8>> {
+        self.compress(data)
+    }
+
+    fn decode_bytes(&self, data: &[u8]) -> Result<Vec<u8>> {
+        self.decompress(data)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::SubSecond;
+
+    use std::collections::HashMap;
+
+    #[test]
+    fn test_new() {
+        let codec = Codec::new(3);
+        assert_eq!(codec.level, 3);
+    }
+
+    #[test]
+    fn test_getters() {
+        let codec = Codec::new(-2).with_format(Format::MessagePack);
+        assert_eq!(codec.level(), -2);
+        assert_eq!(codec.format(), Format::MessagePack);
+        assert_eq!(Codec::default().level(), DEFAULT_LEVEL);
+        assert_eq!(Codec::default().format(), Format::default());
+
+        #[cfg(feature = "json")]
+        assert_eq!(
+            Codec::new(3).with_format(Format::Json).format(),
+            Format::Json
+        );
+    }
+
+    #[test]
+    fn test_default() {
+        let codec = Codec::default();
+        assert_eq!(codec.level, 9);
+    }
+
+    #[test]
+    #[should_panic(expected = "InvalidLevel(23)")]
+    fn test_new_too_high_level() {
+        #[allow(unused_must_use)]
+        Codec::new(23);
+    }
+
+    #[test]
+    fn test_try_new() {
+        let (min, max) = zstd::compression_level_range().into_inner();
+
+        assert_eq!(Codec::try_new(11).unwrap().level, 11);
+        assert_eq!(Codec::try_new(max).unwrap().level, max);
+        assert_eq!(Codec::try_new(min).unwrap().level, min);
+        assert!(matches!(
+            Codec::try_new(23),
+            Err(CodecError::InvalidLevel(23))
+        ));
+        assert!(matches!(
+            Codec::try_new(min - 1),
+            Err(CodecError::InvalidLevel(level)) if level == min - 1
+        ));
+        assert!(matches!(
+            Codec::try_new(i32::MIN),
+            Err(CodecError::InvalidLevel(i32::MIN))
+        ));
+    }
+
+    #[test]
+    fn test_compress() {
+        let data = vec![1, 2, 3, 4, 5];
+
+        for i in 0..22 {
+            let codec = Codec::new(i);
+            let compressed = codec.compress(&data).unwrap();
+            assert_ne!(data, compressed);
+        }
+    }
+
+    #[test]
+    fn test_decompress() {
+        let expected = vec![1, 2, 3, 4, 5];
+        let compressed = [40, 181, 47, 253, 0, 72, 41, 0, 0, 1, 2, 3, 4, 5];
+        let codec = Codec::new(1);
+
+        let decompressed = codec.decompress(&compressed).unwrap();
+        assert_eq!(decompressed, expected);
+    }
+
+    #[test]
     fn test_decompress_fail_invalid_data() {
         let invalid: [u8; 14] = [
             255, 255, 255, 255, 255, 255, 255, 255, 255, 255, 255, 255, 255, 255,
         ];
         let codec = Codec::new(1);
 
-        let decompressed = codec.decompress(&invalid);
-        assert!(decompressed.is_err());
+        let decompressed = codec.decompress(&invalid);
+        assert!(decompressed.is_err());
+    }
+
+    #[test]
+    fn test_encode() {
+        let data = vec![1, 2, 3, 4, 5];
+        let codec = Codec::new(1);
+
+        let encoded = codec.encode(&data).unwrap();
+        let expected = [40, 181, 47, 253, 32, 6, 49, 0, 0, 149, 1, 2, 3, 4, 5];
+        assert_eq!(encoded, expected);
+    }
+
+    #[test]
+    fn test_decode() {
+        let encoded = [40, 181, 47, 253, 0, 72, 49, 0, 0, 149, 1, 2, 3, 4, 5];
+        let expected = vec![1, 2, 3, 4, 5];
+        let codec = Codec::new(1);
+
+        let decoded = codec.decode::<Vec<u8>>(&encoded).unwrap();
+        assert_eq!(decoded, expected);
+    }
+
+    #[test]
+    fn test_algorithm_tag() {
+        let data = vec![1, 2, 3, 4, 5];
+        let codec = Codec::new(1).with_algorithm_tag(true);
+
+        let encoded = codec.encode(&data).unwrap();
+        let expected = [0, 40, 181, 47, 253, 32, 6, 49, 0, 0, 149, 1, 2, 3, 4, 5];
+        assert_eq!(encoded, expected);
+
+        let decoded = codec.decode::<Vec<u8>>(&encoded).unwrap();
+        assert_eq!(decoded, data);
+    }
+
+    #[test]
+    fn test_algorithm_tag_unknown() {
+        let codec = Codec::new(1).with_algorithm_tag(true);
+        let mut encoded = codec.encode(&vec![1, 2, 3, 4, 5]).unwrap();
+        encoded[0] = 7;
+
+        let decoded = codec.decode::<Vec<u8>>(&encoded);
+        assert!(matches!(decoded, Err(CodecError::UnknownAlgorithm(7))));
+    }
+
+    #[test]
+    fn test_algorithm_tag_empty() {
+        let codec = Codec::new(1).with_algorithm_tag(true);
+
+        let decompressed = codec.decompress(&[]);
+        assert!(matches!(decompressed, Err(CodecError::EmptyInput)));
+    }
+
+    #[test]
+    fn test_header() {
+        let data = vec![1, 2, 3, 4, 5];
+        let codec = Codec::new(1).with_header(true).with_algorithm_tag(true);
+
+        let encoded = codec.encode(&data).unwrap();
+        assert_eq!(encoded[..6], [b'E', b'P', b'A', b'R', HEADER_VERSION, 0]);
+        assert_eq!(
+            encoded[5..],
+            Codec::new(1)
+                .with_algorithm_tag(true)
+                .encode(&data)
+                .unwrap()
+        );
+        assert_eq!(codec.decode::<Vec<u8>>(&encoded).unwrap(), data);
+
+        let mut streamed = Vec::new();
+        codec
+            .compress_stream(&b"streamed"[..], &mut streamed)
+            .unwrap();
+        assert_eq!(codec.decompress(&streamed).unwrap(), b"streamed");
+    }
+
+    #[test]
+    fn test_header_invalid() {
+        let codec = Codec::new(1).with_header(true);
+        let encoded = codec.encode(&vec![1, 2, 3, 4, 5]).unwrap();
+
+        let plain = Codec::new(1).encode(&vec![1, 2, 3, 4, 5]).unwrap();
+        assert!(matches!(
+            codec.decompress(&plain),
+            Err(CodecError::UnknownFormat)
+        ));
+        assert!(matches!(
+            codec.decompress(b"EPA"),
+            Err(CodecError::UnknownFormat)
+        ));
+        assert!(matches!(
+            codec.decompress(b"EPAR"),
+            Err(CodecError::TruncatedFrame)
+        ));
+
+        let mut corrupted = encoded.clone();
+        corrupted[1] ^= 0xff;
+        assert!(matches!(
+            codec.decompress(&corrupted),
+            Err(CodecError::UnknownFormat)
+        ));
+
+        let mut future = encoded.clone();
+        future[4] = HEADER_VERSION + 1;
+        assert!(matches!(
+            codec.decompress(&future),
+            Err(CodecError::VersionMismatch { expected: HEADER_VERSION, found }) if found == HEADER_VERSION + 1
+        ));
+        assert!(matches!(
+            codec.decompress_stream(future.as_slice(), Vec::new()),
+            Err(CodecError::VersionMismatch { .. })
+        ));
+
+        assert!(Codec::new(1).decode::<Vec<u8>>(&encoded).is_err());
+    }
+
+    #[test]
+    fn test_sorted_maps() {
+        let codec = Codec::new(1).with_sorted_maps(true);
+
+        // Each map gets its own random hasher state, and the second is also sized and filled
+        // differently, so they iterate in different orders.
+        let first: HashMap<String, u32> = (0..100).map(|i| (format!("key-{i}"), i)).collect();
+        let mut second = HashMap::with_capacity(1024);
+        second.extend((0..100).rev().map(|i| (format!("key-{i}"), i)));
+        assert_ne!(
+            first.keys().collect::<Vec<_>>(),
+            second.keys().collect::<Vec<_>>()
+        );
+
+        let encoded = codec.encode(&first).unwrap();
+        assert_eq!(encoded, codec.encode(&second).unwrap());
+        assert_eq!(
+            codec.decode::<HashMap<String, u32>>(&encoded).unwrap(),
+            first
+        );
+    }
+
+    #[test]
+    fn test_decode_paged_invalid() {
+        let codec = Codec::new(1);
+        let data: Vec<u64> = (0..50).map(|_| rand::random()).collect();
+        let pages = codec.encode_paged::<32, _>(&data).unwrap();
+        assert!(pages.len() > 2);
+
+        let missing = [&pages[..1], &pages[2..]].concat();
+        let decoded = codec.decode_paged::<32, Vec<u64>>(&missing);
+        assert!(matches!(decoded, Err(CodecError::InvalidPageSequence(2))));
+
+        let duplicate = [&pages[..], &pages[1..2]].concat();
+        let decoded = codec.decode_paged::<32, Vec<u64>>(&duplicate);
+        assert!(matches!(decoded, Err(CodecError::InvalidPageSequence(1))));
+
+        let decoded = codec.decode_paged::<32, Vec<u64>>(&pages[..pages.len() - 1]);
+        assert!(matches!(decoded, Err(CodecError::InvalidPageSequence(_))));
+
+        let mut oversized = pages.clone();
+        oversized[0][5..9].copy_from_slice(&24u32.to_le_bytes());
+        let decoded = codec.decode_paged::<32, Vec<u64>>(&oversized);
+        assert!(matches!(decoded, Err(CodecError::TruncatedFrame)));
+
+        let decoded = codec.decode_paged::<32, Vec<u64>>(&[]);
+        assert!(matches!(decoded, Err(CodecError::EmptyInput)));
+    }
+
+    #[test]
+    fn test_decode_or_migrate() {
+        #[derive(Serialize, Deserialize)]
+        struct V1 {
+            name: String,
+            count: u32,
+        }
+
+        #[derive(Debug, PartialEq, Serialize, Deserialize)]
+        struct V2 {
+            name: String,
+            count: u64,
+            tags: Vec<String>,
+        }
+
+        let migrate = |old: V1| V2 {
+            name: old.name,
+            count: u64::from(old.count),
+            tags: vec![String::from("migrated")],
+        };
+        let codec = Codec::new(1);
+
+        let old = V1 {
+            name: String::from("sensor"),
+            count: 7,
+        };
+        let decoded = codec.decode_or_migrate(&codec.encode(&old).unwrap(), migrate);
+        assert_eq!(
+            decoded.unwrap(),
+            V2 {
+                name: String::from("sensor"),
+                count: 7,
+                tags: vec![String::from("migrated")],
+            }
+        );
+
+        let new = V2 {
+            name: String::from("sensor"),
+            count: 8,
+            tags: vec![],
+        };
+        let decoded = codec.decode_or_migrate(&codec.encode(&new).unwrap(), migrate);
+        assert_eq!(decoded.unwrap(), new);
+
+        let decoded = codec.decode_or_migrate::<V2, V1, _>(&codec.encode(&42).unwrap(), migrate);
+        assert!(matches!(
+            decoded,
+            Err(CodecError::DecodeContext { type_name, .. }) if type_name.ends_with("V2")
+        ));
+    }
+
+    #[test]
+    fn test_compress_stream() {
+        let data: Vec<u8> = (0..8 * 1024 * 1024u32)
+            .map(|i| {
+                let [low, _, high, _] = (i % 251 + (i >> 16)).to_le_bytes();
+                low ^ high
+            })
+            .collect();
+        let codecs = [
+            Codec::new(1),
+            Codec::new(3).with_algorithm_tag(true),
+            Codec::with_dictionary_ref(3, sample_dictionary()),
+        ];
+
+        for codec in codecs {
+            let mut frame = std::io::Cursor::new(Vec::new());
+            let written = codec
+                .compress_stream(std::io::Cursor::new(&data), &mut frame)
+                .unwrap();
+            assert_eq!(written, frame.get_ref().len() as u64);
+            assert!(frame.get_ref().len() < data.len());
+            assert_eq!(codec.decompress(frame.get_ref()).unwrap(), data);
+
+            let mut decompressed = std::io::Cursor::new(Vec::new());
+            let written = codec
+                .decompress_stream(std::io::Cursor::new(frame.get_ref()), &mut decompressed)
+                .unwrap();
+            assert_eq!(written, data.len() as u64);
+            assert_eq!(decompressed.into_inner(), data);
+        }
+    }
+
+    #[test]
+    fn test_decompress_stream_invalid() {
+        let codec = Codec::new(1).with_algorithm_tag(true);
+
+        let decompressed = codec.decompress_stream(&[][..], Vec::new());
+        assert!(matches!(decompressed, Err(CodecError::EmptyInput)));
+
+        let decompressed = codec.decompress_stream(&[7, 0, 0][..], Vec::new());
+        assert!(matches!(decompressed, Err(CodecError::UnknownAlgorithm(7))));
+    }
+
+    #[test]
+    fn test_encode_into() {
+        let codec = Codec::new(3);
+        let mut out = Vec::with_capacity(1024);
+        let capacity = out.capacity();
+        let (mut scratch, mut expected) = (Vec::new(), Vec::new());
+
+        for i in 0..100u64 {
+            let record = (i, "sensor-reading", vec![i; 8]);
+            codec.encode_into(&record, &mut out).unwrap();
+            codec
+                .encode_using(&record, &mut scratch, &mut expected)
+                .unwrap();
+
+            assert_eq!(out, expected);
+            assert_eq!(
+                codec.decode::<(u64, String, Vec<u64>)>(&out).unwrap(),
+                codec
+                    .decode::<(u64, String, Vec<u64>)>(&codec.encode(&record).unwrap())
+                    .unwrap()
+            );
+        }
+        assert_eq!(out.capacity(), capacity);
+    }
+
+    #[test]
+    fn test_decompress_into() {
+        let codecs = [
+            Codec::new(1),
+            Codec::new(1).with_header(true).with_algorithm_tag(true),
+            Codec::with_dictionary_ref(3, sample_dictionary()),
+        ];
+        let inputs = [
+            b"x".repeat(100_000),
+            b"a shorter frame".to_vec(),
+            Vec::new(),
+            (0..=255u8).cycle().take(5_000).collect(),
+        ];
+
+        for codec in codecs {
+            let mut out = Vec::new();
+            for input in &inputs {
+                let compressed = codec.compress(input).unwrap();
+                codec.decompress_into(&compressed, &mut out).unwrap();
+                assert_eq!(&out, input);
+                assert_eq!(out, codec.decompress(&compressed).unwrap());
+            }
+            assert!(out.capacity() >= 100_000);
+
+            out.extend_from_slice(b"stale");
+            assert!(matches!(
+                codec.decompress_into(&[], &mut out),
+                Err(CodecError::EmptyInput)
+            ));
+            assert!(out.is_empty());
+        }
+    }
+
+    #[test]
+    fn test_serialize_into() {
+        let mut buf = vec![0xff; 64];
+        Codec::serialize_into(&"reused", &mut buf).unwrap();
+
+        assert_eq!(buf, Codec::serialize(&"reused").unwrap());
+    }
+
+    #[test]
+    fn test_decompress_empty() {
+        let codecs = [
+            Codec::new(1),
+            Codec::with_dictionary_ref(3, sample_dictionary()),
+        ];
+
+        for codec in codecs {
+            assert!(matches!(codec.decompress(&[]), Err(CodecError::EmptyInput)));
+        }
+    }
+
+    #[test]
+    fn test_decode_empty() {
+        let codec = Codec::new(1);
+
+        let decoded = codec.decode::<Vec<u32>>(&[]);
+        assert!(matches!(decoded, Err(CodecError::EmptyInput)));
+
+        let compressed = codec.compress(&[]).unwrap();
+        assert_eq!(codec.decompress(&compressed).unwrap(), [0u8; 0]);
+    }
+
+    fn sample_dictionary() -> Arc<[u8]> {
+        (0..100u32)
+            .flat_map(|i| Codec::serialize(&(i, "sensor-reading", vec![i; 4])).unwrap())
+            .collect()
+    }
+
+    #[test]
+    fn test_with_dictionary_ref() {
+        let loads = DICTIONARY_LOADS.with(std::cell::Cell::get);
+        let codec = Codec::with_dictionary_ref(3, sample_dictionary());
+        let cloned = codec.clone();
+
+        for i in 0..1000u32 {
+            let record = (i, "sensor-reading", vec![i; 4]);
+            let encoded = codec.encode(&record).unwrap();
+            let decoded = cloned.decode::<(u32, String, Vec<u32>)>(&encoded).unwrap();
+            assert_eq!(decoded.0, record.0);
+            assert_eq!(decoded.1, record.1);
+            assert_eq!(decoded.2, record.2);
+        }
+
+        assert_eq!(DICTIONARY_LOADS.with(std::cell::Cell::get), loads + 1);
+    }
+
+    #[test]
+    fn test_with_dictionary_ref_smaller() {
+        let record = (42u32, "sensor-reading", vec![42u32; 4]);
+        let plain = Codec::new(3).encode(&record).unwrap();
+        let with_dictionary = Codec::with_dictionary_ref(3, sample_dictionary())
+            .encode(&record)
+            .unwrap();
+
+        assert!(with_dictionary.len() < plain.len());
+    }
+
+    #[test]
+    fn test_with_dictionary_ref_requires_dictionary() {
+        let codec = Codec::with_dictionary_ref(3, sample_dictionary());
+        let encoded = codec.encode(&(42u32, "sensor-reading")).unwrap();
+
+        assert!(Codec::new(3).decompress(&encoded).is_err());
+    }
+
+    #[test]
+    fn test_with_dictionary_ref_algorithm_tag() {
+        let codec = Codec::with_dictionary_ref(3, sample_dictionary()).with_algorithm_tag(true);
+        let encoded = codec.encode(&(42u32, "sensor-reading")).unwrap();
+
+        assert_eq!(encoded[0], ZSTD_ALGORITHM_ID);
+        let decoded = codec.decode::<(u32, String)>(&encoded).unwrap();
+        assert_eq!(decoded, (42, String::from("sensor-reading")));
+    }
+
+    #[test]
+    fn test_encode_epoch_ext() {
+        let codec = Codec::new(1);
+        let epochs: [(Epoch, &[u8]); 5] = [
+            (
+                Epoch::new(1_700_000_000),
+                &[0xd6, 0xff, 0x65, 0x53, 0xf1, 0x00],
+            ),
+            (
+                Epoch::new(1_700_000_000).with_millis(500),
+                &[0xd7, 0xff, 0x77, 0x35, 0x94, 0x00, 0x65, 0x53, 0xf1, 0x00],
+            ),
+            (
+                Epoch::new(1_700_000_000).with_nanos(500_000_000),
+                &[0xd7, 0xff, 0x77, 0x35, 0x94, 0x00, 0x65, 0x53, 0xf1, 0x00],
+            ),
+            (
+                Epoch::new(-1),
+                &[
+                    0xc7, 12, 0xff, 0, 0, 0, 0, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff,
+                ],
+            ),
+            (
+                Epoch::new(1 << 34).with_nanos(1),
+                &[0xc7, 12, 0xff, 0, 0, 0, 1, 0, 0, 0, 4, 0, 0, 0, 0],
+            ),
+        ];
+
+        for (epoch, expected) in epochs {
+            let encoded = codec.encode_epoch_ext(&epoch).unwrap();
+            assert_eq!(codec.decompress(&encoded).unwrap(), expected);
+        }
+    }
+
+    #[test]
+    fn test_decode_epoch_ext() {
+        let codec = Codec::new(1);
+        let epochs = [
+            (Epoch::new(0), Epoch::new(0)),
+            (Epoch::new(u32::MAX.into()), Epoch::new(u32::MAX.into())),
+            (
+                Epoch::new(1).with_millis(1),
+                Epoch::new(1).with_nanos(1_000_000),
+            ),
+            (Epoch::new(1).with_millis(0), Epoch::new(1)),
+            (
+                Epoch::new(-1).with_micros(1),
+                Epoch::new(-1).with_nanos(1_000),
+            ),
+            (
+                Epoch::new(i64::MIN).with_nanos(999_999_999),
+                Epoch::new(i64::MIN).with_nanos(999_999_999),
+            ),
+            (Epoch::new(i64::MAX), Epoch::new(i64::MAX).with_nanos(0)),
+        ];
+
+        for (epoch, expected) in epochs {
+            let encoded = codec.encode_epoch_ext(&epoch).unwrap();
+            assert_eq!(codec.decode_epoch_ext(&encoded).unwrap(), expected);
+        }
+    }
+
+    #[test]
+    fn test_decode_epoch_ext_invalid() {
+        let codec = Codec::new(1);
+        let invalid: [&[u8]; 5] = [
+            &[],
+            &[0xd6, 0xff, 0, 0, 0],
+            &[0xd6, 0x00, 0, 0, 0, 0],
+            &[0xd7, 0xff, 0xff, 0xff, 0xff, 0xff, 0, 0, 0, 0],
+            &[
+                0xc7, 12, 0xff, 0x3b, 0x9a, 0xca, 0x00, 0, 0, 0, 0, 0, 0, 0, 0,
+            ],
+        ];
+
+        for ext in invalid {
+            let compressed = codec.compress(ext).unwrap();
+            assert!(matches!(
+                codec.decode_epoch_ext(&compressed),
+                Err(CodecError::InvalidTimestamp)
+            ));
+        }
+    }
+
+    #[test]
+    fn test_encode_epochs() {
+        let codec = Codec::new(3);
+        let epochs: Vec<Epoch> = (0..10_000u64)
+            .map(|i| {
+                Epoch::new(1_700_000_000 + i.cast_signed() / 10).with_nanos(i % 10 * 100_000_000)
+            })
+            .collect();
+
+        let encoded = codec.encode_epochs(&epochs).unwrap();
+        let decompressed = codec.decompress(&encoded).unwrap();
+        assert_eq!(decompressed.len(), epochs.len() * EPOCH_NANOS_LEN);
+        for (nanos, epoch) in decompressed.chunks_exact(EPOCH_NANOS_LEN).zip(&epochs) {
+            assert_eq!(nanos, epoch.as_nanos().to_le_bytes());
+        }
+        assert_eq!(codec.decode_epochs(&encoded).unwrap(), epochs);
+
+        let mixed = [
+            Epoch::MIN,
+            Epoch::new(-1).with_millis(250),
+            Epoch::new(0),
+            Epoch::new(1).with_micros(5),
+            Epoch::MAX,
+        ];
+        let decoded = codec
+            .decode_epochs(&codec.encode_epochs(&mixed).unwrap())
+            .unwrap();
+        assert_eq!(decoded, mixed);
+        assert!(
+            decoded
+                .iter()
+                .all(|epoch| matches!(epoch.subsecond(), SubSecond::Nano(_)))
+        );
+
+        let empty = codec.encode_epochs(&[]).unwrap();
+        assert!(codec.decode_epochs(&empty).unwrap().is_empty());
+    }
+
+    #[test]
+    fn test_decode_epochs_invalid() {
+        let codec = Codec::new(1);
+
+        let compressed = codec.compress(&[0; EPOCH_NANOS_LEN + 1]).unwrap();
+        assert!(matches!(
+            codec.decode_epochs(&compressed),
+            Err(CodecError::TruncatedFrame)
+        ));
+
+        let out_of_range = (Epoch::MAX.as_nanos() + 1).to_le_bytes();
+        let compressed = codec.compress(&out_of_range).unwrap();
+        assert!(matches!(
+            codec.decode_epochs(&compressed),
+            Err(CodecError::InvalidTimestamp)
+        ));
+    }
+
+    #[test]
+    fn test_encode_epochs_delta() {
+        let codec = Codec::new(3);
+        let increasing: Vec<Epoch> = (0..10_000)
+            .map(|i| Epoch::from_total_millis(1_700_000_000_000 + i))
+            .collect();
+        let constant = vec![Epoch::new(1_700_000_000).with_micros(5); 1_000];
+        let non_monotonic = vec![
+            Epoch::new(1_700_000_000).with_nanos(999_999_999),
+            Epoch::new(0),
+            Epoch::new(-1).with_millis(250),
+            Epoch::MAX,
+            Epoch::MIN,
+            Epoch::new(5).with_micros(0),
+            Epoch::new(5).with_nanos(0),
+            Epoch::new(5).with_millis(0),
+            Epoch::new(5),
+            Epoch::MAX,
+        ];
+
+        for epochs in [increasing.clone(), constant, non_monotonic, vec![]] {
+            let encoded = codec.encode_epochs_delta(&epochs).unwrap();
+            let decoded = codec.decode_epochs_delta(&encoded).unwrap();
+            assert_eq!(decoded, epochs);
+            for (decoded, epoch) in decoded.iter().zip(&epochs) {
+                assert_eq!(
+                    decoded.subsecond().precision(),
+                    epoch.subsecond().precision()
+                );
+            }
+        }
+
+        let encoded = codec.encode_epochs_delta(&increasing).unwrap();
+        assert!(encoded.len() * 10 < codec.encode_epochs(&increasing).unwrap().len());
+        assert!(encoded.len() * 10 < codec.encode(&increasing).unwrap().len());
+    }
+
+    #[test]
+    fn test_precision_tag() {
+        for (tag, precision) in DELTA_PRECISIONS.into_iter().enumerate() {
+            assert_eq!(usize::from(precision_tag(precision)), tag);
+        }
+    }
+
+    #[test]
+    fn test_decode_epochs_delta_invalid() {
+        let codec = Codec::new(1);
+        let mut overlong = vec![3];
+        overlong.extend([0x80; MAX_VARINT_128_LEN]);
+        overlong.push(0);
+        let mut too_large = vec![3];
+        too_large.extend([0xff; MAX_VARINT_128_LEN - 1]);
+        too_large.push(0x04);
+
+        let invalid = [
+            (vec![4, 0], CodecError::InvalidTimestamp),
+            (vec![1, 2], CodecError::InvalidTimestamp),
+            (vec![3], CodecError::TruncatedFrame),
+            (vec![3, 0x80], CodecError::TruncatedFrame),
+            (overlong, CodecError::InvalidVarint),
+            (too_large, CodecError::InvalidVarint),
+        ];
+
+        for (raw, expected) in invalid {
+            let compressed = codec.compress(&raw).unwrap();
+            let err = codec.decode_epochs_delta(&compressed).unwrap_err();
+            assert_eq!(
+                std::mem::discriminant(&err),
+                std::mem::discriminant(&expected),
+                "{raw:?}: {err:?}"
+            );
+        }
+
+        let mut raw = vec![3];
+        write_zigzag_varint(&mut raw, Epoch::MAX.as_nanos());
+        raw.push(3);
+        write_zigzag_varint(&mut raw, 1);
+        let compressed = codec.compress(&raw).unwrap();
+        assert!(matches!(
+            codec.decode_epochs_delta(&compressed),
+            Err(CodecError::InvalidTimestamp)
+        ));
     }
 
     #[test]
-    fn test_encode() {
-        let data = vec![1, 2, 3, 4, 5];
+    fn test_zigzag_varint() {
+        for value in [
+            0,
+            1,
+            -1,
+            63,
+            -64,
+            64,
+            i128::from(i64::MIN),
+            i128::MAX,
+            i128::MIN,
+        ] {
+            let mut out = Vec::new();
+            write_zigzag_varint(&mut out, value);
+            assert!(out.len() <= MAX_VARINT_128_LEN);
+
+            let mut rest = out.as_slice();
+            assert_eq!(read_zigzag_varint(&mut rest).unwrap(), value);
+            assert!(rest.is_empty());
+        }
+    }
+
+    #[test]
+    fn test_encode_stamped() {
+        let codec = Codec::new(1);
+        let created = Epoch::new(1_700_000_000).with_micros(123_456);
+
+        let frame = codec.encode_stamped(&vec![1u32, 2, 3], &created).unwrap();
+        assert_eq!(frame[..8], 1_700_000_000i64.to_le_bytes());
+        assert_eq!(frame[8..12], 123_456_000u32.to_le_bytes());
+        assert_eq!(frame[12], precision_tag(Precision::Micro));
+
+        let (data, stamp) = codec.decode_stamped::<Vec<u32>>(&frame).unwrap();
+        assert_eq!(data, [1, 2, 3]);
+        assert_eq!(stamp, created);
+        assert!(matches!(stamp.subsecond(), SubSecond::Micro(123_456)));
+
+        for created in [
+            Epoch::new(1_700_000_000),
+            Epoch::new(1_700_000_000).with_millis(0),
+            Epoch::new(1_700_000_000).with_nanos(1),
+        ] {
+            let frame = codec.encode_stamped(&1u8, &created).unwrap();
+            let (_, stamp) = codec.decode_stamped::<u8>(&frame).unwrap();
+            assert_eq!(
+                stamp.subsecond().precision(),
+                created.subsecond().precision()
+            );
+        }
+    }
+
+    #[test]
+    fn test_encode_stamped_negative() {
+        let codec = Codec::new(1);
+        let created = Epoch::new(-1).with_millis(500);
+
+        let frame = codec.encode_stamped(&"before 1970", &created).unwrap();
+        let (data, stamp) = codec.decode_stamped::<String>(&frame).unwrap();
+        assert_eq!(data, "before 1970");
+        assert_eq!(stamp, created);
+        assert!(matches!(stamp.subsecond(), SubSecond::Milli(500)));
+    }
+
+    #[test]
+    fn test_decode_stamped_invalid() {
+        let codec = Codec::new(1);
+        let frame = codec.encode_stamped(&1u8, &Epoch::new(0)).unwrap();
+
+        for len in 0..STAMP_HEADER_LEN {
+            let decoded = codec.decode_stamped::<u8>(&frame[..len]);
+            assert!(matches!(decoded, Err(CodecError::TruncatedFrame)));
+        }
+
+        let mut out_of_range = frame.clone();
+        out_of_range[8..12].copy_from_slice(&1_000_000_000u32.to_le_bytes());
+        let mut unknown_tag = frame.clone();
+        unknown_tag[12] = 4;
+        let mut too_fine = frame;
+        too_fine[8..12].copy_from_slice(&5u32.to_le_bytes());
+        too_fine[12] = precision_tag(Precision::Milli);
+
+        for frame in [out_of_range, unknown_tag, too_fine] {
+            let decoded = codec.decode_stamped::<u8>(&frame);
+            assert!(matches!(decoded, Err(CodecError::InvalidTimestamp)));
+        }
+    }
+
+    #[test]
+    fn test_encode_stamped_invalid_subsecond() {
+        let codec = Codec::new(1);
+        for subsecond in [
+            SubSecond::Nano(1_000_000_000),
+            SubSecond::Nano(5_000_000_000),
+            SubSecond::Nano(u64::MAX),
+            SubSecond::Milli(1_000),
+            SubSecond::Micro(u32::MAX),
+        ] {
+            let created = Epoch::from((0, subsecond));
+            let encoded = codec.encode_stamped(&1u8, &created);
+            assert!(matches!(encoded, Err(CodecError::InvalidTimestamp)));
+        }
+
+        let created = Epoch::from((0, SubSecond::Nano(999_999_999)));
+        let frame = codec.encode_stamped(&1u8, &created).unwrap();
+        assert_eq!(codec.decode_stamped::<u8>(&frame).unwrap().1, created);
+    }
+
+    fn budget_payload() -> Vec<(u32, String)> {
+        (0..20_000)
+            .map(|i| (i, format!("sensor-{}/reading-{}", i % 97, i * 7 % 1013)))
+            .collect()
+    }
+
+    #[test]
+    fn test_encode_within_budget_tiny() {
+        let codec = Codec::new(19);
+        let payload = budget_payload();
+
+        let frame = codec
+            .encode_within_budget(&payload, Duration::from_nanos(1))
+            .unwrap();
+        assert_eq!(codec.decode::<Vec<(u32, String)>>(&frame).unwrap(), payload);
+
+        let fastest = Codec::new(1).encode(&payload).unwrap();
+        assert_eq!(frame, fastest);
+    }
+
+    #[test]
+    fn test_encode_within_budget_generous() {
+        let codecs = [
+            Codec::new(5),
+            Codec::new(5).with_algorithm_tag(true),
+            Codec::with_dictionary_ref(5, sample_dictionary()),
+        ];
+        let payload = budget_payload();
+
+        for codec in codecs {
+            let frame = codec.encode_within_budget(&payload, Duration::MAX).unwrap();
+            assert_eq!(frame, codec.encode(&payload).unwrap());
+        }
+    }
+
+    #[test]
+    fn test_encode_within_budget_fallback() {
+        let codecs = [
+            Codec::new(19).with_algorithm_tag(true),
+            Codec::with_dictionary_ref(19, sample_dictionary()),
+        ];
+        let payload = budget_payload();
+
+        for codec in codecs {
+            let frame = codec
+                .encode_within_budget(&payload, Duration::ZERO)
+                .unwrap();
+            assert_eq!(codec.decode::<Vec<(u32, String)>>(&frame).unwrap(), payload);
+        }
+    }
+
+    /// Interns every string of a sequence into `pool`, returning the index of each string.
+    struct Interner<'a> {
+        pool: &'a mut Vec<String>,
+    }
+
+    impl<'de> DeserializeSeed<'de> for Interner<'_> {
+        type Value = Vec<usize>;
+
+        fn deserialize<D: serde::Deserializer<'de>>(
+            self,
+            deserializer: D,
+        ) -> Result<Self::Value, D::Error> {
+            deserializer.deserialize_seq(self)
+        }
+    }
+
+    impl<'de> Visitor<'de> for Interner<'_> {
+        type Value = Vec<usize>;
+
+        fn expecting(&self, formatter: &mut std::fmt::Formatter) -> std::fmt::Result {
+            formatter.write_str("a sequence of strings")
+        }
+
+        fn visit_seq<A: SeqAccess<'de>>(self, mut seq: A) -> Result<Self::Value, A::Error> {
+            let mut indices = Vec::new();
+            while let Some(string) = seq.next_element::<String>()? {
+                let index = if let Some(index) = self.pool.iter().position(|s| *s == string) {
+                    index
+                } else {
+                    self.pool.push(string);
+                    self.pool.len() - 1
+                };
+                indices.push(index);
+            }
+
+            Ok(indices)
+        }
+    }
+
+    #[test]
+    fn test_decode_seed() {
         let codec = Codec::new(1);
+        let mut pool = vec!["existing".to_string()];
+
+        let encoded = codec
+            .encode(&["a", "b", "a", "existing", "c", "b"])
+            .unwrap();
+        let indices = codec
+            .decode_seed(&encoded, Interner { pool: &mut pool })
+            .unwrap();
+        assert_eq!(indices, [1, 2, 1, 0, 3, 2]);
+        assert_eq!(pool, ["existing", "a", "b", "c"]);
+
+        let encoded = codec.encode(&["c", "d"]).unwrap();
+        let indices = codec
+            .decode_seed(&encoded, Interner { pool: &mut pool })
+            .unwrap();
+        assert_eq!(indices, [3, 4]);
+    }
+
+    #[cfg(feature = "json")]
+    #[test]
+    fn test_message_pack_only_methods() {
+        fn unsupported<T>(result: Result<T>) -> bool {
+            result.is_err_and(|err| matches!(err, CodecError::UnsupportedFormat(Format::Json)))
+        }
+
+        let codec = Codec::new(1).with_format(Format::Json);
+        let message_pack = Codec::new(1);
+        let data = vec![1u32, 2, 3];
+
+        let pages = codec.encode_paged::<32, _>(&data).unwrap();
+        assert_eq!(codec.decode_paged::<32, Vec<u32>>(&pages).unwrap(), data);
+        assert!(message_pack.decode_paged::<32, Vec<u32>>(&pages).is_err());
 
         let encoded = codec.encode(&data).unwrap();
-        let expected = [40, 181, 47, 253, 0, 72, 49, 0, 0, 149, 1, 2, 3, 4, 5];
-        assert_eq!(encoded, expected);
+        assert!(unsupported(codec.decode_seq_visit(&encoded, |_: u32| ())));
+        assert!(unsupported(
+            codec.decode_seed(&encoded, PhantomData::<Vec<u32>>)
+        ));
+        assert!(unsupported(codec.decode_chunked::<u32, _>([&encoded])));
+        assert!(unsupported(
+            codec.continuous_encoder(Vec::new()).map(|_| ())
+        ));
+
+        let mut frames = codec.encode_chunked_by_bytes(data.iter(), 4);
+        assert!(unsupported(frames.next().unwrap()));
+        assert!(frames.next().is_none());
     }
 
     #[test]
-    fn test_decode() {
-        let encoded = [40, 181, 47, 253, 0, 72, 49, 0, 0, 149, 1, 2, 3, 4, 5];
-        let expected = vec![1, 2, 3, 4, 5];
+    fn test_decode_seed_phantom() {
         let codec = Codec::new(1);
+        let encoded = codec.encode(&Some(7u8)).unwrap();
 
-        let decoded = codec.decode::<Vec<u8>>(&encoded).unwrap();
-        assert_eq!(decoded, expected);
+        let decoded = codec
+            .decode_seed(&encoded, PhantomData::<Option<u8>>)
+            .unwrap();
+        assert_eq!(decoded, Some(7));
+
+        let decoded = codec.decode_seed(&encoded, PhantomData::<String>);
+        assert!(matches!(decoded, Err(CodecError::SerdeDecodeError(_))));
+    }
+
+    #[test]
+    fn test_encode_delimited() {
+        let codec = Codec::new(1);
+        let mut stream = Vec::new();
+        codec
+            .encode_delimited(&vec![1u32, 2, 3], &mut stream)
+            .unwrap();
+        codec
+            .encode_delimited(&"x".repeat(1_000), &mut stream)
+            .unwrap();
+
+        let first = codec.encode(&vec![1u32, 2, 3]).unwrap();
+        assert_eq!(usize::from(stream[0]), first.len());
+        assert_eq!(stream[1..=first.len()], first);
+
+        let mut reader = stream.as_slice();
+        let decoded: Vec<u32> = codec.decode_delimited(&mut reader).unwrap();
+        assert_eq!(decoded, [1, 2, 3]);
+        let decoded: String = codec.decode_delimited(&mut reader).unwrap();
+        assert_eq!(decoded, "x".repeat(1_000));
+        assert!(reader.is_empty());
+
+        let decoded = codec.decode_delimited::<String, _>(&mut reader);
+        assert!(
+            matches!(decoded, Err(CodecError::IOError(e)) if e.kind() == std::io::ErrorKind::UnexpectedEof)
+        );
+    }
+
+    #[test]
+    fn test_encode_delimited_multi_byte_length() {
+        let codec = Codec::new(1);
+        let data: Vec<u64> = (0..100).map(|i| i * 0x0123_4567_89ab).collect();
+        let frame = codec.encode(&data).unwrap();
+        assert!(frame.len() >= 0x80);
+
+        let mut stream = Vec::new();
+        codec.encode_delimited(&data, &mut stream).unwrap();
+        let len = usize::from(stream[0] & 0x7f) | usize::from(stream[1]) << 7;
+        assert!(stream[0] & 0x80 != 0);
+        assert_eq!(len, frame.len());
+        assert_eq!(
+            codec
+                .decode_delimited::<Vec<u64>, _>(stream.as_slice())
+                .unwrap(),
+            data
+        );
+    }
+
+    #[test]
+    fn test_decode_delimited_invalid_varint() {
+        let codec = Codec::new(1);
+        let invalid: [&[u8]; 2] = [
+            &[0xff; 11],
+            &[0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0x02],
+        ];
+
+        for stream in invalid {
+            let decoded = codec.decode_delimited::<u8, _>(stream);
+            assert!(matches!(decoded, Err(CodecError::InvalidVarint)));
+        }
+
+        let decoded = codec.decode_delimited::<u8, _>([0x05, 1, 2].as_slice());
+        assert!(matches!(decoded, Err(CodecError::IOError(_))));
+
+        let huge = [0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0x7f, 1];
+        let decoded = codec.decode_delimited::<u8, _>(huge.as_slice());
+        assert!(matches!(decoded, Err(CodecError::IOError(_))));
+    }
+
+    #[test]
+    fn test_decode_expecting_len() {
+        let codec = Codec::new(1);
+        let data = vec![1u32, 2, 3, 300];
+        let len = Codec::serialize(&data).unwrap().len();
+        let encoded = codec.encode(&data).unwrap();
+
+        let decoded: Vec<u32> = codec.decode_expecting_len(&encoded, len).unwrap();
+        assert_eq!(decoded, data);
+
+        let decoded = codec.decode_expecting_len::<Vec<u32>>(&encoded, len + 1);
+        assert!(matches!(
+            decoded,
+            Err(CodecError::LengthMismatch { expected, got }) if expected == len + 1 && got == len
+        ));
+    }
+
+    type Nested = (Vec<u32>, String, Vec<(u8, Vec<f64>)>);
+
+    #[test]
+    fn test_decode_bounded() {
+        let codec = Codec::new(1);
+        let data = (vec![1u32, 2, 3], "text", [(1u8, vec![0.5f64; 2])]);
+        let encoded = codec.encode(&data).unwrap();
+
+        let decoded: Nested = codec.decode_bounded(&encoded, 3).unwrap();
+        assert_eq!(decoded.0, data.0);
+
+        let decoded = codec.decode_bounded::<Nested>(&encoded, 2);
+        assert!(matches!(
+            decoded,
+            Err(CodecError::TooManyElements { max: 2, found: 3 })
+        ));
+    }
+
+    #[test]
+    fn test_decode_limited() {
+        let data = vec![7u8; 100_000];
+        let len = Codec::serialize(&data).unwrap().len();
+
+        for codec in [
+            Codec::new(1),
+            Codec::new(1).with_header(true).with_algorithm_tag(true),
+            Codec::with_dictionary_ref(3, sample_dictionary()),
+        ] {
+            let encoded = codec.encode(&data).unwrap();
+
+            let decoded: Vec<u8> = codec.decode_limited(&encoded, len).unwrap();
+            assert_eq!(decoded, data);
+            let decoded: Vec<u8> = codec.decode_limited(&encoded, usize::MAX).unwrap();
+            assert_eq!(decoded, data);
+
+            for max in [0, 1_000, len - 1] {
+                let decoded = codec.decode_limited::<Vec<u8>>(&encoded, max);
+                assert!(matches!(
+                    decoded,
+                    Err(CodecError::SizeLimitExceeded { max: m }) if m == max
+                ));
+            }
+        }
+
+        let decoded = Codec::new(1).decode_limited::<Vec<u8>>(&[], 10);
+        assert!(matches!(decoded, Err(CodecError::EmptyInput)));
+    }
+
+    #[test]
+    fn test_decode_limited_bomb() {
+        let codec = Codec::new(1);
+        let mut bomb = Vec::new();
+        codec
+            .compress_stream(std::io::repeat(0).take(64 << 20), &mut bomb)
+            .unwrap();
+        assert!(bomb.len() < 64 << 10);
+
+        let decoded = codec.decode_limited::<Vec<u8>>(&bomb, 1 << 20);
+        assert!(matches!(
+            decoded,
+            Err(CodecError::SizeLimitExceeded { max }) if max == 1 << 20
+        ));
+    }
+
+    #[test]
+    fn test_decompressed_size() {
+        let data = "x".repeat(10_000);
+        let serialized = Codec::serialize(&data).unwrap();
+
+        for codec in [
+            Codec::new(1),
+            Codec::new(1).with_header(true).with_algorithm_tag(true),
+            Codec::with_dictionary_ref(3, sample_dictionary()),
+        ] {
+            let (mut scratch, mut frame) = (Vec::new(), Vec::new());
+            codec.encode_using(&data, &mut scratch, &mut frame).unwrap();
+            assert_eq!(
+                codec.decompressed_size(&frame),
+                Some(serialized.len() as u64)
+            );
+
+            let mut streamed = Vec::new();
+            codec
+                .compress_stream(serialized.as_slice(), &mut streamed)
+                .unwrap();
+            assert_eq!(codec.decompressed_size(&streamed), None);
+            assert_eq!(codec.decompress(&streamed).unwrap(), serialized);
+        }
+
+        let codec = Codec::new(1).with_header(true);
+        for data in [&b""[..], b"EPAR", b"EPAR\x01", b"EPAR\x01garbage"] {
+            assert_eq!(codec.decompressed_size(data), None);
+        }
+    }
+
+    #[test]
+    fn test_compress_content_size() {
+        let data = (0..5_000u32).map(|i| (i % 251) as u8).collect::<Vec<_>>();
+
+        for codec in [
+            Codec::new(1),
+            Codec::new(1).with_header(true).with_algorithm_tag(true),
+            Codec::with_dictionary_ref(3, sample_dictionary()),
+        ] {
+            let compressed = codec.compress(&data).unwrap();
+            assert_eq!(
+                codec.decompressed_size(&compressed),
+                Some(data.len() as u64)
+            );
+            assert_eq!(codec.decompress(&compressed).unwrap(), data);
+
+            let compressed = codec.compress_at_level(&data, 5).unwrap();
+            assert_eq!(
+                codec.decompressed_size(&compressed),
+                Some(data.len() as u64)
+            );
+
+            let compressed = codec.compress(&[]).unwrap();
+            assert_eq!(codec.decompressed_size(&compressed), Some(0));
+        }
+
+        let codec = Codec::new(1);
+        let serialized = Codec::serialize(&data).unwrap();
+        let encoded = codec.encode(&data).unwrap();
+        assert_eq!(
+            codec.decompressed_size(&encoded),
+            Some(serialized.len() as u64)
+        );
+    }
+
+    #[test]
+    fn test_builder() {
+        let builder = Codec::builder()
+            .level(5)
+            .header(true)
+            .algorithm_tag(true)
+            .sorted_maps(true)
+            .dictionary(sample_dictionary());
+        #[cfg(feature = "xxhash")]
+        let builder = builder.checksum(true);
+        #[cfg(feature = "zstdmt")]
+        let builder = builder.workers(2);
+        let codec = builder.build().unwrap();
+
+        let expected = Codec::with_dictionary_ref(5, sample_dictionary())
+            .with_header(true)
+            .with_algorithm_tag(true)
+            .with_sorted_maps(true);
+        #[cfg(feature = "xxhash")]
+        let expected = expected.with_checksum(true);
+        #[cfg(feature = "zstdmt")]
+        let expected = Codec {
+            workers: 2,
+            ..expected
+        };
+        assert_eq!(codec, expected);
+
+        let data: std::collections::HashMap<String, u32> =
+            (0..100).map(|i| (format!("sensor-{i}"), i)).collect();
+        let encoded = codec.encode(&data).unwrap();
+        assert!(encoded.starts_with(b"EPAR"));
+        assert_eq!(encoded, expected.encode(&data).unwrap());
+        assert_eq!(
+            codec
+                .decode::<std::collections::HashMap<String, u32>>(&encoded)
+                .unwrap(),
+            data
+        );
+
+        assert_eq!(Codec::builder().build().unwrap(), Codec::default());
+        assert!(matches!(
+            Codec::builder().level(23).build(),
+            Err(CodecError::InvalidLevel(23))
+        ));
+    }
+
+    #[test]
+    fn test_serde_config() {
+        let codecs = [
+            Codec::new(-3),
+            Codec::new(19)
+                .with_header(true)
+                .with_algorithm_tag(true)
+                .with_sorted_maps(true),
+            Codec::with_dictionary_ref(5, sample_dictionary()),
+        ];
+
+        for codec in codecs {
+            let config = rmp_serde::to_vec(&codec).unwrap();
+            let restored: Codec = rmp_serde::from_slice(&config).unwrap();
+            assert_eq!(restored, codec);
+            assert_eq!(restored.level(), codec.level());
+
+            #[cfg(feature = "json")]
+            {
+                let config = serde_json::to_string(&codec).unwrap();
+                assert_eq!(serde_json::from_str::<Codec>(&config).unwrap(), codec);
+            }
+        }
+
+        let invalid = rmp_serde::to_vec(&Codec::builder().level(23)).unwrap();
+        let error = rmp_serde::from_slice::<Codec>(&invalid).unwrap_err();
+        assert!(error.to_string().contains("23"));
+
+        #[cfg(feature = "json")]
+        {
+            let codec: Codec = serde_json::from_str(r#"{"level":4,"header":true}"#).unwrap();
+            assert_eq!(codec, Codec::new(4).with_header(true));
+            assert!(serde_json::from_str::<Codec>(r#"{"level":23}"#).is_err());
+            assert!(serde_json::from_str::<Codec>(r#"{"level":-1000000}"#).is_err());
+        }
+    }
+
+    #[test]
+    fn test_decode_bounded_huge_header() {
+        let codec = Codec::new(1);
+        let headers: [&[u8]; 3] = [
+            &[0xdd, 0xff, 0xff, 0xff, 0xff],
+            &[0xdf, 0x7f, 0xff, 0xff, 0xff, 0x01],
+            &[0x91, 0xdc, 0xff, 0xff],
+        ];
+
+        for header in headers {
+            let compressed = codec.compress(header).unwrap();
+            let decoded = codec.decode_bounded::<Vec<Vec<u8>>>(&compressed, 1_000);
+            assert!(matches!(decoded, Err(CodecError::TooManyElements { .. })));
+        }
+
+        let compressed = codec.compress(&[0xdc, 0x00, 0x10, 0x01]).unwrap();
+        let decoded = codec.decode_bounded::<Vec<u8>>(&compressed, 1_000);
+        assert!(matches!(decoded, Err(CodecError::TruncatedFrame)));
+    }
+
+    #[test]
+    fn test_encode_using() {
+        let codecs = [
+            Codec::new(1),
+            Codec::new(1).with_algorithm_tag(true),
+            Codec::with_dictionary_ref(3, sample_dictionary()),
+            Codec::with_dictionary_ref(3, sample_dictionary()).with_algorithm_tag(true),
+        ];
+        let (mut scratch, mut out) = (Vec::new(), Vec::new());
+
+        for codec in codecs {
+            for i in 0..10u32 {
+                let record = (i, "sensor-reading", vec![i; 4]);
+                codec.encode_using(&record, &mut scratch, &mut out).unwrap();
+
+                assert_eq!(scratch, Codec::serialize(&record).unwrap());
+                assert_eq!(codec.decompress(&out).unwrap(), scratch);
+                assert_eq!(out, codec.encode(&record).unwrap());
+            }
+        }
+
+        // The compressor kept for the thread must pick up each codec's level.
+        let record = vec!["sensor-reading"; 64];
+        for codec in [
+            Codec::new(19).with_header(true),
+            Codec::new(1),
+            Codec::new(19).with_algorithm_tag(true),
+            Codec::new(1).with_header(true).with_algorithm_tag(true),
+        ] {
+            codec.encode_using(&record, &mut scratch, &mut out).unwrap();
+            assert_eq!(out, codec.encode(&record).unwrap());
+        }
+    }
+
+    #[test]
+    fn test_deserialize_error_context() {
+        let codec = Codec::new(1);
+        let serialized = Codec::serialize(&"not a number").unwrap();
+
+        let err = codec.deserialize::<u32>(&serialized).unwrap_err();
+        assert!(matches!(
+            &err,
+            CodecError::DecodeContext { type_name: "u32", preview, .. } if *preview == serialized
+        ));
+        assert!(err.to_string().contains("u32"));
+        assert!(err.to_string().contains("ac, 6e, 6f, 74"));
+
+        let source = std::error::Error::source(&err).unwrap().to_string();
+        assert!(!err.to_string().contains(&source));
+    }
+
+    #[test]
+    fn test_deserialize_error_context_preview_len() {
+        let codec = Codec::new(1);
+        let serialized = Codec::serialize(&"a string longer than the preview").unwrap();
+
+        let err = codec.decode::<u32>(&codec.compress(&serialized).unwrap());
+        assert!(matches!(
+            err,
+            Err(CodecError::DecodeContext { preview, .. }) if preview == serialized[..DECODE_PREVIEW_LEN]
+        ));
+    }
+
+    #[test]
+    fn test_decode_seq_visit_not_a_sequence() {
+        let codec = Codec::new(1);
+        let encoded = codec.encode(&42u32).unwrap();
+
+        let visited = codec.decode_seq_visit(&encoded, |_: u32| {});
+        assert!(matches!(visited, Err(CodecError::SerdeDecodeError(_))));
+    }
+
+    #[test]
+    #[cfg(feature = "sha256")]
+    fn test_sidecar_path() {
+        let sidecar = sidecar_path(Path::new("/tmp/archive.zst"));
+        assert_eq!(sidecar, Path::new("/tmp/archive.zst.sha256"));
+    }
+
+    #[test]
+    #[cfg(feature = "sha256")]
+    fn test_sha256_hex() {
+        assert_eq!(
+            sha256_hex(b"abc"),
+            "ba7816bf8f01cfea414140de5dae2223b00361a396177a9cb410ff61f20015ad"
+        );
+    }
+
+    #[test]
+    #[cfg(feature = "xxhash")]
+    fn test_decode_with_digest_mismatch() {
+        let codec = Codec::new(1);
+        let (mut frame, digest) = codec.encode_with_digest(&vec![1, 2, 3, 4, 5]).unwrap();
+        frame[0] ^= 0xFF;
+
+        let decoded = codec.decode_with_digest::<Vec<u8>>(&frame);
+        assert!(matches!(
+            decoded,
+            Err(CodecError::DigestMismatch { actual, .. }) if actual == digest
+        ));
+    }
+
+    #[test]
+    #[cfg(feature = "xxhash")]
+    fn test_decode_with_digest_truncated() {
+        let codec = Codec::new(1);
+
+        let decoded = codec.decode_with_digest::<Vec<u8>>(&[1, 2, 3]);
+        assert!(matches!(decoded, Err(CodecError::TruncatedFrame)));
+    }
+
+    #[test]
+    #[cfg(feature = "xxhash")]
+    fn test_checksum() {
+        let data = (42u32, "sensor-reading");
+        for codec in [
+            Codec::new(1).with_checksum(true),
+            Codec::new(1)
+                .with_checksum(true)
+                .with_header(true)
+                .with_algorithm_tag(true),
+            Codec::with_dictionary_ref(3, sample_dictionary()).with_checksum(true),
+        ] {
+            let encoded = codec.encode(&data).unwrap();
+            let (frame, stored) = encoded.split_last_chunk::<CHECKSUM_LEN>().unwrap();
+            assert_eq!(
+                u64::from_le_bytes(*stored),
+                xxhash_rust::xxh64::xxh64(frame, DIGEST_SEED)
+            );
+            assert_eq!(codec.decode::<(u32, String)>(&encoded).unwrap().0, 42);
+
+            let (mut scratch, mut out) = (Vec::new(), Vec::new());
+            codec.encode_using(&data, &mut scratch, &mut out).unwrap();
+            assert_eq!(codec.decode::<(u32, String)>(&out).unwrap().0, 42);
+
+            let mut streamed = Vec::new();
+            codec
+                .compress_stream(&b"streamed"[..], &mut streamed)
+                .unwrap();
+            assert_eq!(codec.decompress(&streamed).unwrap(), b"streamed");
+
+            let mut decompressed = Vec::new();
+            codec
+                .decompress_stream(encoded.as_slice(), &mut decompressed)
+                .unwrap();
+            assert_eq!(decompressed, codec.decompress(&encoded).unwrap());
+        }
+    }
+
+    #[test]
+    #[cfg(feature = "xxhash")]
+    fn test_checksum_mismatch() {
+        let codec = Codec::new(1).with_checksum(true);
+        let encoded = codec.encode(&vec![1, 2, 3, 4, 5]).unwrap();
+
+        for i in 0..encoded.len() {
+            let mut corrupted = encoded.clone();
+            corrupted[i] ^= 0x01;
+            assert!(
+                matches!(
+                    codec.decode::<Vec<u8>>(&corrupted),
+                    Err(CodecError::ChecksumMismatch { .. })
+                ),
+                "{i}"
+            );
+        }
+
+        let mut corrupted = encoded.clone();
+        *corrupted.last_mut().unwrap() ^= 0x01;
+        assert!(matches!(
+            codec.decompress_stream(corrupted.as_slice(), Vec::new()),
+            Err(CodecError::ChecksumMismatch { .. })
+        ));
+
+        let truncated = &encoded[..encoded.len() - 1];
+        assert!(matches!(
+            codec.decompress_stream(truncated, Vec::new()),
+            Err(CodecError::TruncatedFrame)
+        ));
+        assert!(matches!(
+            codec.decompress(&encoded[..4]),
+            Err(CodecError::TruncatedFrame)
+        ));
+        assert!(Codec::new(1).decode::<Vec<u8>>(&encoded).is_err());
     }
 }
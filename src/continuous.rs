@@ -0,0 +1,327 @@
+use crate::{Codec, CodecError};
+
+use serde::Serialize;
+use std::io::Write;
+
+type Result<T, E = CodecError> = std::result::Result<T, E>;
+
+impl Codec {
+    /// Starts a [`ContinuousEncoder`] that writes records to `writer` as one long-lived zstd
+    /// stream.
+    ///
+    /// # Errors
+    ///
+    /// Return `epoch_archive::CodecError::UnsupportedFormat` if the codec is not configured for
+    /// `MessagePack`, `epoch_archive::CodecError::UnsupportedFraming` if it has a header,
+    /// algorithm tag or checksum enabled, or `epoch_archive::CodecError` if the zstd encoder
+    /// could not be created.
+    pub fn continuous_encoder<W: Write>(&self, writer: W) -> Result<ContinuousEncoder<'_, W>> {
+        self.require_message_pack()?;
+        self.require_bare_frames()?;
+
+        Ok(ContinuousEncoder {
+            codec: self,
+            encoder: Some(self.stream_encoder(writer)?),
+            scratch: Vec::new(),
+        })
+    }
+}
+
+/// Serializes records into a single zstd stream, keeping the compression context between
+/// records so redundancy across records compresses well.
+///
+/// Records are buffered by the encoder and only written out as a complete frame on
+/// [`ContinuousEncoder::checkpoint`] or [`ContinuousEncoder::finish`]. A decoder has to start
+/// reading at a checkpoint boundary, since the records in the middle of a frame depend on the
+/// ones before them. Everything from a boundary onwards decodes with [`Codec::decode_chunked`].
+///
+/// The frames are bare zstd frames, so the codec cannot have [`Codec::with_header`],
+/// [`Codec::with_algorithm_tag`] or [`Codec::with_checksum`] enabled.
+///
+/// # Examples
+///
+/// ```
+/// use epoch_archive::Codec;
+///
+/// let codec = Codec::default();
+/// let mut encoder = codec.continuous_encoder(Vec::new()).unwrap();
+/// encoder.write_record(&"first").unwrap();
+/// encoder.checkpoint().unwrap();
+/// encoder.write_record(&"second").unwrap();
+/// let stream = encoder.finish().unwrap();
+///
+/// let records: Vec<String> = codec.decode_chunked([stream]).unwrap();
+/// assert_eq!(records, ["first", "second"]);
+/// ```
+pub struct ContinuousEncoder<'a, W: Write> {
+    codec: &'a Codec,
+    encoder: Option<zstd::stream::Encoder<'a, W>>,
+    scratch: Vec<u8>,
+}
+
+impl<W: Write> ContinuousEncoder<'_, W> {
+    /// Serializes `record` into the current frame without flushing it, sorting its maps if
+    /// [`Codec::with_sorted_maps`] is enabled.
+    ///
+    /// # Errors
+    ///
+    /// Return `epoch_archive::CodecError` if there is an issue serializing or compressing the
+    /// record.
+    pub fn write_record<T: Serialize>(&mut self, record: &T) -> Result<()> {
+        self.codec
+            .serialize_format_into(record, &mut self.scratch)?;
+        let encoder = self.encoder.as_mut().ok_or(CodecError::EncoderPoisoned)?;
+        encoder.write_all(&self.scratch)?;
+
+        Ok(())
+    }
+
+    /// Ends the current frame, writing all buffered records to the underlying writer and
+    /// flushing it, and starts a new one. Decoding can start from the position of the writer
+    /// after a checkpoint.
+    ///
+    /// If writing the end of the frame fails, the frame stays open and the checkpoint can be
+    /// retried. If only the flush fails, the frame has been ended and the next one started.
+    ///
+    /// # Errors
+    ///
+    /// Return `epoch_archive::CodecError` if there is an issue finishing the frame, flushing the
+    /// writer or starting the next frame. Return `epoch_archive::CodecError::EncoderPoisoned` if
+    /// an earlier checkpoint could not start the next frame, which also fails every later call.
+    pub fn checkpoint(&mut self) -> Result<()> {
+        let encoder = self.encoder.take().ok_or(CodecError::EncoderPoisoned)?;
+        let writer = match encoder.try_finish() {
+            Ok(writer) => writer,
+            Err((encoder, error)) => {
+                self.encoder = Some(encoder);
+                return Err(error.into());
+            }
+        };
+
+        let encoder = self.encoder.insert(self.codec.stream_encoder(writer)?);
+        encoder.get_mut().flush()?;
+
+        Ok(())
+    }
+
+    /// Returns a reference to the underlying writer.
+    ///
+    /// Directly after a checkpoint this holds every record written so far.
+    ///
+    /// # Errors
+    ///
+    /// Return `epoch_archive::CodecError::EncoderPoisoned` if an earlier checkpoint could not
+    /// start the next frame.
+    pub fn get_ref(&self) -> Result<&W> {
+        self.encoder
+            .as_ref()
+            .map(zstd::stream::Encoder::get_ref)
+            .ok_or(CodecError::EncoderPoisoned)
+    }
+
+    /// Ends the current frame and returns the underlying writer.
+    ///
+    /// # Errors
+    ///
+    /// Return `epoch_archive::CodecError` if there is an issue finishing the frame, or
+    /// `epoch_archive::CodecError::EncoderPoisoned` if an earlier checkpoint could not start the
+    /// next frame.
+    pub fn finish(mut self) -> Result<W> {
+        let encoder = self.encoder.take().ok_or(CodecError::EncoderPoisoned)?;
+
+        Ok(encoder.finish()?)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use std::cell::Cell;
+    use std::rc::Rc;
+
+    /// A writer that fails its next `writes` writes and `flushes` flushes.
+    #[derive(Default)]
+    struct FlakyWriter {
+        data: Vec<u8>,
+        writes: Rc<Cell<usize>>,
+        flushes: Rc<Cell<usize>>,
+    }
+
+    impl Write for FlakyWriter {
+        fn write(&mut self, buf: &[u8]) -> std::io::Result<usize> {
+            if self.writes.get() > 0 {
+                self.writes.set(self.writes.get() - 1);
+                return Err(std::io::Error::other("write failed"));
+            }
+            self.data.write(buf)
+        }
+
+        fn flush(&mut self) -> std::io::Result<()> {
+            if self.flushes.get() > 0 {
+                self.flushes.set(self.flushes.get() - 1);
+                return Err(std::io::Error::other("flush failed"));
+            }
+            Ok(())
+        }
+    }
+
+    fn record(i: u32) -> (String, String, u32) {
+        (
+            "sensor-reading/temperature".to_string(),
+            format!("building-7/floor-{}/room-{}", i % 4, i % 16),
+            i,
+        )
+    }
+
+    #[test]
+    fn test_continuous_round_trip() {
+        let codec = Codec::new(3);
+        let mut encoder = codec.continuous_encoder(Vec::new()).unwrap();
+        for i in 0..100 {
+            encoder.write_record(&record(i)).unwrap();
+            if i % 10 == 9 {
+                encoder.checkpoint().unwrap();
+            }
+        }
+        let stream = encoder.finish().unwrap();
+
+        let records: Vec<(String, String, u32)> = codec.decode_chunked([stream]).unwrap();
+        assert_eq!(records, (0..100).map(record).collect::<Vec<_>>());
+    }
+
+    #[test]
+    fn test_continuous_better_ratio() {
+        let codec = Codec::new(3);
+        let mut encoder = codec.continuous_encoder(Vec::new()).unwrap();
+        let mut per_record = 0;
+        for i in 0..200 {
+            encoder.write_record(&record(i)).unwrap();
+            per_record += codec.encode(&record(i)).unwrap().len();
+        }
+        let stream = encoder.finish().unwrap();
+
+        assert!(
+            stream.len() * 4 < per_record,
+            "{} vs {per_record}",
+            stream.len()
+        );
+    }
+
+    #[test]
+    fn test_continuous_decode_from_checkpoint() {
+        let codec = Codec::new(3);
+        let mut encoder = codec.continuous_encoder(Vec::new()).unwrap();
+        for i in 0..5 {
+            encoder.write_record(&record(i)).unwrap();
+        }
+        encoder.checkpoint().unwrap();
+        let boundary = encoder.get_ref().unwrap().len();
+        for i in 5..8 {
+            encoder.write_record(&record(i)).unwrap();
+        }
+        let stream = encoder.finish().unwrap();
+
+        let records: Vec<(String, String, u32)> =
+            codec.decode_chunked([&stream[boundary..]]).unwrap();
+        assert_eq!(records, (5..8).map(record).collect::<Vec<_>>());
+    }
+
+    #[test]
+    fn test_continuous_empty() {
+        let codec = Codec::new(3);
+        let stream = codec
+            .continuous_encoder(Vec::new())
+            .unwrap()
+            .finish()
+            .unwrap();
+
+        let records: Vec<u32> = codec.decode_chunked([stream]).unwrap();
+        assert!(records.is_empty());
+    }
+
+    #[test]
+    fn test_continuous_checkpoint_failure() {
+        let codec = Codec::new(3);
+        let writer = FlakyWriter::default();
+        let (failed_writes, failed_flushes) = (writer.writes.clone(), writer.flushes.clone());
+        let mut encoder = codec.continuous_encoder(writer).unwrap();
+
+        encoder.write_record(&record(0)).unwrap();
+        failed_flushes.set(1);
+        assert!(matches!(encoder.checkpoint(), Err(CodecError::IOError(_))));
+        let boundary = encoder.get_ref().unwrap().data.len();
+        assert!(boundary > 0);
+
+        encoder.write_record(&record(1)).unwrap();
+        failed_writes.set(1);
+        assert!(matches!(encoder.checkpoint(), Err(CodecError::IOError(_))));
+        encoder.checkpoint().unwrap();
+
+        encoder.write_record(&record(2)).unwrap();
+        let stream = encoder.finish().unwrap().data;
+
+        let records: Vec<(String, String, u32)> = codec.decode_chunked([&stream]).unwrap();
+        assert_eq!(records, (0..3).map(record).collect::<Vec<_>>());
+        let records: Vec<(String, String, u32)> =
+            codec.decode_chunked([&stream[boundary..]]).unwrap();
+        assert_eq!(records, (1..3).map(record).collect::<Vec<_>>());
+    }
+
+    #[test]
+    fn test_continuous_poisoned() {
+        let codec = Codec::new(3);
+        let mut encoder = codec.continuous_encoder(Vec::new()).unwrap();
+        encoder.encoder = None;
+
+        assert!(matches!(
+            encoder.write_record(&record(0)),
+            Err(CodecError::EncoderPoisoned)
+        ));
+        assert!(matches!(
+            encoder.checkpoint(),
+            Err(CodecError::EncoderPoisoned)
+        ));
+        assert!(matches!(
+            encoder.get_ref(),
+            Err(CodecError::EncoderPoisoned)
+        ));
+        assert!(matches!(encoder.finish(), Err(CodecError::EncoderPoisoned)));
+    }
+
+    #[test]
+    fn test_continuous_sorted_maps() {
+        use std::collections::HashMap;
+
+        let codec = Codec::new(3).with_sorted_maps(true);
+        let maps: Vec<HashMap<u32, u32>> =
+            (0..10).map(|i| (0..16).map(|k| (k, i)).collect()).collect();
+        let mut encoder = codec.continuous_encoder(Vec::new()).unwrap();
+        for map in &maps {
+            encoder.write_record(map).unwrap();
+        }
+        let stream = encoder.finish().unwrap();
+
+        let expected: Vec<u8> = maps
+            .iter()
+            .flat_map(|map| codec.decompress(&codec.encode(map).unwrap()).unwrap())
+            .collect();
+        assert_eq!(codec.decompress(&stream).unwrap(), expected);
+    }
+
+    #[test]
+    fn test_continuous_rejects_framing() {
+        let codecs = [
+            Codec::new(3).with_header(true),
+            Codec::new(3).with_algorithm_tag(true),
+            #[cfg(feature = "xxhash")]
+            Codec::new(3).with_checksum(true),
+        ];
+        for codec in codecs {
+            assert!(matches!(
+                codec.continuous_encoder(Vec::new()),
+                Err(CodecError::UnsupportedFraming)
+            ));
+        }
+    }
+}
@@ -1,16 +1,71 @@
 use crate::EpochError;
 
-use std::str::FromStr;
+use alloc::format;
+use alloc::string::{String, ToString};
+use core::fmt::Write;
+use core::hash::{Hash, Hasher};
+use core::ops::{Add, Neg, Sub};
+use core::str::FromStr;
+use core::time::Duration;
+use serde::{Deserialize, Serialize};
+#[cfg(feature = "std")]
+use std::time::{SystemTime, UNIX_EPOCH};
 
 const DELIMITER: char = '.';
 
-#[derive(Debug, Clone, PartialEq, Eq, PartialOrd, Ord)]
+const SECONDS_PER_DAY: i64 = 86_400;
+/// Seconds between the NTP prime epoch, 1900-01-01, and the Unix epoch.
+const NTP_UNIX_OFFSET: i64 = 2_208_988_800;
+/// Days between the Excel serial date origin, 1899-12-30, and the Unix epoch.
+const EXCEL_UNIX_OFFSET: f64 = 25_569.0;
+/// `0000-01-01T00:00:00Z`, the earliest instant RFC 3339 can represent.
+const RFC3339_MIN: i64 = -62_167_219_200;
+/// `9999-12-31T23:59:59Z`, the latest whole second RFC 3339 can represent.
+const RFC3339_MAX: i64 = 253_402_300_799;
+const POWERS_OF_TEN: [u64; 10] = [
+    1,
+    10,
+    100,
+    1_000,
+    10_000,
+    100_000,
+    1_000_000,
+    10_000_000,
+    100_000_000,
+    1_000_000_000,
+];
+
+/// A number of seconds since the Unix epoch, with an optional subsecond.
+///
+/// Serialized as a two element sequence of the seconds and the [`SubSecond`], so no field names
+/// end up in a `MessagePack` payload.
+#[derive(Debug, Clone, PartialEq, Eq, PartialOrd, Ord, Serialize, Deserialize)]
+#[serde(from = "(i64, SubSecond)", into = "(i64, SubSecond)")]
 pub struct Epoch {
     epoch: i64,
     subsecond: SubSecond,
 }
 
 impl Epoch {
+    /// The earliest representable instant, `i64::MIN` seconds with a zero nanosecond subsecond.
+    pub const MIN: Epoch = Epoch {
+        epoch: i64::MIN,
+        subsecond: SubSecond::Nano(0),
+    };
+
+    /// The latest representable instant, `i64::MAX` seconds with a nanosecond subsecond of
+    /// 999999999.
+    pub const MAX: Epoch = Epoch {
+        epoch: i64::MAX,
+        subsecond: SubSecond::Nano(999_999_999),
+    };
+
+    /// The Unix epoch, 1970-01-01T00:00:00Z, without a subsecond.
+    pub const UNIX_EPOCH: Epoch = Epoch {
+        epoch: 0,
+        subsecond: SubSecond::None,
+    };
+
     /// Creates a new Epoch struct.
     ///
     /// # Parameters
@@ -33,6 +88,70 @@ impl Epoch {
         }
     }
 
+    /// Creates an Epoch for the current system time, with `SubSecond::Nano` precision.
+    ///
+    /// A system clock set before 1970 produces a negative epoch, following the same floor
+    /// convention as the `SystemTime` conversion.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use epoch_archive::{Epoch, SubSecond};
+    ///
+    /// let now = Epoch::now();
+    /// assert!(now.epoch() > 1_700_000_000);
+    /// assert!(matches!(now.subsecond(), SubSecond::Nano(_)));
+    /// ```
+    #[cfg(feature = "std")]
+    #[must_use]
+    pub fn now() -> Self {
+        Self::saturating_from_nanos(nanos_since_unix_epoch(SystemTime::now()))
+    }
+
+    /// Creates an Epoch for the current system time like [`Epoch::now`], truncated to
+    /// `SubSecond::Milli` precision.
+    #[cfg(feature = "std")]
+    #[must_use]
+    #[allow(clippy::cast_possible_truncation)]
+    pub fn now_millis() -> Self {
+        let now = Self::now();
+        // The subsecond is always below one second, so it fits in a `u16` once in milliseconds.
+        let millis = now.subsecond.as_nanos() / 1_000_000;
+        now.with_millis(millis as u16)
+    }
+
+    /// Creates an Epoch for the current system time like [`Epoch::now`], truncated to
+    /// `SubSecond::Micro` precision.
+    #[cfg(feature = "std")]
+    #[must_use]
+    #[allow(clippy::cast_possible_truncation)]
+    pub fn now_micros() -> Self {
+        let now = Self::now();
+        // The subsecond is always below one second, so it fits in a `u32` once in microseconds.
+        let micros = now.subsecond.as_nanos() / 1_000;
+        now.with_micros(micros as u32)
+    }
+
+    /// Creates an Epoch holding only a fraction of a second, for sub-second measurements such
+    /// as an elapsed 0.123456 seconds. The epoch value is `0`.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use epoch_archive::{Epoch, SubSecond};
+    ///
+    /// let elapsed = Epoch::sub_second(SubSecond::Micro(123_456));
+    /// assert!(elapsed.is_sub_second_only());
+    /// assert_eq!(elapsed.to_string(), "0.123456");
+    /// ```
+    #[must_use]
+    pub fn sub_second(subsecond: SubSecond) -> Self {
+        Self {
+            epoch: 0,
+            subsecond,
+        }
+    }
+
     /// Sets the epoch value.
     ///
     /// # Examples
@@ -120,256 +239,4303 @@ impl Epoch {
         }
     }
 
-    // -----------------------------
-    // ---------- GETTERS ----------
-    // -----------------------------
+    /// Sets the millisecond value like [`Epoch::with_millis`], but returns an error instead of
+    /// panicking when the value is out of range.
+    ///
+    /// # Errors
+    ///
+    /// Return `epoch_archive::EpochError::SubSecondOutOfRange` if `millis` is >= 1000.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use epoch_archive::Epoch;
+    ///
+    /// assert!(Epoch::new(0).try_with_millis(999).is_ok());
+    /// assert!(Epoch::new(0).try_with_millis(1000).is_err());
+    /// ```
+    pub fn try_with_millis(self, millis: u16) -> Result<Self, EpochError> {
+        let subsecond = SubSecond::milli(millis).ok_or(EpochError::SubSecondOutOfRange {
+            value: u64::from(millis),
+            unit: "ms",
+        })?;
 
-    /// Returns the epoch value.
-    #[must_use]
-    pub fn epoch(&self) -> i64 {
-        self.epoch
+        Ok(Self { subsecond, ..self })
     }
 
-    /// Returns the optional millisecond value.
+    /// Sets the microsecond value like [`Epoch::with_micros`], but returns an error instead of
+    /// panicking when the value is out of range.
     ///
-    /// If no value is present, this returns None.
-    #[must_use]
-    pub fn subsecond(&self) -> &SubSecond {
-        &self.subsecond
+    /// # Errors
+    ///
+    /// Return `epoch_archive::EpochError::SubSecondOutOfRange` if `micros` is >= 1000000.
+    pub fn try_with_micros(self, micros: u32) -> Result<Self, EpochError> {
+        let subsecond = SubSecond::micro(micros).ok_or(EpochError::SubSecondOutOfRange {
+            value: u64::from(micros),
+            unit: "us",
+        })?;
+
+        Ok(Self { subsecond, ..self })
     }
 
-    /// Returns the epoch value as a string with the specified delimiter.
-    #[must_use]
-    pub fn format_with_delimiter(&self, delimiter: char) -> String {
-        match self.subsecond {
-            SubSecond::None => format!("{:}", self.epoch),
-            SubSecond::Milli(ms) => format!("{:}{}{:03}", self.epoch, delimiter, ms),
-            SubSecond::Micro(us) => format!("{:}{}{:06}", self.epoch, delimiter, us),
-            SubSecond::Nano(ns) => format!("{:}{}{:09}", self.epoch, delimiter, ns),
-        }
+    /// Sets the nanosecond value like [`Epoch::with_nanos`], but returns an error instead of
+    /// panicking when the value is out of range.
+    ///
+    /// # Errors
+    ///
+    /// Return `epoch_archive::EpochError::SubSecondOutOfRange` if `nanos` is >= 1000000000.
+    pub fn try_with_nanos(self, nanos: u64) -> Result<Self, EpochError> {
+        let subsecond = SubSecond::nano(nanos).ok_or(EpochError::SubSecondOutOfRange {
+            value: nanos,
+            unit: "ns",
+        })?;
+
+        Ok(Self { subsecond, ..self })
     }
 
-    /// Returns the epoch value as a string.
+    /// Creates an Epoch from a number of nanoseconds since the Unix epoch stored in an `i64`.
+    ///
+    /// The subsecond is always stored as `SubSecond::Nano`. Negative values are floored, so
+    /// `-1` nanosecond becomes epoch `-1` with `SubSecond::Nano(999_999_999)`.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use epoch_archive::{Epoch, SubSecond};
+    ///
+    /// let epoch = Epoch::from_nanos_i64(1_500_000_000);
+    /// assert_eq!(epoch.epoch(), 1);
+    /// assert!(matches!(epoch.subsecond(), SubSecond::Nano(500_000_000)));
+    /// ```
     #[must_use]
-    pub fn format(&self) -> String {
-        Self::format_with_delimiter(self, DELIMITER)
+    pub fn from_nanos_i64(nanos: i64) -> Self {
+        Self {
+            epoch: nanos.div_euclid(1_000_000_000),
+            subsecond: SubSecond::Nano(nanos.rem_euclid(1_000_000_000).unsigned_abs()),
+        }
     }
-}
 
-impl std::fmt::Display for Epoch {
-    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
-        write!(f, "{}", self.format())
+    /// Creates an Epoch from a total number of milliseconds since the Unix epoch.
+    ///
+    /// The subsecond is always stored as `SubSecond::Milli`. Negative values are floored, so
+    /// `-1500` milliseconds becomes epoch `-2` with `SubSecond::Milli(500)`.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use epoch_archive::{Epoch, SubSecond};
+    ///
+    /// let epoch = Epoch::from_total_millis(1500);
+    /// assert_eq!(epoch.epoch(), 1);
+    /// assert!(matches!(epoch.subsecond(), SubSecond::Milli(500)));
+    /// ```
+    #[must_use]
+    #[allow(clippy::cast_possible_truncation)]
+    pub fn from_total_millis(millis: i64) -> Self {
+        Self {
+            epoch: millis.div_euclid(1_000),
+            subsecond: SubSecond::Milli(millis.rem_euclid(1_000) as u16),
+        }
     }
-}
 
-impl Default for Epoch {
-    fn default() -> Self {
+    /// Creates an Epoch from a total number of microseconds since the Unix epoch.
+    ///
+    /// The subsecond is always stored as `SubSecond::Micro`. Negative values are floored, so
+    /// `-1` microsecond becomes epoch `-1` with `SubSecond::Micro(999_999)`.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use epoch_archive::{Epoch, SubSecond};
+    ///
+    /// let epoch = Epoch::from_total_micros(-1);
+    /// assert_eq!(epoch.epoch(), -1);
+    /// assert!(matches!(epoch.subsecond(), SubSecond::Micro(999_999)));
+    /// ```
+    #[must_use]
+    #[allow(clippy::cast_possible_truncation)]
+    pub fn from_total_micros(micros: i64) -> Self {
         Self {
-            epoch: 0,
-            subsecond: SubSecond::None,
+            epoch: micros.div_euclid(1_000_000),
+            subsecond: SubSecond::Micro(micros.rem_euclid(1_000_000) as u32),
         }
     }
-}
 
-#[derive(Debug, Clone, PartialEq, Eq, PartialOrd, Ord)]
-pub enum SubSecond {
-    None,
-    Milli(u16),
-    Micro(u32),
-    Nano(u64),
-}
+    /// Creates an Epoch from a floating point number of seconds, the same as
+    /// [`Epoch::saturating_from_f64`].
+    ///
+    /// The fraction is stored as `SubSecond::Nano`, rounded to the nearest nanosecond. An `f64`
+    /// only has 53 bits of precision though, so present day timestamps are only accurate to a
+    /// few hundred nanoseconds, and the digits below that are noise from the conversion.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use epoch_archive::{Epoch, SubSecond};
+    ///
+    /// let epoch = Epoch::from_secs_f64(1.25);
+    /// assert_eq!(epoch.epoch(), 1);
+    /// assert!(matches!(epoch.subsecond(), SubSecond::Nano(250_000_000)));
+    /// ```
+    #[cfg(feature = "std")]
+    #[must_use]
+    pub fn from_secs_f64(secs: f64) -> Self {
+        Self::saturating_from_f64(secs)
+    }
 
-impl FromStr for SubSecond {
-    type Err = EpochError;
-    fn from_str(s: &str) -> Result<Self, Self::Err> {
-        match s.len() {
-            3 => Ok(SubSecond::Milli(s.parse()?)),
-            6 => Ok(SubSecond::Micro(s.parse()?)),
-            9 => Ok(SubSecond::Nano(s.parse()?)),
-            _ => Err(EpochError::InvalidSubSecond(s.to_string())),
-        }
+    /// Creates an Epoch from a total number of nanoseconds since the Unix epoch, the inverse of
+    /// [`Epoch::as_nanos`].
+    ///
+    /// The subsecond is always stored as `SubSecond::Nano`. Negative values are floored like
+    /// [`Epoch::from_nanos_i64`], so `-1` nanosecond becomes epoch `-1` with
+    /// `SubSecond::Nano(999_999_999)`. Totals whose seconds do not fit in an `i64` are clamped
+    /// to the earliest or latest representable instant.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use epoch_archive::{Epoch, SubSecond};
+    ///
+    /// let epoch = Epoch::from_nanos(-1);
+    /// assert_eq!(epoch.epoch(), -1);
+    /// assert!(matches!(epoch.subsecond(), SubSecond::Nano(999_999_999)));
+    /// assert_eq!(epoch.as_nanos(), -1);
+    /// ```
+    #[must_use]
+    pub fn from_nanos(total: i128) -> Self {
+        Self::saturating_from_nanos(total)
     }
-}
 
-#[cfg(test)]
-mod tests {
-    use super::*;
+    /// Creates an Epoch from a `Duration` measured from the Unix epoch, such as the result of
+    /// `SystemTime::duration_since(UNIX_EPOCH)`.
+    ///
+    /// The subsecond is always stored as `SubSecond::Nano`. Durations of more than `i64::MAX`
+    /// seconds are clamped to [`Epoch::MAX`].
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use epoch_archive::{Epoch, SubSecond};
+    /// use std::time::Duration;
+    ///
+    /// let epoch = Epoch::from_duration(Duration::new(1_700_000_000, 5));
+    /// assert_eq!(epoch.epoch(), 1_700_000_000);
+    /// assert!(matches!(epoch.subsecond(), SubSecond::Nano(5)));
+    /// ```
+    #[must_use]
+    pub fn from_duration(duration: Duration) -> Self {
+        Self::from_signed_duration(duration, false)
+    }
 
-    const TEST_EPOCH: [i64; 9] = [
-        0,
-        1,
-        -1,
-        123,
-        -123,
-        i64::MAX,
-        i64::MIN,
-        i64::MAX / 1000,
-        i64::MIN / 1000,
-    ];
+    /// Creates an Epoch from a `Duration` measured from the Unix epoch, backward if `negative` is
+    /// set, for instants before 1970 that a `Duration` can't express on its own.
+    ///
+    /// The subsecond is always stored as `SubSecond::Nano`, and negative offsets are floored like
+    /// [`Epoch::from_nanos`], so 250 milliseconds before the Unix epoch becomes epoch `-1` with
+    /// `SubSecond::Nano(750_000_000)`. Offsets whose seconds do not fit in an `i64` are clamped
+    /// to [`Epoch::MIN`] or [`Epoch::MAX`].
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use epoch_archive::{Epoch, SubSecond};
+    /// use std::time::Duration;
+    ///
+    /// let epoch = Epoch::from_signed_duration(Duration::from_millis(250), true);
+    /// assert_eq!(epoch.epoch(), -1);
+    /// assert!(matches!(epoch.subsecond(), SubSecond::Nano(750_000_000)));
+    /// ```
+    #[must_use]
+    pub fn from_signed_duration(duration: Duration, negative: bool) -> Self {
+        let nanos = duration.as_nanos().cast_signed();
+        Self::saturating_from_nanos(if negative { -nanos } else { nanos })
+    }
 
-    const TEST_MS: [u16; 4] = [0, 1, 999, 123];
-    const TEST_US: [u32; 4] = [0, 1, 999_999, 123_123];
-    const TEST_NS: [u64; 4] = [0, 1, 999_999_999, 123_123_123];
+    /// Creates an Epoch from an exact fraction of seconds, `numerator / denominator`, such as a
+    /// sample index over a sample rate.
+    ///
+    /// The whole seconds are floored and the fraction is stored as `SubSecond::Nano`. Any
+    /// remainder below one nanosecond is rounded to the nearest nanosecond, with ties rounded
+    /// up towards the later instant, carrying into the seconds when needed.
+    ///
+    /// # Errors
+    ///
+    /// Return `epoch_archive::EpochError::ZeroDenominator` if `denominator` is 0, or
+    /// `epoch_archive::EpochError::Overflow` if the seconds do not fit in an `i64` or the
+    /// fraction is too large to convert to nanoseconds exactly.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use epoch_archive::Epoch;
+    ///
+    /// let epoch = Epoch::from_rational(3, 2).unwrap();
+    /// assert_eq!(epoch.to_string(), "1.500000000");
+    /// ```
+    pub fn from_rational(numerator: i128, denominator: i128) -> Result<Self, EpochError> {
+        let (numerator, denominator) = match denominator {
+            0 => return Err(EpochError::ZeroDenominator),
+            d if d < 0 => (
+                numerator.checked_neg().ok_or(EpochError::Overflow)?,
+                d.checked_neg().ok_or(EpochError::Overflow)?,
+            ),
+            d => (numerator, d),
+        };
 
-    #[test]
-    fn test_new() {
-        for epoch in TEST_EPOCH {
-            let new = Epoch::new(epoch);
-            assert_eq!(new.epoch, epoch);
+        let seconds = numerator.div_euclid(denominator);
+        let scaled = numerator
+            .rem_euclid(denominator)
+            .checked_mul(1_000_000_000)
+            .ok_or(EpochError::Overflow)?;
+        let remainder = scaled % denominator;
+        let mut nanos = scaled / denominator;
+        if remainder >= denominator - remainder {
+            nanos += 1;
         }
+
+        let (seconds, nanos) = if nanos == 1_000_000_000 {
+            (seconds + 1, 0)
+        } else {
+            (seconds, nanos)
+        };
+
+        Ok(Self {
+            epoch: i64::try_from(seconds).map_err(|_| EpochError::Overflow)?,
+            subsecond: SubSecond::Nano(u64::try_from(nanos).map_err(|_| EpochError::Overflow)?),
+        })
     }
 
-    #[test]
-    fn test_with_milli() {
-        for epoch in TEST_EPOCH {
-            for ms in TEST_MS {
-                let new = Epoch::new(epoch).with_millis(ms);
-                assert_eq!(new.subsecond, SubSecond::Milli(ms));
-                assert_eq!(new.epoch, epoch);
-            }
+    /// Creates an Epoch from a floating point number of seconds, clamping instead of failing.
+    ///
+    /// The fraction is rounded to the nearest nanosecond and stored as `SubSecond::Nano`, but an
+    /// `f64` only has 53 bits of precision, so present day timestamps are only accurate to a few
+    /// hundred nanoseconds. Values beyond the range of an `i64` of seconds saturate to the
+    /// earliest or latest representable instant, and `NaN` becomes the Unix epoch.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use epoch_archive::{Epoch, SubSecond};
+    ///
+    /// let epoch = Epoch::saturating_from_f64(-0.25);
+    /// assert_eq!(epoch.epoch(), -1);
+    /// assert!(matches!(epoch.subsecond(), SubSecond::Nano(750_000_000)));
+    ///
+    /// assert_eq!(Epoch::saturating_from_f64(f64::INFINITY).epoch(), i64::MAX);
+    /// ```
+    #[cfg(feature = "std")]
+    #[must_use]
+    #[allow(
+        clippy::cast_possible_truncation,
+        clippy::cast_sign_loss,
+        clippy::cast_precision_loss
+    )]
+    pub fn saturating_from_f64(secs: f64) -> Self {
+        const LIMIT: f64 = i64::MAX as f64;
+
+        if secs.is_nan() {
+            return Self::new(0).with_nanos(0);
         }
-    }
 
-    #[test]
-    fn test_with_micro() {
-        for epoch in TEST_EPOCH {
-            for ms in TEST_US {
-                let new = Epoch::new(epoch).with_micros(ms);
-                assert_eq!(new.subsecond, SubSecond::Micro(ms));
-                assert_eq!(new.epoch, epoch);
-            }
+        let seconds = secs.floor();
+        if seconds >= LIMIT {
+            return Self::MAX;
+        }
+        if seconds < -LIMIT {
+            return Self::MIN;
         }
-    }
 
-    #[test]
-    fn test_with_nano() {
-        for epoch in TEST_EPOCH {
-            for ms in TEST_NS {
-                let new = Epoch::new(epoch).with_nanos(ms);
-                assert_eq!(new.subsecond, SubSecond::Nano(ms));
-                assert_eq!(new.epoch, epoch);
-            }
+        let nanos = ((secs - seconds) * 1e9).round() as u64;
+        let seconds = seconds as i64;
+        if nanos >= 1_000_000_000 {
+            return match seconds.checked_add(1) {
+                Some(seconds) => Self::new(seconds).with_nanos(0),
+                None => Self::MAX,
+            };
         }
+
+        Self::new(seconds).with_nanos(nanos)
     }
 
-    #[test]
-    #[should_panic(expected = "assertion failed: millis < 1000")]
-    #[allow(unused_must_use)]
-    fn test_with_ms_panic() {
-        Epoch::new(0).with_millis(1000);
+    /// Creates an Epoch from a 64-bit NTP timestamp: 32 bits of seconds since 1900-01-01 followed
+    /// by 32 bits of fraction in units of 2^-32 seconds.
+    ///
+    /// The fraction is rounded to the nearest nanosecond and stored as `SubSecond::Nano`. NTP
+    /// timestamps wrap every 2^32 seconds; this always assumes era 0, which ends on
+    /// 2036-02-07T06:28:16Z. Timestamps from later eras have to be offset by the caller.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use epoch_archive::Epoch;
+    ///
+    /// let epoch = Epoch::from_ntp(0xe8fe_6f80_8000_0000);
+    /// assert_eq!(epoch, Epoch::new(1_700_000_000).with_nanos(500_000_000));
+    /// ```
+    #[must_use]
+    pub fn from_ntp(ntp: u64) -> Self {
+        let seconds = i64::from((ntp >> 32) as u32) - NTP_UNIX_OFFSET;
+        let fraction = ntp & 0xFFFF_FFFF;
+        let nanos = (fraction * 1_000_000_000 + (1 << 31)) >> 32;
+
+        if nanos == 1_000_000_000 {
+            Self::new(seconds + 1).with_nanos(0)
+        } else {
+            Self::new(seconds).with_nanos(nanos)
+        }
     }
 
-    #[test]
-    #[should_panic(expected = "assertion failed: micros < 1000000")]
-    #[allow(unused_must_use)]
+    /// Returns the epoch as a 64-bit NTP timestamp in era 0, see [`Epoch::from_ntp`].
+    ///
+    /// The subsecond is rounded to the nearest 2^-32 second.
+    ///
+    /// # Errors
+    ///
+    /// Return `epoch_archive::EpochError::OutOfRange` if the epoch falls outside NTP era 0,
+    /// 1900-01-01T00:00:00Z up to 2036-02-07T06:28:16Z.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use epoch_archive::Epoch;
+    ///
+    /// let epoch = Epoch::new(1_700_000_000).with_millis(500);
+    /// assert_eq!(epoch.to_ntp().unwrap(), 0xe8fe_6f80_8000_0000);
+    /// ```
+    pub fn to_ntp(&self) -> Result<u64, EpochError> {
+        let nanos = self.subsecond.as_nanos();
+        let fraction = ((nanos << 32) + 500_000_000) / 1_000_000_000;
+        let (carry, fraction) = (fraction >> 32, fraction & 0xFFFF_FFFF);
+
+        let seconds = self
+            .epoch
+            .checked_add(NTP_UNIX_OFFSET)
+            .and_then(|seconds| u64::try_from(seconds).ok())
+            .map(|seconds| seconds + carry)
+            .filter(|seconds| seconds >> 32 == 0)
+            .ok_or(EpochError::OutOfRange(self.epoch))?;
+
+        Ok(seconds << 32 | fraction)
+    }
+
+    /// Creates an Epoch from an Excel serial date: days since 1899-12-30, with the time of day as
+    /// the fraction of a day.
+    ///
+    /// The time of day is rounded to the nearest microsecond and stored as `SubSecond::Micro`,
+    /// which is about as fine as an `f64` serial resolves present day times.
+    ///
+    /// Excel treats 1900 as a leap year, so its serials for January and February 1900 are one
+    /// day ahead of the real calendar. Serials below 60 are shifted back by a day to undo that,
+    /// and serial 60, the nonexistent 1900-02-29, is rejected. Serials from 61 (1900-03-01)
+    /// onwards need no correction.
+    ///
+    /// # Errors
+    ///
+    /// Return `epoch_archive::EpochError::InvalidSerial` if `serial` is not finite or falls on
+    /// 1900-02-29, or `epoch_archive::EpochError::Overflow` if the seconds do not fit in an `i64`.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use epoch_archive::Epoch;
+    ///
+    /// let epoch = Epoch::from_excel_serial(45_244.75).unwrap();
+    /// assert_eq!(epoch.to_rfc3339_with_digits(0).unwrap(), "2023-11-14T18:00:00Z");
+    /// ```
+    #[cfg(feature = "std")]
+    #[allow(clippy::cast_possible_truncation)]
+    pub fn from_excel_serial(serial: f64) -> Result<Self, EpochError> {
+        let days = match serial {
+            serial if !serial.is_finite() || (60.0..61.0).contains(&serial) => {
+                return Err(EpochError::InvalidSerial(serial));
+            }
+            serial if serial < 60.0 => serial + 1.0,
+            serial => serial,
+        };
+
+        let micros = ((days - EXCEL_UNIX_OFFSET) * 86_400e6).round();
+        if micros.abs() >= 2f64.powi(126) {
+            return Err(EpochError::Overflow);
+        }
+        let micros = micros as i128;
+
+        Ok(Self {
+            epoch: i64::try_from(micros.div_euclid(1_000_000)).map_err(|_| EpochError::Overflow)?,
+            subsecond: SubSecond::Micro(
+                u32::try_from(micros.rem_euclid(1_000_000)).map_err(|_| EpochError::Overflow)?,
+            ),
+        })
+    }
+
+    /// Returns the epoch as an Excel serial date, the inverse of [`Epoch::from_excel_serial`],
+    /// including the shift for dates before 1900-03-01.
+    ///
+    /// An `f64` serial only resolves present day times to about a microsecond.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use epoch_archive::Epoch;
+    ///
+    /// assert_eq!(Epoch::new(0).to_excel_serial(), 25_569.0);
+    /// assert_eq!(Epoch::new(43_200).to_excel_serial(), 25_569.5);
+    /// ```
+    #[must_use]
+    #[allow(clippy::cast_precision_loss)]
+    pub fn to_excel_serial(&self) -> f64 {
+        let days = self.as_nanos() as f64 / 86_400e9 + EXCEL_UNIX_OFFSET;
+        if days < 61.0 { days - 1.0 } else { days }
+    }
+
+    /// Creates an Epoch from a total number of nanoseconds since the Unix epoch, or `None` if the
+    /// seconds do not fit in an `i64`.
+    pub(crate) fn checked_from_nanos(total: i128) -> Option<Self> {
+        Some(Self {
+            epoch: i64::try_from(total.div_euclid(1_000_000_000)).ok()?,
+            subsecond: SubSecond::Nano(u64::try_from(total.rem_euclid(1_000_000_000)).ok()?),
+        })
+    }
+
+    /// Creates an Epoch from a total number of nanoseconds since the Unix epoch, clamping to the
+    /// earliest or latest representable instant if the seconds do not fit in an `i64`.
+    pub(crate) fn saturating_from_nanos(total: i128) -> Self {
+        Self::checked_from_nanos(total).unwrap_or(if total < 0 { Self::MIN } else { Self::MAX })
+    }
+
+    /// Parses a whole number annotated with a unit suffix, such as `"1700000000123ms"`.
+    ///
+    /// Recognized suffixes are `s`, `ms`, `us` (or `µs`) and `ns`. The value is interpreted in
+    /// that unit and stored with the matching `SubSecond` precision, or `SubSecond::None` for
+    /// seconds. Negative values are floored, so `"-1ms"` becomes epoch `-1` with
+    /// `SubSecond::Milli(999)`.
+    ///
+    /// # Errors
+    ///
+    /// Return `epoch_archive::EpochError::InvalidUnit` if the suffix is missing or unknown, or
+    /// `epoch_archive::EpochError::InvalidEpoch` if the value is not a valid `i64`.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use epoch_archive::{Epoch, SubSecond};
+    ///
+    /// let epoch = Epoch::parse_with_unit("1700000000123ms").unwrap();
+    /// assert_eq!(epoch.epoch(), 1_700_000_000);
+    /// assert!(matches!(epoch.subsecond(), SubSecond::Milli(123)));
+    /// ```
+    #[allow(clippy::cast_possible_truncation, clippy::cast_sign_loss)]
+    pub fn parse_with_unit(s: &str) -> Result<Self, EpochError> {
+        let split = s
+            .find(|c: char| c.is_alphabetic())
+            .ok_or_else(|| EpochError::InvalidUnit(String::new()))?;
+        let (value, unit) = s.split_at(split);
+
+        let per_second = match unit {
+            "s" => return Ok(Self::new(value.parse()?)),
+            "ms" => 1_000,
+            "us" | "µs" => 1_000_000,
+            "ns" => 1_000_000_000,
+            _ => return Err(EpochError::InvalidUnit(unit.to_string())),
+        };
+
+        let value: i64 = value.parse()?;
+        let fraction = value.rem_euclid(per_second);
+        let subsecond = match per_second {
+            1_000 => SubSecond::Milli(fraction as u16),
+            1_000_000 => SubSecond::Micro(fraction as u32),
+            _ => SubSecond::Nano(fraction as u64),
+        };
+
+        Ok(Self {
+            epoch: value.div_euclid(per_second),
+            subsecond,
+        })
+    }
+
+    /// Parses an epoch written with a decimal comma, such as `"1700,500"` in European CSV exports.
+    ///
+    /// The comma separates the seconds from the subsecond, which must have 3, 6 or 9 digits like
+    /// [`SubSecond::from_str`]. The seconds may carry a single leading `+` or `-`, the subsecond
+    /// can't be signed. A string without a comma is parsed as whole seconds. Because a
+    /// comma is also commonly used as a thousands separator, a string with more than one comma
+    /// (e.g. `"1,700,500"`) is ambiguous and rejected rather than guessed at.
+    ///
+    /// # Errors
+    ///
+    /// Return `epoch_archive::EpochError::AmbiguousDelimiter` if the string contains more than one
+    /// comma, or `epoch_archive::EpochError` if either part fails to parse.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use epoch_archive::Epoch;
+    ///
+    /// let epoch = Epoch::parse_decimal_comma("1700,500").unwrap();
+    /// assert_eq!(epoch, Epoch::new(1700).with_millis(500));
+    /// assert!(Epoch::parse_decimal_comma("1,700,500").is_err());
+    /// ```
+    pub fn parse_decimal_comma(s: &str) -> Result<Self, EpochError> {
+        Self::parse_with_delimiter(s, ',')
+    }
+
+    /// Parses the form written by [`Epoch::format_with_delimiter`], the seconds optionally
+    /// followed by `delimiter` and a subsecond of 3, 6 or 9 digits.
+    ///
+    /// A single leading `+` or `-` always belongs to the seconds, even when it is also the
+    /// delimiter, so with `-` as the delimiter `"-1-999"` is epoch `-1` with
+    /// `SubSecond::Milli(999)`. A string without the delimiter is parsed as whole seconds.
+    ///
+    /// # Errors
+    ///
+    /// Return `epoch_archive::EpochError::AmbiguousDelimiter` if the delimiter appears more than
+    /// once after the sign, or `epoch_archive::EpochError` if either part fails to parse.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use epoch_archive::Epoch;
+    ///
+    /// let epoch = Epoch::parse_with_delimiter("1700000000:250", ':').unwrap();
+    /// assert_eq!(epoch, Epoch::new(1_700_000_000).with_millis(250));
+    ///
+    /// let epoch = Epoch::parse_with_delimiter("-1-999", '-').unwrap();
+    /// assert_eq!(epoch, Epoch::new(-1).with_millis(999));
+    /// ```
+    pub fn parse_with_delimiter(s: &str, delimiter: char) -> Result<Self, EpochError> {
+        let sign_len = usize::from(s.starts_with(['+', '-']));
+        let unsigned = &s[sign_len..];
+        if unsigned.matches(delimiter).count() > 1 {
+            return Err(EpochError::AmbiguousDelimiter(s.to_string()));
+        }
+
+        match unsigned.find(delimiter) {
+            Some(index) => {
+                let (epoch, subsecond) = s.split_at(sign_len + index);
+                Ok(Self {
+                    epoch: epoch.parse()?,
+                    subsecond: subsecond[delimiter.len_utf8()..].parse()?,
+                })
+            }
+            None => Ok(Self::new(s.parse()?)),
+        }
+    }
+
+    /// Parses the form written by [`Epoch::format`], with `options` deciding which fraction
+    /// lengths are accepted and how they map to a precision. A string without a `.` is parsed as
+    /// whole seconds regardless of the options.
+    ///
+    /// # Errors
+    ///
+    /// Return `epoch_archive::EpochError::InvalidFractionDigits` if the fraction has fewer than
+    /// `min_frac_digits` or more than `max_frac_digits` digits, or a length the pad policy
+    /// rejects, `epoch_archive::EpochError::AmbiguousDelimiter` if there is more than one `.`,
+    /// or `epoch_archive::EpochError` if either part fails to parse.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use epoch_archive::{Epoch, PadPolicy, ParseOptions};
+    ///
+    /// let options = ParseOptions {
+    ///     min_frac_digits: 1,
+    ///     max_frac_digits: 9,
+    ///     pad_policy: PadPolicy::PadRight,
+    /// };
+    /// let epoch = Epoch::parse_with_options("1.5", options).unwrap();
+    /// assert_eq!(epoch, Epoch::new(1).with_millis(500));
+    ///
+    /// assert!(Epoch::parse_with_options("1.5", ParseOptions::default()).is_err());
+    /// ```
+    pub fn parse_with_options(s: &str, options: ParseOptions) -> Result<Self, EpochError> {
+        if s.matches(DELIMITER).count() > 1 {
+            return Err(EpochError::AmbiguousDelimiter(s.to_string()));
+        }
+        let Some((epoch, fraction)) = s.split_once(DELIMITER) else {
+            return Ok(Self::new(s.parse()?));
+        };
+
+        let digits = fraction.len();
+        let max_digits = options.max_frac_digits.min(POWERS_OF_TEN.len() - 1);
+        if digits == 0 || digits < options.min_frac_digits || digits > max_digits {
+            return Err(EpochError::InvalidFractionDigits(digits));
+        }
+
+        let width = match options.pad_policy {
+            PadPolicy::Reject if digits % 3 != 0 => {
+                return Err(EpochError::InvalidFractionDigits(digits));
+            }
+            PadPolicy::Reject => digits,
+            PadPolicy::PadRight => digits.next_multiple_of(3),
+        };
+
+        Ok(Self {
+            epoch: epoch.parse()?,
+            subsecond: format!("{fraction:0<width$}").parse()?,
+        })
+    }
+
+    /// Parses an RFC 3339 timestamp such as `"2023-11-14T22:13:20.123Z"`, the form written by
+    /// [`Epoch::to_rfc3339`].
+    ///
+    /// The timestamp ends in `Z` or a numeric offset like `+02:00`, which is subtracted to get
+    /// back to UTC. The fraction is stored at the precision matching its number of digits, so 3
+    /// digits become `SubSecond::Milli`, 6 `SubSecond::Micro` and 9 `SubSecond::Nano`. Other
+    /// lengths are padded with zeros on the right up to the next of these, and a timestamp
+    /// without a fraction is stored as `SubSecond::None`. Leap seconds (`23:59:60`) are rejected.
+    ///
+    /// # Errors
+    ///
+    /// Return `epoch_archive::EpochError::InvalidRfc3339` if the string is not an RFC 3339
+    /// timestamp or names a date or time that does not exist, or
+    /// `epoch_archive::EpochError::InvalidFractionDigits` if the fraction has more than 9 digits.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use epoch_archive::Epoch;
+    ///
+    /// let epoch = Epoch::from_rfc3339("2023-11-14T22:13:20.123456Z").unwrap();
+    /// assert_eq!(epoch, Epoch::new(1_700_000_000).with_micros(123_456));
+    /// assert_eq!(epoch.to_rfc3339().unwrap(), "2023-11-14T22:13:20.123456Z");
+    ///
+    /// let epoch = Epoch::from_rfc3339("2023-11-15T00:13:20+02:00").unwrap();
+    /// assert_eq!(epoch, Epoch::new(1_700_000_000));
+    /// ```
+    pub fn from_rfc3339(s: &str) -> Result<Self, EpochError> {
+        let invalid = || EpochError::InvalidRfc3339(s.to_string());
+        if !s.is_ascii() || s.len() < 20 {
+            return Err(invalid());
+        }
+
+        let (date_time, mut rest) = s.split_at(19);
+        let separators = [(4, b'-'), (7, b'-'), (13, b':'), (16, b':')];
+        if separators
+            .iter()
+            .any(|&(i, c)| date_time.as_bytes()[i] != c)
+            || !matches!(date_time.as_bytes()[10], b'T' | b't')
+        {
+            return Err(invalid());
+        }
+        let field = |range| parse_digits(&date_time[range]).ok_or_else(invalid);
+        let (year, month, day) = (field(0..4)?, field(5..7)?, field(8..10)?);
+        let (hour, minute, second) = (field(11..13)?, field(14..16)?, field(17..19)?);
+        if !(1..=12).contains(&month)
+            || !(1..=days_in_month(year, month)).contains(&day)
+            || hour > 23
+            || minute > 59
+            || second > 59
+        {
+            return Err(invalid());
+        }
+
+        let mut subsecond = SubSecond::None;
+        if let Some(fraction) = rest.strip_prefix('.') {
+            let digits = fraction.bytes().take_while(u8::is_ascii_digit).count();
+            if digits == 0 {
+                return Err(invalid());
+            }
+
+            subsecond = SubSecond::from_fraction(&fraction[..digits])?;
+            rest = &fraction[digits..];
+        }
+
+        let offset = match rest.as_bytes() {
+            [b'Z' | b'z'] => 0,
+            [sign @ (b'+' | b'-'), _, _, b':', _, _] => {
+                let hours = parse_digits(&rest[1..3]).ok_or_else(invalid)?;
+                let minutes = parse_digits(&rest[4..6]).ok_or_else(invalid)?;
+                if hours > 23 || minutes > 59 {
+                    return Err(invalid());
+                }
+
+                let offset = hours * 3600 + minutes * 60;
+                if *sign == b'-' { -offset } else { offset }
+            }
+            _ => return Err(invalid()),
+        };
+
+        Ok(Self {
+            epoch: days_from_civil(year, month, day) * SECONDS_PER_DAY
+                + hour * 3600
+                + minute * 60
+                + second
+                - offset,
+            subsecond,
+        })
+    }
+
+    // -----------------------------
+    // ---------- GETTERS ----------
+    // -----------------------------
+
+    /// Returns the epoch value.
+    #[must_use]
+    pub fn epoch(&self) -> i64 {
+        self.epoch
+    }
+
+    /// Returns true if the epoch is `0` and a subsecond is set, i.e. it only holds a fraction of
+    /// a second. A zero subsecond such as `SubSecond::Milli(0)` still counts.
+    #[must_use]
+    pub fn is_sub_second_only(&self) -> bool {
+        self.epoch == 0 && !matches!(self.subsecond, SubSecond::None)
+    }
+
+    /// Returns the optional millisecond value.
+    ///
+    /// If no value is present, this returns None.
+    #[must_use]
+    pub fn subsecond(&self) -> &SubSecond {
+        &self.subsecond
+    }
+
+    /// Returns the total number of nanoseconds since the Unix epoch, a single scalar that
+    /// sorts and subtracts like the instant itself. An `i128` holds every epoch without
+    /// overflowing.
+    ///
+    /// The subsecond is always added to the seconds, the same way [`Epoch::format`] presents
+    /// it, so `-1.999` is one millisecond before the Unix epoch.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use epoch_archive::Epoch;
+    ///
+    /// assert_eq!(Epoch::new(1).with_micros(5).as_nanos(), 1_000_005_000);
+    /// assert_eq!(Epoch::new(-1).with_millis(999).as_nanos(), -1_000_000);
+    /// ```
+    #[must_use]
+    pub fn as_nanos(&self) -> i128 {
+        i128::from(self.epoch) * 1_000_000_000 + i128::from(self.subsecond.as_nanos())
+    }
+
+    /// Returns the number of nanoseconds since the Unix epoch as an `i64`.
+    ///
+    /// # Errors
+    ///
+    /// Return `epoch_archive::EpochError::OutOfRange` if the epoch lies outside the range an `i64`
+    /// of nanoseconds can hold, roughly the years 1677 to 2262.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use epoch_archive::Epoch;
+    ///
+    /// let epoch = Epoch::new(1).with_millis(500);
+    /// assert_eq!(epoch.to_nanos_i64().unwrap(), 1_500_000_000);
+    /// ```
+    pub fn to_nanos_i64(&self) -> Result<i64, EpochError> {
+        i64::try_from(self.as_nanos()).map_err(|_| EpochError::OutOfRange(self.epoch))
+    }
+
+    /// Returns true if both epochs represent the same instant, regardless of the precision the
+    /// subsecond is stored at. Equivalent to `==`.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use epoch_archive::Epoch;
+    ///
+    /// let millis = Epoch::new(1).with_millis(500);
+    /// let nanos = Epoch::new(1).with_nanos(500_000_000);
+    /// assert!(millis.value_eq(&nanos));
+    /// ```
+    #[must_use]
+    pub fn value_eq(&self, other: &Epoch) -> bool {
+        self.as_nanos() == other.as_nanos()
+    }
+
+    /// Compares two epochs by the instant they represent: by seconds first, then by the subsecond
+    /// converted to nanoseconds, ignoring the precision the subsecond is stored at. Equivalent
+    /// to `Ord::cmp`.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use epoch_archive::Epoch;
+    ///
+    /// let mut epochs = vec![Epoch::new(0).with_millis(2), Epoch::new(0).with_nanos(1)];
+    /// epochs.sort_by(Epoch::total_cmp);
+    /// assert_eq!(epochs[0], Epoch::new(0).with_nanos(1));
+    /// ```
+    #[must_use]
+    pub fn total_cmp(&self, other: &Epoch) -> core::cmp::Ordering {
+        self.epoch
+            .cmp(&other.epoch)
+            .then_with(|| self.subsecond.as_nanos().cmp(&other.subsecond.as_nanos()))
+    }
+
+    /// Returns the earliest epoch in the slice, or `None` if the slice is empty.
+    ///
+    /// Epochs are compared by the instant they represent, so `Milli(1)` and `Micro(1000)` are
+    /// treated as the same point in time regardless of their precision.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use epoch_archive::Epoch;
+    ///
+    /// let epochs = [Epoch::new(1).with_millis(2), Epoch::new(1).with_nanos(1)];
+    /// assert_eq!(Epoch::min_of(&epochs), Some(&epochs[1]));
+    /// ```
+    #[must_use]
+    pub fn min_of(epochs: &[Epoch]) -> Option<&Epoch> {
+        epochs.iter().min_by_key(|epoch| epoch.as_nanos())
+    }
+
+    /// Returns the latest epoch in the slice, or `None` if the slice is empty.
+    ///
+    /// Epochs are compared by the instant they represent, see [`Epoch::min_of`].
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use epoch_archive::Epoch;
+    ///
+    /// let epochs = [Epoch::new(1).with_millis(2), Epoch::new(1).with_nanos(1)];
+    /// assert_eq!(Epoch::max_of(&epochs), Some(&epochs[0]));
+    /// ```
+    #[must_use]
+    pub fn max_of(epochs: &[Epoch]) -> Option<&Epoch> {
+        epochs.iter().max_by_key(|epoch| epoch.as_nanos())
+    }
+
+    /// Returns the signed number of nanoseconds from `other` to this epoch, positive when this
+    /// epoch is the later one.
+    ///
+    /// Both epochs are converted to nanoseconds first, so differing precisions are compared by
+    /// the instant they represent: `Milli(1)` and `Micro(1000)` are zero nanoseconds apart.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use epoch_archive::Epoch;
+    ///
+    /// let earlier = Epoch::new(1).with_millis(900);
+    /// let later = Epoch::new(2).with_micros(100_000);
+    /// assert_eq!(later.signed_nanos_since(&earlier), 200_000_000);
+    /// assert_eq!(earlier.signed_nanos_since(&later), -200_000_000);
+    /// ```
+    #[must_use]
+    pub fn signed_nanos_since(&self, other: &Epoch) -> i128 {
+        self.as_nanos() - other.as_nanos()
+    }
+
+    /// Returns how much time passed from `other` to this epoch, like
+    /// [`SystemTime::duration_since`].
+    ///
+    /// # Errors
+    ///
+    /// Return `epoch_archive::EpochError::NegativeDuration` carrying the signed difference in
+    /// nanoseconds if `other` is later than this epoch.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use epoch_archive::Epoch;
+    /// use std::time::Duration;
+    ///
+    /// let since = Epoch::new(2).duration_since(&Epoch::new(1).with_millis(500));
+    /// assert_eq!(since.unwrap(), Duration::from_millis(500));
+    /// assert!(Epoch::new(1).duration_since(&Epoch::new(2)).is_err());
+    /// ```
+    pub fn duration_since(&self, other: &Epoch) -> Result<Duration, EpochError> {
+        let nanos = self.signed_nanos_since(other);
+        if nanos < 0 {
+            return Err(EpochError::NegativeDuration(nanos));
+        }
+
+        Ok(duration_from_nanos(nanos.cast_unsigned()))
+    }
+
+    /// Adds `other` to this epoch, treating it as an offset, or returns `None` if the seconds
+    /// overflow.
+    ///
+    /// The subseconds are summed and carried into the seconds. The result keeps the finer of the
+    /// two precisions, since the coarser subsecond is always an exact multiple of the finer unit:
+    /// `Milli + Milli` stays `Milli`, `Milli + Nano` becomes `Nano`, and `SubSecond::None` only
+    /// results when neither epoch has a subsecond.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use epoch_archive::{Epoch, SubSecond};
+    ///
+    /// let sum = Epoch::new(1).with_millis(700).add_epoch(&Epoch::new(2).with_millis(400));
+    /// assert_eq!(sum, Some(Epoch::new(4).with_millis(100)));
+    ///
+    /// let sum = Epoch::new(1).with_millis(1).add_epoch(&Epoch::new(0).with_nanos(1));
+    /// assert!(matches!(sum.unwrap().subsecond(), SubSecond::Nano(1_000_001)));
+    /// ```
+    #[must_use]
+    pub fn add_epoch(&self, other: &Epoch) -> Option<Epoch> {
+        let nanos = self.subsecond.as_nanos() + other.subsecond.as_nanos();
+        let epoch = self
+            .epoch
+            .checked_add(other.epoch)?
+            .checked_add(i64::from(nanos >= 1_000_000_000))?;
+        let nanos = nanos % 1_000_000_000;
+
+        let subsecond = match (&self.subsecond, &other.subsecond) {
+            (SubSecond::Nano(_), _) | (_, SubSecond::Nano(_)) => SubSecond::Nano(nanos),
+            (SubSecond::Micro(_), _) | (_, SubSecond::Micro(_)) => {
+                SubSecond::Micro(u32::try_from(nanos / 1_000).ok()?)
+            }
+            (SubSecond::Milli(_), _) | (_, SubSecond::Milli(_)) => {
+                SubSecond::Milli(u16::try_from(nanos / 1_000_000).ok()?)
+            }
+            (SubSecond::None, SubSecond::None) => SubSecond::None,
+        };
+
+        Some(Self { epoch, subsecond })
+    }
+
+    /// Moves the epoch forward by `duration`, or returns `None` if the seconds overflow once the
+    /// subsecond is carried. Precision is handled like the `Add` implementation, which panics
+    /// where this returns `None`.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use epoch_archive::Epoch;
+    /// use std::time::Duration;
+    ///
+    /// let epoch = Epoch::new(1).with_millis(500);
+    /// assert_eq!(
+    ///     epoch.checked_add(Duration::from_millis(600)),
+    ///     Some(Epoch::new(2).with_millis(100))
+    /// );
+    /// assert_eq!(Epoch::new(i64::MAX).checked_add(Duration::from_secs(1)), None);
+    /// ```
+    #[must_use]
+    pub fn checked_add(&self, rhs: Duration) -> Option<Epoch> {
+        self.offset_by(rhs, true)
+    }
+
+    /// Moves the epoch backward by `duration`, or returns `None` if the seconds overflow once
+    /// the subsecond is borrowed. Precision is handled like the `Sub` implementation, which
+    /// panics where this returns `None`.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use epoch_archive::Epoch;
+    /// use std::time::Duration;
+    ///
+    /// let epoch = Epoch::new(1).with_millis(500);
+    /// assert_eq!(
+    ///     epoch.checked_sub(Duration::from_millis(600)),
+    ///     Some(Epoch::new(0).with_millis(900))
+    /// );
+    /// assert_eq!(Epoch::new(i64::MIN).checked_sub(Duration::from_nanos(1)), None);
+    /// ```
+    #[must_use]
+    pub fn checked_sub(&self, rhs: Duration) -> Option<Epoch> {
+        self.offset_by(rhs, false)
+    }
+
+    /// Moves the epoch forward by `duration`, clamping to the latest representable instant if
+    /// the seconds overflow. A clamped result keeps the precision the `Add` implementation would
+    /// pick, with the largest subsecond it can hold.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use epoch_archive::Epoch;
+    /// use std::time::Duration;
+    ///
+    /// let epoch = Epoch::new(i64::MAX).with_millis(500);
+    /// assert_eq!(
+    ///     epoch.saturating_add(Duration::from_secs(1)),
+    ///     Epoch::new(i64::MAX).with_millis(999)
+    /// );
+    /// ```
+    #[must_use]
+    pub fn saturating_add(&self, rhs: Duration) -> Epoch {
+        let moved = Self::saturating_from_nanos(self.offset_nanos(rhs, true));
+        self.with_offset_precision(&moved, rhs)
+    }
+
+    /// Moves the epoch backward by `duration`, clamping to the earliest representable instant if
+    /// the seconds overflow. A clamped result keeps the precision the `Sub` implementation would
+    /// pick, with a zero subsecond.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use epoch_archive::Epoch;
+    /// use std::time::Duration;
+    ///
+    /// let epoch = Epoch::new(i64::MIN).with_millis(500);
+    /// assert_eq!(
+    ///     epoch.saturating_sub(Duration::from_secs(1)),
+    ///     Epoch::new(i64::MIN).with_millis(0)
+    /// );
+    /// ```
+    #[must_use]
+    pub fn saturating_sub(&self, rhs: Duration) -> Epoch {
+        let moved = Self::saturating_from_nanos(self.offset_nanos(rhs, false));
+        self.with_offset_precision(&moved, rhs)
+    }
+
+    /// Negates the epoch, mirroring it around the Unix epoch, or returns `None` if the result
+    /// does not fit, which only happens for `i64::MIN` seconds with a zero subsecond.
+    ///
+    /// The subsecond always counts forward from the seconds, so negating renormalizes it:
+    /// `1.250` becomes `-2.750`, i.e. `-1.25` seconds. The precision is kept.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use epoch_archive::Epoch;
+    ///
+    /// let epoch = Epoch::new(1).with_millis(250);
+    /// assert_eq!(epoch.checked_neg(), Some(Epoch::new(-2).with_millis(750)));
+    /// assert_eq!(Epoch::MIN.checked_neg(), None);
+    /// ```
+    #[must_use]
+    pub fn checked_neg(&self) -> Option<Epoch> {
+        let negated = Self::checked_from_nanos(-self.as_nanos())?;
+
+        Some(Self {
+            epoch: negated.epoch,
+            subsecond: SubSecond::from_nanos_in_unit(
+                negated.subsecond.as_nanos(),
+                self.subsecond.unit_nanos(),
+            ),
+        })
+    }
+
+    /// Returns the distance of the epoch from the Unix epoch as a non-negative epoch, keeping
+    /// the precision.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the epoch is [`Epoch::MIN`] with a zero subsecond, whose negation does not fit.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use epoch_archive::Epoch;
+    ///
+    /// assert_eq!(Epoch::new(-2).with_millis(750).abs(), Epoch::new(1).with_millis(250));
+    /// assert_eq!(Epoch::new(3).abs(), Epoch::new(3));
+    /// ```
+    #[must_use]
+    pub fn abs(&self) -> Epoch {
+        if self.epoch < 0 {
+            -self.clone()
+        } else {
+            self.clone()
+        }
+    }
+
+    /// Moves the epoch by `duration`, forward or backward, keeping the finer of the two
+    /// precisions involved. Returns `None` if the seconds overflow.
+    fn offset_by(&self, duration: Duration, forward: bool) -> Option<Self> {
+        let moved = Self::checked_from_nanos(self.offset_nanos(duration, forward))?;
+        Some(self.with_offset_precision(&moved, duration))
+    }
+
+    /// Returns the total nanoseconds of the epoch moved by `duration`, forward or backward.
+    fn offset_nanos(&self, duration: Duration, forward: bool) -> i128 {
+        let offset = duration.as_nanos().cast_signed();
+        if forward {
+            self.as_nanos() + offset
+        } else {
+            self.as_nanos() - offset
+        }
+    }
+
+    /// Stores the subsecond of `moved`, this epoch offset by `duration`, at the finer of this
+    /// epoch's precision and the precision `duration` needs.
+    fn with_offset_precision(&self, moved: &Self, duration: Duration) -> Self {
+        let duration_unit = [1_000_000_000, 1_000_000, 1_000, 1]
+            .into_iter()
+            .find(|unit| u64::from(duration.subsec_nanos()) % unit == 0)
+            .unwrap_or(1);
+        let unit = self.subsecond.unit_nanos().min(duration_unit);
+
+        Self {
+            epoch: moved.epoch,
+            subsecond: SubSecond::from_nanos_in_unit(moved.subsecond.as_nanos(), unit),
+        }
+    }
+
+    /// Floors the epoch to the start of the `step` wide window it falls in, with windows aligned
+    /// to `anchor` instead of the Unix epoch, i.e. `anchor + floor((self - anchor) / step) * step`.
+    ///
+    /// Epochs before the anchor floor toward negative infinity, into the window that ends at or
+    /// before them. The result has `SubSecond::Nano` precision, and is clamped to the
+    /// representable range if it falls outside of it.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `step` is zero.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use epoch_archive::Epoch;
+    /// use std::time::Duration;
+    ///
+    /// let anchor = Epoch::new(1_700_000_000);
+    /// let epoch = Epoch::new(1_700_000_000 + 5_400);
+    /// let floored = epoch.floor_to_anchored(&anchor, Duration::from_secs(3_600));
+    /// assert_eq!(floored.epoch(), 1_700_003_600);
+    /// ```
+    #[must_use]
+    pub fn floor_to_anchored(&self, anchor: &Epoch, step: Duration) -> Epoch {
+        assert!(!step.is_zero(), "step should be greater than zero");
+
+        let step = step.as_nanos().cast_signed();
+        let anchor = anchor.as_nanos();
+        let windows = (self.as_nanos() - anchor).div_euclid(step);
+
+        Self::saturating_from_nanos(anchor + windows * step)
+    }
+
+    /// Returns how long it is from this epoch until the next multiple of `step` since the Unix
+    /// epoch, e.g. the next full minute for a 60 second step.
+    ///
+    /// An epoch that already lies exactly on a boundary returns the whole `step`, not zero, so a
+    /// scheduler that sleeps for the returned duration after firing waits for the following
+    /// boundary instead of firing again straight away.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `step` is zero.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use epoch_archive::Epoch;
+    /// use std::time::Duration;
+    ///
+    /// let minute = Duration::from_secs(60);
+    /// let epoch = Epoch::new(1_700_000_035).with_millis(500);
+    /// assert_eq!(epoch.until_next_multiple(minute), Duration::from_millis(4_500));
+    /// assert_eq!(Epoch::new(1_700_000_040).until_next_multiple(minute), minute);
+    /// ```
+    #[must_use]
+    pub fn until_next_multiple(&self, step: Duration) -> Duration {
+        assert!(!step.is_zero(), "step should be greater than zero");
+
+        let step = step.as_nanos().cast_signed();
+        let elapsed = self.as_nanos().rem_euclid(step);
+
+        duration_from_nanos((step - elapsed).cast_unsigned())
+    }
+
+    /// Coarsens the subsecond to `precision`, dropping the digits below it. The subsecond is
+    /// always added to the seconds, so this moves the epoch back in time, also for negative
+    /// epochs.
+    ///
+    /// Truncating to a finer precision than the subsecond has only changes how it is stored, and
+    /// truncating to [`Precision::Second`] gives `SubSecond::None`.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use epoch_archive::{Epoch, Precision, SubSecond};
+    ///
+    /// let epoch = Epoch::new(1).with_nanos(123_456_789);
+    /// assert!(matches!(epoch.truncate_to(Precision::Milli).subsecond(), SubSecond::Milli(123)));
+    /// assert!(matches!(epoch.truncate_to(Precision::Second).subsecond(), SubSecond::None));
+    /// ```
+    #[must_use]
+    pub fn truncate_to(&self, precision: Precision) -> Epoch {
+        Self {
+            epoch: self.epoch,
+            subsecond: SubSecond::from_nanos_in_unit(
+                self.subsecond.as_nanos(),
+                precision.unit_nanos(),
+            ),
+        }
+    }
+
+    /// Rounds the subsecond to the nearest `precision`, with halves rounded up, carrying into the
+    /// seconds when it rounds up to a whole second.
+    ///
+    /// An epoch that would round past the latest representable instant is truncated instead.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use epoch_archive::{Epoch, Precision, SubSecond};
+    ///
+    /// let epoch = Epoch::new(1).with_nanos(123_900_000);
+    /// assert!(matches!(epoch.round_to(Precision::Milli).subsecond(), SubSecond::Milli(124)));
+    ///
+    /// let carried = Epoch::new(1).with_micros(999_600).round_to(Precision::Milli);
+    /// assert_eq!(carried.epoch(), 2);
+    /// assert!(matches!(carried.subsecond(), SubSecond::Milli(0)));
+    /// ```
+    #[must_use]
+    pub fn round_to(&self, precision: Precision) -> Epoch {
+        let unit = precision.unit_nanos();
+        let rounded = (self.subsecond.as_nanos() + unit / 2) / unit * unit;
+
+        Self::saturating_from_nanos(i128::from(self.epoch) * 1_000_000_000 + i128::from(rounded))
+            .truncate_to(precision)
+    }
+
+    /// Returns the epoch in `sorted` closest to this one, or `None` if the slice is empty.
+    ///
+    /// `sorted` has to be in ascending order by the instant each epoch represents, it is searched
+    /// with a binary search. When this epoch lies exactly halfway between two references the
+    /// earlier one is returned.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use epoch_archive::Epoch;
+    ///
+    /// let frames = [Epoch::new(0), Epoch::new(0).with_millis(40), Epoch::new(0).with_millis(80)];
+    /// let snapped = Epoch::new(0).with_millis(55).nearest_in(&frames);
+    /// assert_eq!(snapped, Some(&frames[1]));
+    /// ```
+    #[must_use]
+    pub fn nearest_in<'a>(&self, sorted: &'a [Epoch]) -> Option<&'a Epoch> {
+        let target = self.as_nanos();
+        let index = sorted.partition_point(|epoch| epoch.as_nanos() < target);
+
+        let after = sorted.get(index);
+        let before = index.checked_sub(1).and_then(|index| sorted.get(index));
+        match (before, after) {
+            (Some(before), Some(after)) => {
+                if target - before.as_nanos() <= after.as_nanos() - target {
+                    Some(before)
+                } else {
+                    Some(after)
+                }
+            }
+            (before, after) => before.or(after),
+        }
+    }
+
+    /// Returns the epoch value as a string with the specified delimiter.
+    #[must_use]
+    pub fn format_with_delimiter(&self, delimiter: char) -> String {
+        match self.subsecond {
+            SubSecond::None => format!("{:}", self.epoch),
+            SubSecond::Milli(ms) => format!("{:}{}{:03}", self.epoch, delimiter, ms),
+            SubSecond::Micro(us) => format!("{:}{}{:06}", self.epoch, delimiter, us),
+            SubSecond::Nano(ns) => format!("{:}{}{:09}", self.epoch, delimiter, ns),
+        }
+    }
+
+    /// Returns the epoch value as a string.
+    #[must_use]
+    pub fn format(&self) -> String {
+        Self::format_with_delimiter(self, DELIMITER)
+    }
+
+    /// Returns [`Epoch::format`] right-aligned to `total_width` characters, padded on the left
+    /// with `fill`, so epochs of differing magnitude and precision line up in a column. The sign
+    /// and the delimiter count toward the width.
+    ///
+    /// A string already wider than `total_width` is returned unchanged rather than truncated.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use epoch_archive::Epoch;
+    ///
+    /// assert_eq!(Epoch::new(-1).with_millis(5).format_padded(10, ' '), "    -1.005");
+    /// assert_eq!(Epoch::new(1_700_000_000).format_padded(4, '0'), "1700000000");
+    /// ```
+    #[must_use]
+    pub fn format_padded(&self, total_width: usize, fill: char) -> String {
+        let formatted = self.format();
+        let width = formatted.chars().count();
+        if width >= total_width {
+            return formatted;
+        }
+
+        let mut padded: String = core::iter::repeat_n(fill, total_width - width).collect();
+        padded.push_str(&formatted);
+        padded
+    }
+
+    /// Returns the canonical lossless string form of the epoch, which [`Epoch::from_str`] parses
+    /// back into exactly the same `Epoch`, precision included.
+    ///
+    /// The signed seconds are followed by a `.` and the subsecond zero padded to its native width
+    /// of 3, 6 or 9 digits, or nothing for `SubSecond::None`. The subsecond is always added to
+    /// the seconds, so `"-1.250"` is the instant 0.75 seconds before the Unix epoch, not 1.25.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use epoch_archive::Epoch;
+    ///
+    /// let epoch = Epoch::new(-1).with_micros(250);
+    /// assert_eq!(epoch.to_decimal_string(), "-1.000250");
+    /// assert_eq!(epoch.to_decimal_string().parse::<Epoch>().unwrap(), epoch);
+    /// ```
+    #[must_use]
+    pub fn to_decimal_string(&self) -> String {
+        self.format()
+    }
+
+    /// Returns the epoch as an RFC 3339 timestamp in UTC, with 3, 6 or 9 fractional digits to
+    /// match the stored precision, or no fraction for `SubSecond::None`.
+    ///
+    /// # Errors
+    ///
+    /// Return `epoch_archive::EpochError::OutOfRange` if the epoch falls outside the years
+    /// 0000-9999.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use epoch_archive::Epoch;
+    ///
+    /// let epoch = Epoch::new(1_700_000_000).with_micros(123_456);
+    /// assert_eq!(epoch.to_rfc3339().unwrap(), "2023-11-14T22:13:20.123456Z");
+    /// assert_eq!(Epoch::new(0).to_rfc3339().unwrap(), "1970-01-01T00:00:00Z");
+    /// ```
+    pub fn to_rfc3339(&self) -> Result<String, EpochError> {
+        self.to_rfc3339_with_digits(self.subsecond.digits())
+    }
+
+    /// Returns the epoch as an RFC 3339 timestamp in UTC with exactly `digits` fractional digits,
+    /// regardless of the stored precision. `digits == 0` omits the fraction entirely.
+    ///
+    /// Digits beyond the stored precision are zero filled, and digits below it are truncated.
+    ///
+    /// # Errors
+    ///
+    /// Return `epoch_archive::EpochError::InvalidFractionDigits` if `digits` is larger than 9, or
+    /// `epoch_archive::EpochError::OutOfRange` if the epoch falls outside the years 0000-9999.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use epoch_archive::Epoch;
+    ///
+    /// let epoch = Epoch::new(1_700_000_000).with_nanos(123_456_789);
+    /// assert_eq!(epoch.to_rfc3339_with_digits(3).unwrap(), "2023-11-14T22:13:20.123Z");
+    /// ```
+    pub fn to_rfc3339_with_digits(&self, digits: usize) -> Result<String, EpochError> {
+        if digits >= POWERS_OF_TEN.len() {
+            return Err(EpochError::InvalidFractionDigits(digits));
+        }
+        if !(RFC3339_MIN..=RFC3339_MAX).contains(&self.epoch) {
+            return Err(EpochError::OutOfRange(self.epoch));
+        }
+
+        let (year, month, day) = civil_from_days(self.epoch.div_euclid(SECONDS_PER_DAY));
+        let seconds = self.epoch.rem_euclid(SECONDS_PER_DAY);
+
+        let mut rfc3339 = format!(
+            "{year:04}-{month:02}-{day:02}T{:02}:{:02}:{:02}",
+            seconds / 3600,
+            seconds % 3600 / 60,
+            seconds % 60
+        );
+        if digits > 0 {
+            let fraction = self.subsecond.as_nanos() / POWERS_OF_TEN[9 - digits];
+            write!(rfc3339, ".{fraction:0digits$}").expect("writing to a String cannot fail");
+        }
+        rfc3339.push('Z');
+
+        Ok(rfc3339)
+    }
+
+    /// Returns the epoch as an RFC 3339 timestamp in UTC, rendering inserted leap seconds as
+    /// `23:59:60`. The fraction has as many digits as the stored precision.
+    ///
+    /// Each entry of `leap_table` is the Unix time at which a leap second was inserted, the
+    /// midnight that follows it. Unix time repeats that second, so an epoch inside the second
+    /// starting at an entry is rendered as second 60 of the minute before. Entries that are not
+    /// on a minute boundary are ignored, and an empty table formats like any other timestamp.
+    ///
+    /// # Errors
+    ///
+    /// Return `epoch_archive::EpochError::OutOfRange` if the epoch falls outside the years
+    /// 0000-9999.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use epoch_archive::Epoch;
+    ///
+    /// // The leap second at the end of 2016.
+    /// let leap_table = [Epoch::new(1_483_228_800)];
+    /// let epoch = Epoch::new(1_483_228_800).with_millis(500);
+    /// assert_eq!(
+    ///     epoch.to_rfc3339_with_leap(&leap_table).unwrap(),
+    ///     "2016-12-31T23:59:60.500Z"
+    /// );
+    /// ```
+    #[cfg(feature = "leap-seconds")]
+    pub fn to_rfc3339_with_leap(&self, leap_table: &[Epoch]) -> Result<String, EpochError> {
+        let digits = self.subsecond.digits();
+
+        let nanos = self.as_nanos();
+        let in_leap_second = leap_table.iter().any(|leap| {
+            let start = leap.as_nanos();
+            leap.epoch.rem_euclid(60) == 0 && (start..start + 1_000_000_000).contains(&nanos)
+        });
+        if !in_leap_second {
+            return self.to_rfc3339_with_digits(digits);
+        }
+
+        let previous = Self {
+            epoch: self
+                .epoch
+                .checked_sub(1)
+                .ok_or(EpochError::OutOfRange(self.epoch))?,
+            subsecond: self.subsecond.clone(),
+        };
+        let mut rfc3339 = previous.to_rfc3339_with_digits(digits)?;
+        rfc3339.replace_range(17..19, "60");
+
+        Ok(rfc3339)
+    }
+}
+
+impl Add<Duration> for Epoch {
+    type Output = Epoch;
+
+    /// Moves the epoch forward by `duration`, carrying the subsecond into the seconds.
+    ///
+    /// The result keeps the finer of the epoch's precision and the precision the duration
+    /// needs, so `Milli` plus a whole number of milliseconds stays `Milli`, while `Milli` plus a
+    /// duration with nanoseconds becomes `Nano`. A `SubSecond::None` epoch plus whole seconds
+    /// stays `SubSecond::None`.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the seconds overflow an `i64`.
+    fn add(self, duration: Duration) -> Self::Output {
+        self.offset_by(duration, true)
+            .expect("overflow when adding duration to epoch")
+    }
+}
+
+impl Sub<Duration> for Epoch {
+    type Output = Epoch;
+
+    /// Moves the epoch backward by `duration`, borrowing from the seconds when the subsecond
+    /// runs out. Precision is handled like the `Add` implementation.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the seconds overflow an `i64`.
+    fn sub(self, duration: Duration) -> Self::Output {
+        self.offset_by(duration, false)
+            .expect("overflow when subtracting duration from epoch")
+    }
+}
+
+impl Neg for Epoch {
+    type Output = Epoch;
+
+    /// Negates the epoch like [`Epoch::checked_neg`].
+    ///
+    /// # Panics
+    ///
+    /// Panics if the negated seconds overflow an `i64`.
+    fn neg(self) -> Self::Output {
+        self.checked_neg().expect("overflow when negating epoch")
+    }
+}
+
+impl core::fmt::Display for Epoch {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        write!(f, "{}", self.format())
+    }
+}
+
+impl FromStr for Epoch {
+    type Err = EpochError;
+
+    /// Parses the form written by [`Epoch::format`], the seconds optionally followed by a `.` and
+    /// a subsecond of 3, 6 or 9 digits. The seconds may have a single leading `+` or `-`, the
+    /// subsecond is unsigned.
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        if s.matches(DELIMITER).count() > 1 {
+            return Err(EpochError::AmbiguousDelimiter(s.to_string()));
+        }
+
+        match s.split_once(DELIMITER) {
+            Some((epoch, subsecond)) => Ok(Self {
+                epoch: epoch.parse()?,
+                subsecond: subsecond.parse()?,
+            }),
+            None => Ok(Self::new(s.parse()?)),
+        }
+    }
+}
+
+impl From<(i64, SubSecond)> for Epoch {
+    fn from((epoch, subsecond): (i64, SubSecond)) -> Self {
+        Self { epoch, subsecond }
+    }
+}
+
+impl From<Epoch> for (i64, SubSecond) {
+    fn from(epoch: Epoch) -> Self {
+        (epoch.epoch, epoch.subsecond)
+    }
+}
+
+#[cfg(feature = "std")]
+impl TryFrom<SystemTime> for Epoch {
+    type Error = EpochError;
+
+    /// Converts a `SystemTime` into an Epoch with `SubSecond::Nano` precision. Times before 1970
+    /// follow the usual floor convention, so 250 milliseconds before the Unix epoch becomes
+    /// epoch `-1` with `SubSecond::Nano(750_000_000)`.
+    ///
+    /// Fails with `EpochError::Overflow` if the seconds do not fit in an `i64`.
+    fn try_from(time: SystemTime) -> Result<Self, Self::Error> {
+        Self::checked_from_nanos(nanos_since_unix_epoch(time)).ok_or(EpochError::Overflow)
+    }
+}
+
+#[cfg(feature = "std")]
+impl TryFrom<Epoch> for SystemTime {
+    type Error = EpochError;
+
+    /// Converts an Epoch into the `SystemTime` it represents.
+    ///
+    /// Fails with `EpochError::OutOfRange` if the platform's `SystemTime` can't represent it.
+    fn try_from(epoch: Epoch) -> Result<Self, Self::Error> {
+        let nanos = epoch.as_nanos();
+        let offset = Duration::new(
+            u64::try_from(nanos.unsigned_abs() / 1_000_000_000).unwrap_or(u64::MAX),
+            u32::try_from(nanos.unsigned_abs() % 1_000_000_000).unwrap_or_default(),
+        );
+
+        if nanos < 0 {
+            UNIX_EPOCH.checked_sub(offset)
+        } else {
+            UNIX_EPOCH.checked_add(offset)
+        }
+        .ok_or(EpochError::OutOfRange(epoch.epoch))
+    }
+}
+
+/// Hashes the total nanoseconds, so epochs that compare equal at different precisions hash the
+/// same.
+impl Hash for Epoch {
+    fn hash<H: Hasher>(&self, state: &mut H) {
+        self.as_nanos().hash(state);
+    }
+}
+
+impl Default for Epoch {
+    fn default() -> Self {
+        Self {
+            epoch: 0,
+            subsecond: SubSecond::None,
+        }
+    }
+}
+
+/// Controls how [`Epoch::parse_with_options`] maps fractions of unusual lengths to a precision.
+///
+/// The default accepts exactly what [`Epoch::from_str`] accepts: 3, 6 or 9 fractional digits.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct ParseOptions {
+    /// The fewest fractional digits accepted when a fraction is present.
+    pub min_frac_digits: usize,
+    /// The most fractional digits accepted, at most 9.
+    pub max_frac_digits: usize,
+    /// What to do with a fraction that is not 3, 6 or 9 digits long.
+    pub pad_policy: PadPolicy,
+}
+
+impl Default for ParseOptions {
+    fn default() -> Self {
+        Self {
+            min_frac_digits: 3,
+            max_frac_digits: 9,
+            pad_policy: PadPolicy::Reject,
+        }
+    }
+}
+
+/// How a fraction that is not 3, 6 or 9 digits long is handled, see [`ParseOptions`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum PadPolicy {
+    /// Reject the fraction with `EpochError::InvalidFractionDigits`.
+    #[default]
+    Reject,
+    /// Pad the fraction with zeros on the right up to the next precision, so 1 or 2 digits
+    /// become `SubSecond::Milli`, 4 or 5 become `SubSecond::Micro`, and 7 or 8 become
+    /// `SubSecond::Nano`. `"1.5"` is read as 500 milliseconds.
+    PadRight,
+}
+
+/// The precision an [`Epoch`] is coarsened to by [`Epoch::truncate_to`] and [`Epoch::round_to`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub enum Precision {
+    /// Whole seconds, stored as `SubSecond::None`.
+    Second,
+    /// Milliseconds, stored as `SubSecond::Milli`.
+    Milli,
+    /// Microseconds, stored as `SubSecond::Micro`.
+    Micro,
+    /// Nanoseconds, stored as `SubSecond::Nano`.
+    Nano,
+}
+
+impl Precision {
+    /// Returns the length of one unit of this precision in nanoseconds.
+    fn unit_nanos(self) -> u64 {
+        match self {
+            Precision::Second => 1_000_000_000,
+            Precision::Milli => 1_000_000,
+            Precision::Micro => 1_000,
+            Precision::Nano => 1,
+        }
+    }
+}
+
+/// The fractional part of an [`Epoch`], stored at the precision it was created with.
+///
+/// The variants can be constructed directly, but they do not validate their value.
+/// Prefer [`SubSecond::milli`], [`SubSecond::micro`] and [`SubSecond::nano`], which
+/// return `None` when the value does not fit in a single second.
+///
+/// Subseconds compare by the fraction of a second they represent, not by their precision, so
+/// `Milli(1)`, `Micro(1000)` and `Nano(1_000_000)` are equal, as are `None` and a zero value.
+///
+/// Serialized as a two element sequence of a precision tag, `0` for `None` up to `3` for
+/// `Nano`, and the value, so `Milli(123)` is written as `[1, 123]`. Deserializing rejects
+/// unknown tags and values of a second or more.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(into = "SubSecondRepr", try_from = "SubSecondRepr")]
+pub enum SubSecond {
+    None,
+    Milli(u16),
+    Micro(u32),
+    Nano(u64),
+}
+
+impl SubSecond {
+    /// Creates a millisecond subsecond, returning `None` if `millis` is >= 1000.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use epoch_archive::SubSecond;
+    ///
+    /// assert!(matches!(SubSecond::milli(123), Some(SubSecond::Milli(123))));
+    /// assert_eq!(SubSecond::milli(1000), None);
+    /// ```
+    #[must_use]
+    pub fn milli(millis: u16) -> Option<Self> {
+        (millis < 1000).then_some(SubSecond::Milli(millis))
+    }
+
+    /// Creates a microsecond subsecond, returning `None` if `micros` is >= 1000000.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use epoch_archive::SubSecond;
+    ///
+    /// assert!(matches!(SubSecond::micro(123), Some(SubSecond::Micro(123))));
+    /// assert_eq!(SubSecond::micro(1_000_000), None);
+    /// ```
+    #[must_use]
+    pub fn micro(micros: u32) -> Option<Self> {
+        (micros < 1_000_000).then_some(SubSecond::Micro(micros))
+    }
+
+    /// Creates a nanosecond subsecond, returning `None` if `nanos` is >= 1000000000.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use epoch_archive::SubSecond;
+    ///
+    /// assert!(matches!(SubSecond::nano(123), Some(SubSecond::Nano(123))));
+    /// assert_eq!(SubSecond::nano(1_000_000_000), None);
+    /// ```
+    #[must_use]
+    pub fn nano(nanos: u64) -> Option<Self> {
+        (nanos < 1_000_000_000).then_some(SubSecond::Nano(nanos))
+    }
+
+    /// Parses the digits after the decimal point of a number of seconds, of any length from 1 to
+    /// 9, unlike [`SubSecond::from_str`] which only accepts exactly 3, 6 or 9 digits.
+    ///
+    /// The digits are read as a decimal fraction of a second and padded with zeros on the right
+    /// up to the next standard precision: 1 to 3 digits become `SubSecond::Milli`, 4 to 6
+    /// `SubSecond::Micro` and 7 to 9 `SubSecond::Nano`. So `"1"` is 100 milliseconds and
+    /// `"1234"` is `SubSecond::Micro(123_400)`. The same rules apply to `SubSecond::try_from`.
+    ///
+    /// # Errors
+    ///
+    /// Return `epoch_archive::EpochError::InvalidSubSecond` if `s` contains anything but ASCII
+    /// digits, or `epoch_archive::EpochError::InvalidFractionDigits` if it is empty or longer
+    /// than 9 digits.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use epoch_archive::SubSecond;
+    ///
+    /// assert!(matches!(SubSecond::from_fraction("1"), Ok(SubSecond::Milli(100))));
+    /// assert!(matches!(SubSecond::from_fraction("1234"), Ok(SubSecond::Micro(123_400))));
+    /// assert!(matches!(SubSecond::from_fraction("12345678"), Ok(SubSecond::Nano(123_456_780))));
+    /// ```
+    pub fn from_fraction(s: &str) -> Result<Self, EpochError> {
+        if !s.bytes().all(|b| b.is_ascii_digit()) {
+            return Err(EpochError::InvalidSubSecond(s.to_string()));
+        }
+        if s.is_empty() || s.len() >= POWERS_OF_TEN.len() {
+            return Err(EpochError::InvalidFractionDigits(s.len()));
+        }
+
+        let width = s.len().next_multiple_of(3);
+        format!("{s:0<width$}").parse()
+    }
+
+    /// Returns the subsecond value in nanoseconds, or 0 for `SubSecond::None`.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use epoch_archive::SubSecond;
+    ///
+    /// assert_eq!(SubSecond::None.as_nanos(), 0);
+    /// assert_eq!(SubSecond::Milli(123).as_nanos(), 123_000_000);
+    /// assert_eq!(SubSecond::Micro(123).as_nanos(), 123_000);
+    /// assert_eq!(SubSecond::Nano(123).as_nanos(), 123);
+    /// ```
+    #[must_use]
+    pub fn as_nanos(&self) -> u64 {
+        match self {
+            SubSecond::None => 0,
+            SubSecond::Milli(ms) => u64::from(*ms) * 1_000_000,
+            SubSecond::Micro(us) => u64::from(*us) * 1_000,
+            SubSecond::Nano(ns) => *ns,
+        }
+    }
+
+    /// Returns the precision the subsecond is stored at, or `None` for `SubSecond::None`.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use epoch_archive::{Precision, SubSecond};
+    ///
+    /// assert_eq!(SubSecond::None.precision(), None);
+    /// assert_eq!(SubSecond::Milli(0).precision(), Some(Precision::Milli));
+    /// assert_eq!(SubSecond::Nano(5).precision(), Some(Precision::Nano));
+    /// ```
+    #[must_use]
+    pub fn precision(&self) -> Option<Precision> {
+        match self {
+            SubSecond::None => None,
+            SubSecond::Milli(_) => Some(Precision::Milli),
+            SubSecond::Micro(_) => Some(Precision::Micro),
+            SubSecond::Nano(_) => Some(Precision::Nano),
+        }
+    }
+
+    /// Returns the number of fractional digits of this precision, 0 for `SubSecond::None`.
+    fn digits(&self) -> usize {
+        match self {
+            SubSecond::None => 0,
+            SubSecond::Milli(_) => 3,
+            SubSecond::Micro(_) => 6,
+            SubSecond::Nano(_) => 9,
+        }
+    }
+
+    /// Returns the length of one unit of this precision in nanoseconds, a whole second for
+    /// `SubSecond::None`.
+    fn unit_nanos(&self) -> u64 {
+        match self {
+            SubSecond::None => 1_000_000_000,
+            SubSecond::Milli(_) => 1_000_000,
+            SubSecond::Micro(_) => 1_000,
+            SubSecond::Nano(_) => 1,
+        }
+    }
+
+    /// Creates the subsecond of `nanos` at the precision whose unit is `unit_nanos`, as returned
+    /// by [`SubSecond::unit_nanos`]. `nanos` has to be below one second.
+    #[allow(clippy::cast_possible_truncation)]
+    fn from_nanos_in_unit(nanos: u64, unit_nanos: u64) -> Self {
+        match unit_nanos {
+            1_000_000_000 => SubSecond::None,
+            1_000_000 => SubSecond::Milli((nanos / 1_000_000) as u16),
+            1_000 => SubSecond::Micro((nanos / 1_000) as u32),
+            _ => SubSecond::Nano(nanos),
+        }
+    }
+}
+
+impl PartialEq for SubSecond {
+    fn eq(&self, other: &Self) -> bool {
+        self.as_nanos() == other.as_nanos()
+    }
+}
+
+impl Eq for SubSecond {}
+
+impl PartialOrd for SubSecond {
+    fn partial_cmp(&self, other: &Self) -> Option<core::cmp::Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+impl Ord for SubSecond {
+    fn cmp(&self, other: &Self) -> core::cmp::Ordering {
+        self.as_nanos().cmp(&other.as_nanos())
+    }
+}
+
+impl Hash for SubSecond {
+    fn hash<H: Hasher>(&self, state: &mut H) {
+        self.as_nanos().hash(state);
+    }
+}
+
+/// The serialized form of a [`SubSecond`], its precision tag and value.
+#[derive(Serialize, Deserialize)]
+struct SubSecondRepr(u8, u64);
+
+impl From<SubSecond> for SubSecondRepr {
+    fn from(subsecond: SubSecond) -> Self {
+        match subsecond {
+            SubSecond::None => Self(0, 0),
+            SubSecond::Milli(millis) => Self(1, u64::from(millis)),
+            SubSecond::Micro(micros) => Self(2, u64::from(micros)),
+            SubSecond::Nano(nanos) => Self(3, nanos),
+        }
+    }
+}
+
+impl TryFrom<SubSecondRepr> for SubSecond {
+    type Error = EpochError;
+
+    fn try_from(SubSecondRepr(tag, value): SubSecondRepr) -> Result<Self, Self::Error> {
+        let out_of_range = |unit| EpochError::SubSecondOutOfRange { value, unit };
+
+        match tag {
+            0 if value == 0 => Ok(SubSecond::None),
+            0 => Err(out_of_range("s")),
+            1 => u16::try_from(value)
+                .ok()
+                .and_then(SubSecond::milli)
+                .ok_or_else(|| out_of_range("ms")),
+            2 => u32::try_from(value)
+                .ok()
+                .and_then(SubSecond::micro)
+                .ok_or_else(|| out_of_range("us")),
+            3 => SubSecond::nano(value).ok_or_else(|| out_of_range("ns")),
+            _ => Err(EpochError::InvalidSubSecond(format!(
+                "unknown precision tag {tag}"
+            ))),
+        }
+    }
+}
+
+impl FromStr for SubSecond {
+    type Err = EpochError;
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        // The integer parsers accept a leading sign, which has no meaning for a fraction.
+        if !s.bytes().all(|b| b.is_ascii_digit()) {
+            return Err(EpochError::InvalidSubSecond(s.to_string()));
+        }
+
+        match s.len() {
+            3 => Ok(SubSecond::Milli(s.parse()?)),
+            6 => Ok(SubSecond::Micro(s.parse()?)),
+            9 => Ok(SubSecond::Nano(s.parse()?)),
+            _ => Err(EpochError::InvalidSubSecond(s.to_string())),
+        }
+    }
+}
+
+impl TryFrom<&str> for SubSecond {
+    type Error = EpochError;
+
+    /// Parses a fraction of a second of 1 to 9 digits, see [`SubSecond::from_fraction`].
+    fn try_from(s: &str) -> Result<Self, Self::Error> {
+        Self::from_fraction(s)
+    }
+}
+
+/// Returns the signed number of nanoseconds between the Unix epoch and `time`.
+#[cfg(feature = "std")]
+fn nanos_since_unix_epoch(time: SystemTime) -> i128 {
+    match time.duration_since(UNIX_EPOCH) {
+        Ok(since) => since.as_nanos().cast_signed(),
+        Err(before) => -before.duration().as_nanos().cast_signed(),
+    }
+}
+
+/// Converts a number of nanoseconds into a [`Duration`]. The seconds must fit in a `u64`, which
+/// they do for any difference between two epochs.
+#[allow(clippy::cast_possible_truncation)]
+fn duration_from_nanos(nanos: u128) -> Duration {
+    Duration::new(
+        (nanos / 1_000_000_000) as u64,
+        (nanos % 1_000_000_000) as u32,
+    )
+}
+
+/// Converts a number of days since 1970-01-01 into a proleptic Gregorian `(year, month, day)`.
+///
+/// See <http://howardhinnant.github.io/date_algorithms.html#civil_from_days>.
+fn civil_from_days(days: i64) -> (i64, i64, i64) {
+    let z = days + 719_468;
+    let era = z.div_euclid(146_097);
+    let doe = z.rem_euclid(146_097);
+    let yoe = (doe - doe / 1460 + doe / 36_524 - doe / 146_096) / 365;
+    let doy = doe - (365 * yoe + yoe / 4 - yoe / 100);
+    let mp = (5 * doy + 2) / 153;
+    let day = doy - (153 * mp + 2) / 5 + 1;
+    let month = if mp < 10 { mp + 3 } else { mp - 9 };
+    let year = yoe + era * 400 + i64::from(month <= 2);
+
+    (year, month, day)
+}
+
+/// Converts a proleptic Gregorian date into a number of days since 1970-01-01, the inverse of
+/// [`civil_from_days`].
+///
+/// See <http://howardhinnant.github.io/date_algorithms.html#days_from_civil>.
+fn days_from_civil(year: i64, month: i64, day: i64) -> i64 {
+    let year = year - i64::from(month <= 2);
+    let era = year.div_euclid(400);
+    let yoe = year.rem_euclid(400);
+    let mp = if month > 2 { month - 3 } else { month + 9 };
+    let doy = (153 * mp + 2) / 5 + day - 1;
+    let doe = yoe * 365 + yoe / 4 - yoe / 100 + doy;
+
+    era * 146_097 + doe - 719_468
+}
+
+/// Returns the number of days in `month` of `year`, in the proleptic Gregorian calendar.
+fn days_in_month(year: i64, month: i64) -> i64 {
+    match month {
+        2 if year % 4 == 0 && (year % 100 != 0 || year % 400 == 0) => 29,
+        2 => 28,
+        4 | 6 | 9 | 11 => 30,
+        _ => 31,
+    }
+}
+
+/// Parses a string made only of ASCII digits, without the sign `str::parse` would accept.
+fn parse_digits(s: &str) -> Option<i64> {
+    if s.is_empty() || !s.bytes().all(|b| b.is_ascii_digit()) {
+        return None;
+    }
+
+    s.parse().ok()
+}
+
+#[cfg(all(test, feature = "std"))]
+mod tests {
+    use super::*;
+
+    const TEST_EPOCH: [i64; 9] = [
+        0,
+        1,
+        -1,
+        123,
+        -123,
+        i64::MAX,
+        i64::MIN,
+        i64::MAX / 1000,
+        i64::MIN / 1000,
+    ];
+
+    const TEST_MS: [u16; 4] = [0, 1, 999, 123];
+    const TEST_US: [u32; 4] = [0, 1, 999_999, 123_123];
+    const TEST_NS: [u64; 4] = [0, 1, 999_999_999, 123_123_123];
+
+    #[test]
+    fn test_new() {
+        for epoch in TEST_EPOCH {
+            let new = Epoch::new(epoch);
+            assert_eq!(new.epoch, epoch);
+        }
+    }
+
+    #[test]
+    fn test_sub_second() {
+        let subseconds = [
+            SubSecond::Milli(123),
+            SubSecond::Micro(123_456),
+            SubSecond::Nano(123_456_789),
+            SubSecond::Nano(0),
+        ];
+
+        for subsecond in subseconds {
+            let epoch = Epoch::sub_second(subsecond.clone());
+            assert_eq!(epoch.epoch, 0);
+            assert_eq!(epoch.subsecond, subsecond);
+            assert!(epoch.is_sub_second_only());
+        }
+    }
+
+    #[test]
+    fn test_is_sub_second_only() {
+        assert!(!Epoch::sub_second(SubSecond::None).is_sub_second_only());
+        assert!(!Epoch::new(0).is_sub_second_only());
+        assert!(!Epoch::new(1).with_millis(500).is_sub_second_only());
+        assert!(!Epoch::new(-1).with_millis(500).is_sub_second_only());
+        assert!(Epoch::new(0).with_millis(500).is_sub_second_only());
+    }
+
+    #[test]
+    fn test_with_milli() {
+        for epoch in TEST_EPOCH {
+            for ms in TEST_MS {
+                let new = Epoch::new(epoch).with_millis(ms);
+                assert_eq!(new.subsecond, SubSecond::Milli(ms));
+                assert_eq!(new.epoch, epoch);
+            }
+        }
+    }
+
+    #[test]
+    fn test_with_micro() {
+        for epoch in TEST_EPOCH {
+            for ms in TEST_US {
+                let new = Epoch::new(epoch).with_micros(ms);
+                assert_eq!(new.subsecond, SubSecond::Micro(ms));
+                assert_eq!(new.epoch, epoch);
+            }
+        }
+    }
+
+    #[test]
+    fn test_with_nano() {
+        for epoch in TEST_EPOCH {
+            for ms in TEST_NS {
+                let new = Epoch::new(epoch).with_nanos(ms);
+                assert_eq!(new.subsecond, SubSecond::Nano(ms));
+                assert_eq!(new.epoch, epoch);
+            }
+        }
+    }
+
+    #[test]
+    #[should_panic(expected = "assertion failed: millis < 1000")]
+    #[allow(unused_must_use)]
+    fn test_with_ms_panic() {
+        Epoch::new(0).with_millis(1000);
+    }
+
+    #[test]
+    #[should_panic(expected = "assertion failed: micros < 1000000")]
+    #[allow(unused_must_use)]
     fn test_with_micros_panic() {
         Epoch::new(0).with_micros(1_000_000);
     }
 
     #[test]
-    #[should_panic(expected = "assertion failed: nanos < 1000000000")]
-    #[allow(unused_must_use)]
-    fn test_with_nanos_panic() {
-        Epoch::new(0).with_nanos(1_000_000_000);
+    #[should_panic(expected = "assertion failed: nanos < 1000000000")]
+    #[allow(unused_must_use)]
+    fn test_with_nanos_panic() {
+        Epoch::new(0).with_nanos(1_000_000_000);
+    }
+
+    #[test]
+    fn test_default() {
+        let default = Epoch::default();
+        assert_eq!(default.epoch, 0);
+        assert!(matches!(default.subsecond, SubSecond::None));
+    }
+
+    #[test]
+    fn test_display() {
+        let epochs = [
+            (0, "0"),
+            (1, "1"),
+            (-1, "-1"),
+            (123, "123"),
+            (-123, "-123"),
+            (i64::MAX, "9223372036854775807"),
+            (i64::MIN, "-9223372036854775808"),
+        ];
+
+        for (epoch, expected) in epochs {
+            let epoch = Epoch::new(epoch);
+            assert_eq!(epoch.to_string(), expected);
+        }
+    }
+
+    #[test]
+    fn test_display_with_millis() {
+        let epochs = [
+            (0, 0, "0.000"),
+            (0, 999, "0.999"),
+            (1, 123, "1.123"),
+            (-1, 123, "-1.123"),
+            (123, 999, "123.999"),
+            (-123, 999, "-123.999"),
+            (i64::MAX, 999, "9223372036854775807.999"),
+            (i64::MIN, 999, "-9223372036854775808.999"),
+        ];
+
+        for (epoch, ms, expected) in epochs {
+            let epoch = Epoch::new(epoch).with_millis(ms);
+            assert_eq!(epoch.to_string(), expected);
+        }
+    }
+
+    #[test]
+    fn test_display_with_micros() {
+        let epochs = [
+            (0, 0, "0.000000"),
+            (0, 999_999, "0.999999"),
+            (1, 123_123, "1.123123"),
+            (-1, 123_123, "-1.123123"),
+            (123, 999_999, "123.999999"),
+            (-123, 999_999, "-123.999999"),
+            (i64::MAX, 999_999, "9223372036854775807.999999"),
+            (i64::MIN, 999_999, "-9223372036854775808.999999"),
+        ];
+
+        for (epoch, ms, expected) in epochs {
+            let epoch = Epoch::new(epoch).with_micros(ms);
+            assert_eq!(epoch.to_string(), expected);
+        }
+    }
+
+    #[test]
+    fn test_display_with_nanos() {
+        let epochs = [
+            (0, 0, "0.000000000"),
+            (0, 999_999_999, "0.999999999"),
+            (1, 123_123_123, "1.123123123"),
+            (-1, 123_123_123, "-1.123123123"),
+            (123, 999_999_999, "123.999999999"),
+            (-123, 999_999_999, "-123.999999999"),
+            (i64::MAX, 999_999_999, "9223372036854775807.999999999"),
+            (i64::MIN, 999_999_999, "-9223372036854775808.999999999"),
+        ];
+
+        for (epoch, ms, expected) in epochs {
+            let epoch = Epoch::new(epoch).with_nanos(ms);
+            assert_eq!(epoch.to_string(), expected);
+        }
+    }
+
+    #[test]
+    fn test_to_decimal_string_round_trip() {
+        for epoch in TEST_EPOCH {
+            let mut epochs = vec![Epoch::new(epoch)];
+            epochs.extend(TEST_MS.map(|ms| Epoch::new(epoch).with_millis(ms)));
+            epochs.extend(TEST_US.map(|us| Epoch::new(epoch).with_micros(us)));
+            epochs.extend(TEST_NS.map(|ns| Epoch::new(epoch).with_nanos(ns)));
+
+            for epoch in epochs {
+                let decimal = epoch.to_decimal_string();
+                assert_eq!(Epoch::from_str(&decimal).unwrap(), epoch, "{decimal}");
+            }
+        }
+    }
+
+    #[test]
+    fn test_to_decimal_string_sign() {
+        let epoch = Epoch::new(-1).with_millis(250);
+        assert_eq!(epoch.to_decimal_string(), "-1.250");
+        assert_eq!(epoch.as_nanos(), -750_000_000);
+    }
+
+    #[test]
+    fn test_from_str_error() {
+        for input in ["", ".", "1.", ".123", "1.1234", "1.2.3", "a.123", "1,123"] {
+            assert!(Epoch::from_str(input).is_err(), "{input}");
+        }
+        assert!(matches!(
+            Epoch::from_str("1.123.456"),
+            Err(EpochError::AmbiguousDelimiter(_))
+        ));
+    }
+
+    #[test]
+    fn test_from_str_plus_sign() {
+        assert_eq!(
+            Epoch::from_str("+123.456").unwrap(),
+            Epoch::from_str("123.456").unwrap()
+        );
+        assert_eq!(
+            "+123.456".parse::<Epoch>().unwrap(),
+            Epoch::new(123).with_millis(456)
+        );
+        assert_eq!("+0".parse::<Epoch>().unwrap(), Epoch::new(0));
+
+        for input in [
+            "1+23", "123.+456", "123.-456", "++123", "+-123", "+", "+.123", "123+",
+        ] {
+            assert!(input.parse::<Epoch>().is_err(), "{input}");
+        }
+    }
+
+    #[test]
+    fn test_from_str_display_round_trip() {
+        let seconds = [0, 1, -1, 123, -123, i64::MAX, i64::MIN];
+        let mut epochs: Vec<Epoch> = seconds.map(Epoch::new).into();
+        for epoch in seconds {
+            epochs.extend([0, 123, 999].map(|ms| Epoch::new(epoch).with_millis(ms)));
+            epochs.extend([0, 123_123, 999_999].map(|us| Epoch::new(epoch).with_micros(us)));
+            epochs.extend([0, 123_123_123, 999_999_999].map(|ns| Epoch::new(epoch).with_nanos(ns)));
+        }
+
+        for epoch in epochs {
+            let parsed = Epoch::from_str(&epoch.to_string()).unwrap();
+            assert_eq!(parsed, epoch);
+            assert_eq!(parsed.epoch(), epoch.epoch());
+            assert_eq!(parsed.to_string(), epoch.to_string());
+        }
+
+        let parsed = Epoch::from_str("-1.123").unwrap();
+        assert_eq!(parsed.epoch(), -1);
+        assert!(matches!(parsed.subsecond(), SubSecond::Milli(123)));
+        assert!(matches!(
+            Epoch::from_str("1700000000").unwrap().subsecond(),
+            SubSecond::None
+        ));
+    }
+
+    #[test]
+    fn test_format_padded() {
+        let expected = [
+            (Epoch::new(0), "              0"),
+            (Epoch::new(-1).with_millis(999), "         -1.999"),
+            (Epoch::new(1_700_000_000), "     1700000000"),
+            (Epoch::new(-42).with_nanos(7), "  -42.000000007"),
+            (
+                Epoch::new(1_700_000_000).with_micros(5),
+                "1700000000.000005",
+            ),
+        ];
+
+        for (epoch, padded) in expected {
+            assert_eq!(epoch.format_padded(15, ' '), padded);
+        }
+
+        assert_eq!(
+            Epoch::new(7).with_millis(1).format_padded(8, '0'),
+            "0007.001"
+        );
+        assert_eq!(Epoch::new(7).format_padded(3, '·'), "··7");
+        assert_eq!(Epoch::new(123).format_padded(0, ' '), "123");
+    }
+
+    #[test]
+    fn test_display_with_delimiter() {
+        let epochs = [
+            (0, 0, '-', "0-000"),
+            (0, 0, ':', "0:000"),
+            (1, 0, ':', "1:000"),
+            (-1, 0, ':', "-1:000"),
+            (1, 999, ':', "1:999"),
+            (-1, 999, ':', "-1:999"),
+        ];
+
+        for (epoch, ms, delimiter, expected) in epochs {
+            let epoch = Epoch::new(epoch).with_millis(ms);
+            assert_eq!(epoch.format_with_delimiter(delimiter), expected);
+        }
+    }
+
+    #[test]
+    fn test_try_with_subsecond() {
+        let epoch = Epoch::new(-5);
+
+        let millis = epoch.clone().try_with_millis(999).unwrap();
+        assert!(matches!(millis.subsecond(), SubSecond::Milli(999)));
+        let micros = epoch.clone().try_with_micros(999_999).unwrap();
+        assert!(matches!(micros.subsecond(), SubSecond::Micro(999_999)));
+        let nanos = epoch.clone().try_with_nanos(0).unwrap();
+        assert!(matches!(nanos.subsecond(), SubSecond::Nano(0)));
+        assert_eq!(nanos.epoch(), -5);
+
+        assert!(matches!(
+            epoch.clone().try_with_millis(1000),
+            Err(EpochError::SubSecondOutOfRange {
+                value: 1000,
+                unit: "ms"
+            })
+        ));
+        assert!(matches!(
+            epoch.clone().try_with_micros(u32::MAX),
+            Err(EpochError::SubSecondOutOfRange {
+                value: 4_294_967_295,
+                unit: "us"
+            })
+        ));
+        assert!(matches!(
+            epoch.try_with_nanos(1_000_000_000),
+            Err(EpochError::SubSecondOutOfRange {
+                value: 1_000_000_000,
+                unit: "ns"
+            })
+        ));
+    }
+
+    #[test]
+    fn test_from_nanos_i64() {
+        let nanos = [
+            (0, 0, 0),
+            (1, 0, 1),
+            (1_500_000_000, 1, 500_000_000),
+            (-1, -1, 999_999_999),
+            (-1_000_000_000, -1, 0),
+            (-1_000_000_001, -2, 999_999_999),
+            (i64::MAX, 9_223_372_036, 854_775_807),
+            (i64::MIN, -9_223_372_037, 145_224_192),
+        ];
+
+        for (total, epoch, ns) in nanos {
+            let from = Epoch::from_nanos_i64(total);
+            assert_eq!(from, Epoch::new(epoch).with_nanos(ns));
+            assert_eq!(from.to_nanos_i64().unwrap(), total);
+        }
+    }
+
+    #[test]
+    fn test_from_nanos() {
+        let max = i128::from(i64::MAX) * 1_000_000_000 + 999_999_999;
+        let min = i128::from(i64::MIN) * 1_000_000_000;
+        let mut totals = vec![0, 1, -1, 999_999_999, -999_999_999, 1_000_000_000, max, min];
+        totals.extend((0..10_000).map(|_| rand::random_range(min..=max)));
+        totals.extend((0..10_000).map(|_| rand::random_range(-10i128.pow(12)..10i128.pow(12))));
+
+        for total in totals {
+            let epoch = Epoch::from_nanos(total);
+            assert!(matches!(epoch.subsecond(), SubSecond::Nano(ns) if *ns < 1_000_000_000));
+            assert_eq!(epoch.as_nanos(), total);
+        }
+
+        assert_eq!(
+            Epoch::from_nanos(-1),
+            Epoch::new(-1).with_nanos(999_999_999)
+        );
+        assert_eq!(Epoch::from_nanos(max + 1).as_nanos(), max);
+        assert_eq!(Epoch::from_nanos(i128::MIN).as_nanos(), min);
+    }
+
+    #[test]
+    fn test_to_nanos_i64() {
+        let epochs = [
+            (Epoch::new(0), 0),
+            (Epoch::new(-1).with_millis(999), -1_000_000),
+            (Epoch::new(-2).with_micros(1), -1_999_999_000),
+            (Epoch::new(1).with_micros(1), 1_000_001_000),
+        ];
+
+        for (epoch, expected) in epochs {
+            assert_eq!(epoch.to_nanos_i64().unwrap(), expected);
+        }
+    }
+
+    #[test]
+    fn test_to_nanos_i64_overflow() {
+        // 2262-04-11T23:47:16.854775807Z is the last representable instant.
+        let last = Epoch::new(9_223_372_036).with_nanos(854_775_807);
+        assert_eq!(last.to_nanos_i64().unwrap(), i64::MAX);
+        assert_eq!(
+            last.to_rfc3339_with_digits(9).unwrap(),
+            "2262-04-11T23:47:16.854775807Z"
+        );
+
+        let epochs = [
+            Epoch::new(9_223_372_036).with_nanos(854_775_808),
+            Epoch::new(9_223_372_037),
+            Epoch::new(-9_223_372_037).with_nanos(145_224_191),
+            Epoch::new(-9_223_372_038),
+            Epoch::new(i64::MAX),
+            Epoch::new(i64::MIN),
+        ];
+
+        for epoch in epochs {
+            assert!(matches!(
+                epoch.to_nanos_i64(),
+                Err(EpochError::OutOfRange(e)) if e == epoch.epoch()
+            ));
+        }
+    }
+
+    #[test]
+    fn test_from_rational() {
+        let fractions = [
+            (3, 2, Epoch::new(1).with_nanos(500_000_000)),
+            (0, 1, Epoch::new(0).with_nanos(0)),
+            (-3, 2, Epoch::new(-2).with_nanos(500_000_000)),
+            (3, -2, Epoch::new(-2).with_nanos(500_000_000)),
+            (-3, -2, Epoch::new(1).with_nanos(500_000_000)),
+            (1, 3, Epoch::new(0).with_nanos(333_333_333)),
+            (2, 3, Epoch::new(0).with_nanos(666_666_667)),
+            (1, 2_000_000_000, Epoch::new(0).with_nanos(1)),
+            (-1, 2_000_000_000, Epoch::new(0).with_nanos(0)),
+            (2_999_999_999, 3_000_000_000, Epoch::new(1).with_nanos(0)),
+            (
+                100_000_000_000_000_000_000_000_000_000,
+                150_000_000_000_000_000_000_000_000_000_000_000_000,
+                Epoch::new(0).with_nanos(1),
+            ),
+            (i128::from(i64::MAX), 1, Epoch::new(i64::MAX).with_nanos(0)),
+        ];
+
+        for (numerator, denominator, expected) in fractions {
+            assert_eq!(
+                Epoch::from_rational(numerator, denominator).unwrap(),
+                expected
+            );
+        }
+    }
+
+    #[test]
+    fn test_from_rational_sample_rate() {
+        let rate = 48_000;
+        let samples = [
+            (0, Epoch::new(0).with_nanos(0)),
+            (1, Epoch::new(0).with_nanos(20_833)),
+            (24_000, Epoch::new(0).with_nanos(500_000_000)),
+            (48_000, Epoch::new(1).with_nanos(0)),
+            (48_001, Epoch::new(1).with_nanos(20_833)),
+            (
+                1_700_000_000 * 48_000 + 36_000,
+                Epoch::new(1_700_000_000).with_nanos(750_000_000),
+            ),
+        ];
+
+        for (sample, expected) in samples {
+            assert_eq!(Epoch::from_rational(sample, rate).unwrap(), expected);
+        }
+    }
+
+    #[test]
+    fn test_from_rational_error() {
+        assert!(matches!(
+            Epoch::from_rational(1, 0),
+            Err(EpochError::ZeroDenominator)
+        ));
+
+        let overflows = [
+            (i128::from(i64::MAX) + 1, 1),
+            (i128::from(i64::MIN) - 1, 1),
+            (1, i128::MIN),
+            (i128::MAX - 1, i128::MAX),
+        ];
+        for (numerator, denominator) in overflows {
+            assert!(matches!(
+                Epoch::from_rational(numerator, denominator),
+                Err(EpochError::Overflow)
+            ));
+        }
+    }
+
+    #[test]
+    fn test_saturating_from_f64() {
+        let seconds = [
+            (0.0, Epoch::new(0).with_nanos(0)),
+            (1.5, Epoch::new(1).with_nanos(500_000_000)),
+            (-1.5, Epoch::new(-2).with_nanos(500_000_000)),
+            (-0.25, Epoch::new(-1).with_nanos(750_000_000)),
+            (0.999_999_999_9, Epoch::new(1).with_nanos(0)),
+            (f64::NAN, Epoch::new(0).with_nanos(0)),
+            (f64::INFINITY, Epoch::new(i64::MAX).with_nanos(999_999_999)),
+            (f64::MAX, Epoch::new(i64::MAX).with_nanos(999_999_999)),
+            (f64::NEG_INFINITY, Epoch::new(i64::MIN).with_nanos(0)),
+            (f64::MIN, Epoch::new(i64::MIN).with_nanos(0)),
+        ];
+
+        for (secs, expected) in seconds {
+            assert_eq!(Epoch::saturating_from_f64(secs), expected);
+        }
+    }
+
+    #[test]
+    fn test_from_ntp() {
+        let timestamps = [
+            (0, Epoch::new(-NTP_UNIX_OFFSET).with_nanos(0)),
+            (
+                NTP_UNIX_OFFSET.unsigned_abs() << 32,
+                Epoch::new(0).with_nanos(0),
+            ),
+            (
+                0xe8fe_6f80_0000_0000,
+                Epoch::new(1_700_000_000).with_nanos(0),
+            ),
+            (
+                0xe8fe_6f80_8000_0000,
+                Epoch::new(1_700_000_000).with_nanos(500_000_000),
+            ),
+            (
+                0xe8fe_6f80_4000_0000,
+                Epoch::new(1_700_000_000).with_nanos(250_000_000),
+            ),
+            (
+                0xe8fe_6f80_0000_0001,
+                Epoch::new(1_700_000_000).with_nanos(0),
+            ),
+            (
+                0xe8fe_6f80_0000_0005,
+                Epoch::new(1_700_000_000).with_nanos(1),
+            ),
+            (
+                0xe8fe_6f80_ffff_ffff,
+                Epoch::new(1_700_000_001).with_nanos(0),
+            ),
+            (u64::MAX, Epoch::new(2_085_978_496).with_nanos(0)),
+        ];
+
+        for (ntp, expected) in timestamps {
+            assert_eq!(Epoch::from_ntp(ntp), expected);
+        }
+    }
+
+    #[test]
+    fn test_to_ntp() {
+        let epochs = [
+            (Epoch::new(-NTP_UNIX_OFFSET), 0),
+            (Epoch::new(0), NTP_UNIX_OFFSET.unsigned_abs() << 32),
+            (Epoch::new(1_700_000_000), 0xe8fe_6f80_0000_0000),
+            (
+                Epoch::new(1_700_000_000).with_millis(500),
+                0xe8fe_6f80_8000_0000,
+            ),
+            (
+                Epoch::new(1_700_000_000).with_micros(250_000),
+                0xe8fe_6f80_4000_0000,
+            ),
+            (
+                Epoch::new(1_700_000_000).with_nanos(999_999_999),
+                0xe8fe_6f80_ffff_fffc,
+            ),
+            (
+                Epoch::new(2_085_978_495).with_nanos(999_999_999),
+                0xffff_ffff_ffff_fffc,
+            ),
+        ];
+
+        for (epoch, expected) in epochs {
+            assert_eq!(epoch.to_ntp().unwrap(), expected);
+        }
+    }
+
+    #[test]
+    fn test_to_ntp_out_of_range() {
+        for epoch in [-NTP_UNIX_OFFSET - 1, 2_085_978_496, i64::MIN, i64::MAX] {
+            assert!(matches!(
+                Epoch::new(epoch).to_ntp(),
+                Err(EpochError::OutOfRange(e)) if e == epoch
+            ));
+        }
+    }
+
+    #[test]
+    fn test_from_excel_serial() {
+        let serials = [
+            (25_569.0, "1970-01-01T00:00:00.000000Z"),
+            (25_569.5, "1970-01-01T12:00:00.000000Z"),
+            (25_568.75, "1969-12-31T18:00:00.000000Z"),
+            (45_244.927_083_333_336, "2023-11-14T22:15:00.000000Z"),
+            (36_526.000_011_574_07, "2000-01-01T00:00:01.000000Z"),
+            (61.0, "1900-03-01T00:00:00.000000Z"),
+            (59.5, "1900-02-28T12:00:00.000000Z"),
+            (1.0, "1900-01-01T00:00:00.000000Z"),
+        ];
+
+        for (serial, rfc3339) in serials {
+            let epoch = Epoch::from_excel_serial(serial).unwrap();
+            assert_eq!(
+                epoch.to_rfc3339_with_digits(6).unwrap(),
+                rfc3339,
+                "{serial}"
+            );
+            assert!(matches!(epoch.subsecond(), SubSecond::Micro(_)));
+        }
+    }
+
+    #[test]
+    fn test_from_excel_serial_error() {
+        for serial in [60.0, 60.5, f64::NAN, f64::INFINITY, f64::NEG_INFINITY] {
+            assert!(matches!(
+                Epoch::from_excel_serial(serial),
+                Err(EpochError::InvalidSerial(_))
+            ));
+        }
+        assert!(matches!(
+            Epoch::from_excel_serial(1e300),
+            Err(EpochError::Overflow)
+        ));
+    }
+
+    #[test]
+    fn test_to_excel_serial() {
+        let epochs = [
+            (Epoch::new(0), 25_569.0),
+            (Epoch::new(-21_600), 25_568.75),
+            (Epoch::new(-2_203_891_200), 61.0),
+            (Epoch::new(-2_203_934_400), 59.5),
+            (Epoch::new(-2_208_988_800), 1.0),
+        ];
+
+        for (epoch, serial) in epochs {
+            assert!((epoch.to_excel_serial() - serial).abs() < 1e-9, "{epoch}");
+            assert!(Epoch::from_excel_serial(serial).unwrap().value_eq(&epoch));
+        }
+
+        let epoch = Epoch::new(1_700_000_000).with_micros(123_456);
+        let round_trip = Epoch::from_excel_serial(epoch.to_excel_serial()).unwrap();
+        assert!((round_trip.as_nanos() - epoch.as_nanos()).abs() <= 1_000);
+    }
+
+    #[test]
+    fn test_ntp_round_trip() {
+        // A 2^-32 unit is finer than a nanosecond, so nanoseconds survive the round trip exactly.
+        for ns in [
+            0,
+            1,
+            232,
+            233,
+            499_999_999,
+            500_000_000,
+            123_456_789,
+            999_999_999,
+        ] {
+            let epoch = Epoch::new(1_700_000_000).with_nanos(ns);
+            assert_eq!(Epoch::from_ntp(epoch.to_ntp().unwrap()), epoch);
+        }
+
+        // A nanosecond spans ~4.3 units, so NTP fractions come back within half a nanosecond.
+        for fraction in [0, 1, 2, 0x8000_0000, 0x1234_5678, 0xffff_fffe] {
+            let ntp = 0xe8fe_6f80_0000_0000 | fraction;
+            let round_trip = Epoch::from_ntp(ntp).to_ntp().unwrap();
+            assert!(round_trip.abs_diff(ntp) <= 3);
+        }
+    }
+
+    #[test]
+    fn test_parse_with_unit() {
+        let inputs = [
+            ("1700000000s", Epoch::new(1_700_000_000)),
+            (
+                "1700000000123ms",
+                Epoch::new(1_700_000_000).with_millis(123),
+            ),
+            (
+                "1700000000000001us",
+                Epoch::new(1_700_000_000).with_micros(1),
+            ),
+            (
+                "1700000000000001µs",
+                Epoch::new(1_700_000_000).with_micros(1),
+            ),
+            (
+                "1700000000123456789ns",
+                Epoch::new(1_700_000_000).with_nanos(123_456_789),
+            ),
+            ("0ms", Epoch::new(0).with_millis(0)),
+            ("-1s", Epoch::new(-1)),
+            ("-1ms", Epoch::new(-1).with_millis(999)),
+            ("-1500us", Epoch::new(-1).with_micros(998_500)),
+            ("-1ns", Epoch::new(-1).with_nanos(999_999_999)),
+        ];
+
+        for (input, expected) in inputs {
+            assert_eq!(Epoch::parse_with_unit(input).unwrap(), expected);
+        }
+    }
+
+    #[test]
+    fn test_parse_with_unit_error() {
+        let units = [
+            ("1700000000", ""),
+            ("1700000000m", "m"),
+            ("1700000000min", "min"),
+            ("1700000000sec", "sec"),
+            ("1700000000MS", "MS"),
+        ];
+
+        for (input, unit) in units {
+            assert!(matches!(
+                Epoch::parse_with_unit(input),
+                Err(EpochError::InvalidUnit(u)) if u == unit
+            ));
+        }
+
+        for input in [
+            "ms",
+            "1700000000 s",
+            "1.5s",
+            "99999999999999999999s",
+            "--1ms",
+        ] {
+            assert!(Epoch::parse_with_unit(input).is_err());
+        }
+    }
+
+    #[test]
+    fn test_parse_with_delimiter() {
+        let epochs = [
+            Epoch::new(0),
+            Epoch::new(1_700_000_000).with_millis(250),
+            Epoch::new(-1).with_millis(999),
+            Epoch::new(-1_700_000_000).with_micros(1),
+            Epoch::new(i64::MIN).with_nanos(999_999_999),
+            Epoch::new(i64::MAX).with_nanos(0),
+        ];
+
+        for delimiter in [':', '-', '+', '_', 'é'] {
+            for epoch in &epochs {
+                let formatted = epoch.format_with_delimiter(delimiter);
+                let parsed = Epoch::parse_with_delimiter(&formatted, delimiter).unwrap();
+                assert_eq!(parsed, *epoch, "{formatted}");
+            }
+        }
+
+        assert_eq!(
+            Epoch::parse_with_delimiter("-1-999", '-').unwrap(),
+            Epoch::new(-1).with_millis(999)
+        );
+        assert_eq!(
+            Epoch::parse_with_delimiter("-1", '-').unwrap(),
+            Epoch::new(-1)
+        );
+
+        for delimiter in ['.', ':', '-'] {
+            assert_eq!(
+                Epoch::parse_with_delimiter(&format!("+123{delimiter}456"), delimiter).unwrap(),
+                Epoch::new(123).with_millis(456)
+            );
+        }
+        for (input, delimiter) in [("1+23", '.'), ("123.+456", '.'), ("1+23:456", ':')] {
+            assert!(
+                Epoch::parse_with_delimiter(input, delimiter).is_err(),
+                "{input}"
+            );
+        }
+    }
+
+    #[test]
+    fn test_parse_with_delimiter_ambiguous() {
+        for (input, delimiter) in [("1:700:500", ':'), ("-1-2-999", '-'), ("1--999", '-')] {
+            assert!(matches!(
+                Epoch::parse_with_delimiter(input, delimiter),
+                Err(EpochError::AmbiguousDelimiter(s)) if s == input
+            ));
+        }
+
+        for (input, delimiter) in [("1:", ':'), (":500", ':'), ("--1-999", '+'), ("1-", '-')] {
+            assert!(
+                Epoch::parse_with_delimiter(input, delimiter).is_err(),
+                "{input}"
+            );
+        }
+    }
+
+    #[test]
+    fn test_parse_decimal_comma() {
+        let inputs = [
+            ("1700,500", Epoch::new(1700).with_millis(500)),
+            ("1700", Epoch::new(1700)),
+            ("-1700,500", Epoch::new(-1700).with_millis(500)),
+            ("0,000001", Epoch::new(0).with_micros(1)),
+            ("1,123456789", Epoch::new(1).with_nanos(123_456_789)),
+        ];
+
+        for (input, expected) in inputs {
+            assert_eq!(Epoch::parse_decimal_comma(input).unwrap(), expected);
+            assert_eq!(expected.format_with_delimiter(','), input);
+        }
+    }
+
+    #[test]
+    fn test_parse_decimal_comma_plus_sign() {
+        assert_eq!(
+            Epoch::parse_decimal_comma("+123,456").unwrap(),
+            Epoch::parse_decimal_comma("123,456").unwrap()
+        );
+        assert_eq!(
+            Epoch::parse_decimal_comma("+1700000000").unwrap(),
+            Epoch::new(1_700_000_000)
+        );
+
+        for input in [
+            "1+23",
+            "1+23,456",
+            "++123,456",
+            "+-123,456",
+            "123,+56",
+            "123+,456",
+            "+",
+        ] {
+            assert!(Epoch::parse_decimal_comma(input).is_err(), "{input}");
+        }
+    }
+
+    #[test]
+    fn test_parse_decimal_comma_ambiguous() {
+        for input in ["1,700,500", "1,700,500,000", ",,"] {
+            assert!(matches!(
+                Epoch::parse_decimal_comma(input),
+                Err(EpochError::AmbiguousDelimiter(s)) if s == input
+            ));
+        }
+    }
+
+    #[test]
+    fn test_parse_decimal_comma_error() {
+        for input in [
+            "",
+            ",",
+            "1700,",
+            ",500",
+            "1700,5",
+            "1700.500",
+            "1.700,500",
+            "a,500",
+        ] {
+            assert!(Epoch::parse_decimal_comma(input).is_err());
+        }
+    }
+
+    const PAD_RIGHT: ParseOptions = ParseOptions {
+        min_frac_digits: 1,
+        max_frac_digits: 9,
+        pad_policy: PadPolicy::PadRight,
+    };
+
+    #[test]
+    fn test_parse_with_options_pad_right() {
+        let inputs = [
+            ("1.5", Epoch::new(1).with_millis(500)),
+            ("1.05", Epoch::new(1).with_millis(50)),
+            ("1.123", Epoch::new(1).with_millis(123)),
+            ("1.1234", Epoch::new(1).with_micros(123_400)),
+            ("1.00001", Epoch::new(1).with_micros(10)),
+            ("-1.1234567", Epoch::new(-1).with_nanos(123_456_700)),
+            ("1.12345678", Epoch::new(1).with_nanos(123_456_780)),
+            ("1.123456789", Epoch::new(1).with_nanos(123_456_789)),
+            ("+1700000000", Epoch::new(1_700_000_000)),
+        ];
+
+        for (input, expected) in inputs {
+            assert_eq!(
+                Epoch::parse_with_options(input, PAD_RIGHT).unwrap(),
+                expected
+            );
+        }
+    }
+
+    #[test]
+    fn test_parse_with_options_reject() {
+        let options = ParseOptions {
+            min_frac_digits: 1,
+            ..ParseOptions::default()
+        };
+
+        for (input, digits) in [("1.5", 1), ("1.05", 2), ("1.1234", 4), ("1.1234567", 7)] {
+            assert!(matches!(
+                Epoch::parse_with_options(input, options),
+                Err(EpochError::InvalidFractionDigits(d)) if d == digits
+            ));
+        }
+        assert_eq!(
+            Epoch::parse_with_options("1.123456", options).unwrap(),
+            Epoch::new(1).with_micros(123_456)
+        );
+    }
+
+    #[test]
+    fn test_parse_with_options_bounds() {
+        let options = ParseOptions {
+            min_frac_digits: 2,
+            max_frac_digits: 4,
+            pad_policy: PadPolicy::PadRight,
+        };
+
+        assert!(Epoch::parse_with_options("1.12", options).is_ok());
+        assert!(Epoch::parse_with_options("1.1234", options).is_ok());
+        for (input, digits) in [("1.1", 1), ("1.12345", 5), ("1.", 0)] {
+            assert!(matches!(
+                Epoch::parse_with_options(input, options),
+                Err(EpochError::InvalidFractionDigits(d)) if d == digits
+            ));
+        }
+
+        let unbounded = ParseOptions {
+            max_frac_digits: usize::MAX,
+            ..PAD_RIGHT
+        };
+        assert!(Epoch::parse_with_options("1.1234567890", unbounded).is_err());
+    }
+
+    #[test]
+    fn test_parse_with_options_default() {
+        for epoch in [
+            Epoch::new(-1),
+            Epoch::new(-1).with_millis(5),
+            Epoch::new(1).with_nanos(7),
+        ] {
+            let parsed = Epoch::parse_with_options(&epoch.format(), ParseOptions::default());
+            assert_eq!(parsed.unwrap(), epoch);
+        }
+
+        for input in ["1.2.3", "1.+12", "a.123", "1.12a"] {
+            assert!(
+                Epoch::parse_with_options(input, PAD_RIGHT).is_err(),
+                "{input}"
+            );
+        }
+    }
+
+    #[test]
+    fn test_value_eq() {
+        for epoch in TEST_EPOCH {
+            let millis = Epoch::new(epoch).with_millis(500);
+            let micros = Epoch::new(epoch).with_micros(500_000);
+            let nanos = Epoch::new(epoch).with_nanos(500_000_000);
+
+            assert!(millis.value_eq(&micros));
+            assert!(micros.value_eq(&nanos));
+            assert!(nanos.value_eq(&millis));
+            assert_eq!(millis, micros);
+            assert_eq!(micros, nanos);
+            assert_eq!(nanos, millis);
+        }
+    }
+
+    #[test]
+    fn test_value_eq_none() {
+        assert!(Epoch::new(1).value_eq(&Epoch::new(1).with_millis(0)));
+        assert!(Epoch::new(1).value_eq(&Epoch::new(1).with_nanos(0)));
+        assert!(!Epoch::new(1).value_eq(&Epoch::new(1).with_nanos(1)));
+        assert!(!Epoch::new(1).value_eq(&Epoch::new(-1)));
+    }
+
+    #[test]
+    fn test_subsecond_eq_across_precisions() {
+        assert_eq!(SubSecond::Milli(1), SubSecond::Micro(1000));
+        assert_eq!(SubSecond::Micro(1000), SubSecond::Nano(1_000_000));
+        assert_eq!(SubSecond::None, SubSecond::Milli(0));
+        assert_ne!(SubSecond::Milli(1), SubSecond::Nano(1));
+        assert!(SubSecond::Nano(1) < SubSecond::Milli(1));
+        assert!(SubSecond::Micro(999) < SubSecond::Milli(1));
+    }
+
+    #[test]
+    fn test_sort_mixed_precisions() {
+        let mut epochs = [
+            Epoch::new(1).with_millis(1),
+            Epoch::new(0).with_nanos(999_999_999),
+            Epoch::new(1).with_nanos(2),
+            Epoch::new(-1).with_millis(500),
+            Epoch::new(1),
+            Epoch::new(1).with_micros(999),
+            Epoch::new(-1).with_micros(499_999),
+        ];
+        epochs.sort();
+
+        assert!(
+            epochs
+                .windows(2)
+                .all(|pair| pair[0].as_nanos() <= pair[1].as_nanos())
+        );
+        assert_eq!(epochs[0], Epoch::new(-1).with_micros(499_999));
+        assert_eq!(epochs[6], Epoch::new(1).with_millis(1));
+    }
+
+    #[test]
+    fn test_total_cmp() {
+        use std::cmp::Ordering;
+
+        let comparisons = [
+            (Epoch::new(0), Epoch::new(0).with_nanos(0), Ordering::Equal),
+            (
+                Epoch::new(1).with_millis(1),
+                Epoch::new(1).with_micros(1000),
+                Ordering::Equal,
+            ),
+            (
+                Epoch::new(1).with_millis(1),
+                Epoch::new(1).with_nanos(999_999),
+                Ordering::Greater,
+            ),
+            (
+                Epoch::new(-1).with_nanos(999_999_999),
+                Epoch::new(0),
+                Ordering::Less,
+            ),
+            (Epoch::new(i64::MIN), Epoch::new(i64::MAX), Ordering::Less),
+        ];
+
+        for (a, b, expected) in comparisons {
+            assert_eq!(a.total_cmp(&b), expected);
+            assert_eq!(b.total_cmp(&a), expected.reverse());
+        }
+    }
+
+    #[test]
+    fn test_total_cmp_sort() {
+        let mut epochs = [
+            Epoch::new(1).with_nanos(1),
+            Epoch::new(-1).with_millis(500),
+            Epoch::new(0).with_micros(999_999),
+            Epoch::new(-2).with_nanos(999_999_999),
+            Epoch::new(1),
+            Epoch::new(-1).with_micros(499_999),
+            Epoch::new(0).with_millis(1),
+            Epoch::new(1).with_micros(1),
+        ];
+        epochs.sort_by(Epoch::total_cmp);
+
+        let nanos: Vec<i128> = epochs.iter().map(Epoch::as_nanos).collect();
+        assert!(nanos.is_sorted());
+        assert_eq!(
+            nanos,
+            [
+                -1_000_000_001,
+                -500_001_000,
+                -500_000_000,
+                1_000_000,
+                999_999_000,
+                1_000_000_000,
+                1_000_000_001,
+                1_000_001_000
+            ]
+        );
+    }
+
+    #[test]
+    fn test_min_max_of() {
+        let epochs = [
+            Epoch::new(1).with_nanos(999_999_999),
+            Epoch::new(1).with_millis(1),
+            Epoch::new(-1).with_nanos(1),
+            Epoch::new(-1).with_millis(999),
+            Epoch::new(0),
+            Epoch::new(1).with_micros(2),
+        ];
+
+        assert_eq!(Epoch::min_of(&epochs), Some(&epochs[2]));
+        assert_eq!(Epoch::max_of(&epochs), Some(&epochs[0]));
+    }
+
+    #[test]
+    fn test_min_max_of_ignores_variant_order() {
+        let epochs = [Epoch::new(0).with_nanos(1), Epoch::new(0).with_millis(500)];
+
+        assert_eq!(Epoch::min_of(&epochs), Some(&epochs[0]));
+        assert_eq!(Epoch::max_of(&epochs), Some(&epochs[1]));
+    }
+
+    #[test]
+    fn test_min_max_of_empty() {
+        assert_eq!(Epoch::min_of(&[]), None);
+        assert_eq!(Epoch::max_of(&[]), None);
+    }
+
+    #[test]
+    #[cfg(feature = "leap-seconds")]
+    fn test_to_rfc3339_with_leap() {
+        let leap_table = [Epoch::new(1_435_708_800), Epoch::new(1_483_228_800)];
+        let expected = [
+            (Epoch::new(1_483_228_799), "2016-12-31T23:59:59Z"),
+            (Epoch::new(1_483_228_800), "2016-12-31T23:59:60Z"),
+            (
+                Epoch::new(1_483_228_800).with_nanos(999_999_999),
+                "2016-12-31T23:59:60.999999999Z",
+            ),
+            (Epoch::new(1_483_228_801), "2017-01-01T00:00:01Z"),
+            (
+                Epoch::new(1_435_708_800).with_millis(250),
+                "2015-06-30T23:59:60.250Z",
+            ),
+            (Epoch::new(1_700_000_000), "2023-11-14T22:13:20Z"),
+        ];
+
+        for (epoch, rfc3339) in expected {
+            assert_eq!(epoch.to_rfc3339_with_leap(&leap_table).unwrap(), rfc3339);
+        }
+    }
+
+    #[test]
+    #[cfg(feature = "leap-seconds")]
+    fn test_to_rfc3339_with_leap_without_table() {
+        let epoch = Epoch::new(1_483_228_800).with_micros(1);
+        assert_eq!(
+            epoch.to_rfc3339_with_leap(&[]).unwrap(),
+            "2017-01-01T00:00:00.000001Z"
+        );
+
+        let off_minute = [Epoch::new(1_483_228_830)];
+        let epoch = Epoch::new(1_483_228_830);
+        assert_eq!(
+            epoch.to_rfc3339_with_leap(&off_minute).unwrap(),
+            "2017-01-01T00:00:30Z"
+        );
+    }
+
+    #[test]
+    fn test_add_epoch() {
+        let inputs = [
+            (Epoch::new(1), Epoch::new(2), Epoch::new(3)),
+            (
+                Epoch::new(1).with_millis(700),
+                Epoch::new(2).with_millis(400),
+                Epoch::new(4).with_millis(100),
+            ),
+            (
+                Epoch::new(1).with_millis(1),
+                Epoch::new(0).with_nanos(1),
+                Epoch::new(1).with_nanos(1_000_001),
+            ),
+            (
+                Epoch::new(0).with_micros(999_999),
+                Epoch::new(0).with_millis(1),
+                Epoch::new(1).with_micros(999),
+            ),
+            (
+                Epoch::new(5),
+                Epoch::new(0).with_millis(250),
+                Epoch::new(5).with_millis(250),
+            ),
+            (
+                Epoch::new(-1).with_millis(500),
+                Epoch::new(0).with_millis(500),
+                Epoch::new(0).with_millis(0),
+            ),
+        ];
+
+        for (lhs, rhs, expected) in inputs {
+            assert_eq!(lhs.add_epoch(&rhs), Some(expected.clone()));
+            assert_eq!(rhs.add_epoch(&lhs), Some(expected));
+        }
+    }
+
+    #[test]
+    fn test_add_epoch_overflow() {
+        assert_eq!(Epoch::new(i64::MAX).add_epoch(&Epoch::new(1)), None);
+        assert_eq!(Epoch::new(i64::MIN).add_epoch(&Epoch::new(-1)), None);
+
+        let almost = Epoch::new(i64::MAX).with_millis(999);
+        assert_eq!(almost.add_epoch(&Epoch::new(0).with_millis(1)), None);
+        assert_eq!(
+            almost.add_epoch(&Epoch::new(-1).with_millis(1)),
+            Some(Epoch::new(i64::MAX).with_millis(0))
+        );
+    }
+
+    #[test]
+    fn test_floor_to_anchored() {
+        let anchor = Epoch::new(1_700_000_000).with_millis(250);
+        let hour = Duration::from_secs(3600);
+        let expected = [
+            (Epoch::new(1_700_000_000).with_millis(250), 1_700_000_000),
+            (Epoch::new(1_700_003_600).with_millis(249), 1_700_000_000),
+            (Epoch::new(1_700_003_600).with_millis(250), 1_700_003_600),
+            (Epoch::new(1_700_010_000), 1_700_007_200),
+            (Epoch::new(1_700_000_000).with_millis(249), 1_699_996_400),
+            (Epoch::new(1_699_996_400).with_millis(250), 1_699_996_400),
+            (Epoch::new(1_699_996_400), 1_699_992_800),
+        ];
+
+        for (epoch, seconds) in expected {
+            let floored = epoch.floor_to_anchored(&anchor, hour);
+            assert_eq!(
+                floored,
+                Epoch::new(seconds).with_nanos(250_000_000),
+                "{epoch}"
+            );
+        }
+    }
+
+    #[test]
+    fn test_floor_to_anchored_extremes() {
+        let floored = Epoch::new(i64::MIN).floor_to_anchored(&Epoch::new(1), Duration::MAX);
+        assert_eq!(floored, Epoch::new(i64::MIN).with_nanos(0));
+
+        let floored = Epoch::new(i64::MAX).floor_to_anchored(&Epoch::new(i64::MIN), Duration::MAX);
+        assert_eq!(floored, Epoch::new(i64::MIN).with_nanos(0));
+    }
+
+    #[test]
+    #[should_panic(expected = "step should be greater than zero")]
+    fn test_floor_to_anchored_zero_step() {
+        let _ = Epoch::new(0).floor_to_anchored(&Epoch::new(0), Duration::ZERO);
+    }
+
+    #[test]
+    fn test_until_next_multiple() {
+        let minute = Duration::from_secs(60);
+        let expected = [
+            (Epoch::new(1_700_000_040), minute),
+            (
+                Epoch::new(1_700_000_040).with_nanos(1),
+                Duration::from_nanos(59_999_999_999),
+            ),
+            (Epoch::new(1_700_000_041), Duration::from_secs(59)),
+            (
+                Epoch::new(1_700_000_070).with_millis(250),
+                Duration::from_millis(29_750),
+            ),
+            (
+                Epoch::new(1_700_000_099).with_micros(999_999),
+                Duration::from_micros(1),
+            ),
+            (Epoch::new(1_700_000_100).with_millis(0), minute),
+            (Epoch::new(-1), Duration::from_secs(1)),
+        ];
+
+        for (epoch, until) in expected {
+            assert_eq!(epoch.until_next_multiple(minute), until, "{epoch}");
+        }
+    }
+
+    #[test]
+    #[should_panic(expected = "step should be greater than zero")]
+    fn test_until_next_multiple_zero_step() {
+        let _ = Epoch::new(0).until_next_multiple(Duration::ZERO);
+    }
+
+    #[test]
+    fn test_from_total_millis() {
+        let expected = [
+            (1_500, 1, 500),
+            (-1_500, -2, 500),
+            (-1, -1, 999),
+            (0, 0, 0),
+            (-1_000, -1, 0),
+            (i64::MAX, i64::MAX / 1_000, 807),
+            (i64::MIN, i64::MIN / 1_000 - 1, 192),
+        ];
+
+        for (millis, seconds, sub) in expected {
+            let epoch = Epoch::from_total_millis(millis);
+            assert_eq!(epoch.epoch(), seconds, "{millis}");
+            assert!(
+                matches!(epoch.subsecond(), SubSecond::Milli(ms) if *ms == sub),
+                "{millis}"
+            );
+            assert_eq!(epoch.as_nanos(), i128::from(millis) * 1_000_000);
+        }
+    }
+
+    #[test]
+    fn test_from_total_micros() {
+        let expected = [
+            (1_500_000, 1, 500_000),
+            (-1_500_000, -2, 500_000),
+            (-1, -1, 999_999),
+            (0, 0, 0),
+        ];
+
+        for (micros, seconds, sub) in expected {
+            let epoch = Epoch::from_total_micros(micros);
+            assert_eq!(epoch.epoch(), seconds, "{micros}");
+            assert!(
+                matches!(epoch.subsecond(), SubSecond::Micro(us) if *us == sub),
+                "{micros}"
+            );
+        }
+
+        for _ in 0..1000 {
+            let micros = rand::random::<i64>();
+            assert_eq!(
+                Epoch::from_total_micros(micros).as_nanos(),
+                i128::from(micros) * 1_000
+            );
+        }
+    }
+
+    #[test]
+    fn test_from_secs_f64() {
+        let expected = [
+            (1.5, 1, 500_000_000),
+            (-1.5, -2, 500_000_000),
+            (-0.25, -1, 750_000_000),
+            (0.000_000_001, 0, 1),
+        ];
+
+        for (secs, seconds, nanos) in expected {
+            let epoch = Epoch::from_secs_f64(secs);
+            assert_eq!(epoch.epoch(), seconds, "{secs}");
+            assert!(
+                matches!(epoch.subsecond(), SubSecond::Nano(ns) if *ns == nanos),
+                "{secs}"
+            );
+        }
+
+        let epoch = Epoch::from_secs_f64(1_700_000_000.123_456_7);
+        assert_eq!(epoch.epoch(), 1_700_000_000);
+        assert!(epoch.subsecond().as_nanos().abs_diff(123_456_700) < 1_000);
+    }
+
+    #[test]
+    fn test_subsecond_as_nanos() {
+        assert_eq!(SubSecond::None.as_nanos(), 0);
+        assert_eq!(SubSecond::Milli(0).as_nanos(), 0);
+        assert_eq!(SubSecond::Milli(999).as_nanos(), 999_000_000);
+        assert_eq!(SubSecond::Milli(u16::MAX).as_nanos(), 65_535_000_000);
+        assert_eq!(SubSecond::Micro(999_999).as_nanos(), 999_999_000);
+        assert_eq!(SubSecond::Micro(u32::MAX).as_nanos(), 4_294_967_295_000);
+        assert_eq!(SubSecond::Nano(999_999_999).as_nanos(), 999_999_999);
+        assert_eq!(SubSecond::Nano(u64::MAX).as_nanos(), u64::MAX);
+    }
+
+    #[test]
+    fn test_subsecond_precision() {
+        assert_eq!(SubSecond::None.precision(), None);
+        assert_eq!(
+            SubSecond::Milli(u16::MAX).precision(),
+            Some(Precision::Milli)
+        );
+        assert_eq!(
+            SubSecond::Micro(u32::MAX).precision(),
+            Some(Precision::Micro)
+        );
+        assert_eq!(SubSecond::Nano(u64::MAX).precision(), Some(Precision::Nano));
+        assert_eq!(SubSecond::Nano(0).precision(), Some(Precision::Nano));
+    }
+
+    #[test]
+    fn test_checked_add() {
+        let second = Duration::from_secs(1);
+        assert_eq!(Epoch::new(i64::MAX).checked_add(second), None);
+        assert_eq!(
+            Epoch::new(i64::MAX - 1).checked_add(second),
+            Some(Epoch::new(i64::MAX))
+        );
+        assert_eq!(
+            Epoch::new(i64::MAX)
+                .with_nanos(999_999_998)
+                .checked_add(Duration::from_nanos(1)),
+            Some(Epoch::new(i64::MAX).with_nanos(999_999_999))
+        );
+        assert_eq!(
+            Epoch::new(i64::MAX)
+                .with_millis(999)
+                .checked_add(Duration::from_millis(1)),
+            None
+        );
+        assert_eq!(
+            Epoch::new(i64::MIN).checked_add(Duration::MAX),
+            Some(Epoch::new(i64::MAX).with_nanos(999_999_999))
+        );
+
+        let sum = Epoch::new(1)
+            .with_millis(700)
+            .checked_add(Duration::from_millis(400))
+            .unwrap();
+        assert_eq!(sum, Epoch::new(2).with_millis(100));
+        assert!(matches!(sum.subsecond(), SubSecond::Milli(100)));
+    }
+
+    #[test]
+    fn test_checked_sub() {
+        let second = Duration::from_secs(1);
+        assert_eq!(Epoch::new(i64::MIN).checked_sub(second), None);
+        assert_eq!(
+            Epoch::new(i64::MIN).with_millis(1).checked_sub(second),
+            None
+        );
+        assert_eq!(
+            Epoch::new(i64::MIN)
+                .with_millis(1)
+                .checked_sub(Duration::from_millis(1)),
+            Some(Epoch::new(i64::MIN).with_millis(0))
+        );
+        assert_eq!(
+            Epoch::new(i64::MIN + 1).checked_sub(second),
+            Some(Epoch::new(i64::MIN))
+        );
+
+        let difference = Epoch::new(0)
+            .with_micros(100)
+            .checked_sub(Duration::from_micros(200))
+            .unwrap();
+        assert_eq!(difference, Epoch::new(-1).with_micros(999_900));
+        assert!(matches!(difference.subsecond(), SubSecond::Micro(999_900)));
+    }
+
+    #[test]
+    fn test_saturating_add() {
+        let epochs = [
+            (
+                Epoch::new(i64::MAX),
+                Duration::from_secs(1),
+                Epoch::new(i64::MAX),
+            ),
+            (
+                Epoch::new(i64::MAX).with_micros(10),
+                Duration::from_secs(u64::MAX),
+                Epoch::new(i64::MAX).with_micros(999_999),
+            ),
+            (
+                Epoch::new(i64::MAX).with_millis(999),
+                Duration::new(1, 1),
+                Epoch::new(i64::MAX).with_nanos(999_999_999),
+            ),
+            (
+                Epoch::new(i64::MAX - 1).with_millis(500),
+                Duration::from_millis(250),
+                Epoch::new(i64::MAX - 1).with_millis(750),
+            ),
+        ];
+
+        for (epoch, duration, expected) in epochs {
+            let sum = epoch.saturating_add(duration);
+            assert_eq!(sum, expected);
+            assert_eq!(
+                sum.subsecond().precision(),
+                expected.subsecond().precision()
+            );
+            assert_eq!(sum.saturating_add(Duration::MAX).epoch(), i64::MAX);
+        }
+    }
+
+    #[test]
+    fn test_saturating_sub() {
+        let epochs = [
+            (
+                Epoch::new(i64::MIN),
+                Duration::from_secs(1),
+                Epoch::new(i64::MIN),
+            ),
+            (
+                Epoch::new(i64::MIN).with_micros(10),
+                Duration::from_secs(u64::MAX),
+                Epoch::new(i64::MIN).with_micros(0),
+            ),
+            (
+                Epoch::new(i64::MIN).with_millis(0),
+                Duration::from_nanos(1),
+                Epoch::new(i64::MIN).with_nanos(0),
+            ),
+            (
+                Epoch::new(i64::MIN + 1).with_millis(500),
+                Duration::from_millis(750),
+                Epoch::new(i64::MIN).with_millis(750),
+            ),
+        ];
+
+        for (epoch, duration, expected) in epochs {
+            let difference = epoch.saturating_sub(duration);
+            assert_eq!(difference, expected);
+            assert_eq!(
+                difference.subsecond().precision(),
+                expected.subsecond().precision()
+            );
+            assert_eq!(difference.saturating_sub(Duration::MAX).epoch(), i64::MIN);
+        }
+    }
+
+    #[test]
+    fn test_bounds() {
+        assert!(Epoch::MIN < Epoch::UNIX_EPOCH);
+        assert!(Epoch::UNIX_EPOCH < Epoch::MAX);
+        assert_eq!(Epoch::UNIX_EPOCH, Epoch::default());
+
+        for epoch in TEST_EPOCH {
+            for subsecond in [SubSecond::None, SubSecond::Milli(999), SubSecond::Nano(1)] {
+                let epoch = Epoch::from((epoch, subsecond));
+                assert!((Epoch::MIN..=Epoch::MAX).contains(&epoch));
+            }
+        }
+        assert_eq!(Epoch::checked_from_nanos(Epoch::MAX.as_nanos() + 1), None);
+        assert_eq!(Epoch::checked_from_nanos(Epoch::MIN.as_nanos() - 1), None);
+    }
+
+    #[test]
+    fn test_neg() {
+        for epoch in TEST_EPOCH {
+            for subsecond in [
+                SubSecond::None,
+                SubSecond::Milli(250),
+                SubSecond::Micro(1),
+                SubSecond::Nano(999_999_999),
+            ] {
+                let epoch = Epoch::from((epoch, subsecond));
+                let Some(negated) = epoch.checked_neg() else {
+                    assert_eq!(epoch, Epoch::MIN.with_nanos(0));
+                    continue;
+                };
+
+                assert_eq!(negated.as_nanos(), -epoch.as_nanos());
+                assert_eq!(
+                    negated.subsecond().precision(),
+                    epoch.subsecond().precision()
+                );
+                assert_eq!(-negated.clone(), epoch);
+                assert_eq!(epoch.abs(), negated.abs());
+                assert!(epoch.abs().as_nanos() >= 0);
+            }
+        }
+
+        assert_eq!(
+            -Epoch::new(1).with_millis(250),
+            Epoch::new(-2).with_millis(750)
+        );
+        assert_eq!(-Epoch::new(-1), Epoch::new(1));
+        assert_eq!(-Epoch::UNIX_EPOCH, Epoch::UNIX_EPOCH);
+        assert_eq!(-Epoch::MAX, Epoch::new(i64::MIN).with_nanos(1));
+        assert_eq!(Epoch::MIN.with_millis(0).checked_neg(), None);
+        assert_eq!(
+            Epoch::MIN.with_millis(1).abs(),
+            Epoch::new(i64::MAX).with_millis(999)
+        );
+    }
+
+    #[test]
+    #[should_panic(expected = "overflow when negating epoch")]
+    fn test_neg_overflow() {
+        let _ = -Epoch::MIN;
     }
 
     #[test]
-    fn test_default() {
-        let default = Epoch::default();
-        assert_eq!(default.epoch, 0);
-        assert!(matches!(default.subsecond, SubSecond::None));
+    fn test_from_duration() {
+        let durations = [
+            (Duration::ZERO, 0, 0),
+            (Duration::from_millis(250), 0, 250_000_000),
+            (Duration::from_nanos(1), 0, 1),
+            (
+                Duration::new(1_700_000_000, 123_456_789),
+                1_700_000_000,
+                123_456_789,
+            ),
+            (Duration::from_secs(i64::MAX as u64), i64::MAX, 0),
+            (Duration::from_secs(u64::MAX), i64::MAX, 999_999_999),
+            (Duration::MAX, i64::MAX, 999_999_999),
+        ];
+
+        for (duration, epoch, nanos) in durations {
+            let from = Epoch::from_duration(duration);
+            assert_eq!(from.epoch(), epoch);
+            assert!(matches!(from.subsecond(), SubSecond::Nano(n) if *n == nanos));
+            assert_eq!(from, Epoch::from_signed_duration(duration, false));
+        }
+
+        let since = SystemTime::now().duration_since(UNIX_EPOCH).unwrap();
+        assert_eq!(
+            Epoch::from_duration(since),
+            Epoch::try_from(UNIX_EPOCH + since).unwrap()
+        );
     }
 
     #[test]
-    fn test_display() {
-        let epochs = [
-            (0, "0"),
-            (1, "1"),
-            (-1, "-1"),
-            (123, "123"),
-            (-123, "-123"),
-            (i64::MAX, "9223372036854775807"),
-            (i64::MIN, "-9223372036854775808"),
+    fn test_from_signed_duration() {
+        let durations = [
+            (Duration::ZERO, 0, 0),
+            (Duration::from_millis(250), -1, 750_000_000),
+            (Duration::from_secs(1), -1, 0),
+            (Duration::new(1_700_000_000, 1), -1_700_000_001, 999_999_999),
+            (Duration::from_secs(i64::MIN.unsigned_abs()), i64::MIN, 0),
+            (Duration::from_secs(u64::MAX), i64::MIN, 0),
         ];
 
-        for (epoch, expected) in epochs {
-            let epoch = Epoch::new(epoch);
-            assert_eq!(epoch.to_string(), expected);
+        for (duration, epoch, nanos) in durations {
+            let from = Epoch::from_signed_duration(duration, true);
+            assert_eq!(from.epoch(), epoch);
+            assert!(matches!(from.subsecond(), SubSecond::Nano(n) if *n == nanos));
+        }
+
+        for nanos in [0, 1, 999_999_999, 1_000_000_001, 1_700_000_000_123_456_789] {
+            let duration = Duration::from_nanos(nanos);
+            let nanos = i128::from(nanos);
+            assert_eq!(
+                Epoch::from_signed_duration(duration, true).as_nanos(),
+                -nanos
+            );
+            assert_eq!(
+                Epoch::from_signed_duration(duration, false).as_nanos(),
+                nanos
+            );
         }
     }
 
     #[test]
-    fn test_display_with_millis() {
-        let epochs = [
-            (0, 0, "0.000"),
-            (0, 999, "0.999"),
-            (1, 123, "1.123"),
-            (-1, 123, "-1.123"),
-            (123, 999, "123.999"),
-            (-123, 999, "-123.999"),
-            (i64::MAX, 999, "9223372036854775807.999"),
-            (i64::MIN, 999, "-9223372036854775808.999"),
+    fn test_subsecond_serde() {
+        let subseconds = [
+            (SubSecond::None, (0u8, 0u64)),
+            (SubSecond::Milli(123), (1, 123)),
+            (SubSecond::Micro(999_999), (2, 999_999)),
+            (SubSecond::Nano(1), (3, 1)),
         ];
 
-        for (epoch, ms, expected) in epochs {
-            let epoch = Epoch::new(epoch).with_millis(ms);
-            assert_eq!(epoch.to_string(), expected);
+        for (subsecond, tagged) in subseconds {
+            let serialized = rmp_serde::to_vec(&subsecond).unwrap();
+            assert_eq!(serialized, rmp_serde::to_vec(&tagged).unwrap());
+
+            let deserialized: SubSecond = rmp_serde::from_slice(&serialized).unwrap();
+            assert_eq!(
+                deserialized.precision(),
+                subsecond.precision(),
+                "{subsecond:?}"
+            );
+            assert_eq!(deserialized, subsecond);
         }
+
+        let epoch = Epoch::new(1_700_000_000).with_millis(5);
+        let serialized = rmp_serde::to_vec(&epoch).unwrap();
+        assert_eq!(
+            serialized,
+            rmp_serde::to_vec(&(1_700_000_000i64, (1u8, 5u64))).unwrap()
+        );
+        assert_eq!(serialized.len(), 9);
+        assert_eq!(rmp_serde::from_slice::<Epoch>(&serialized).unwrap(), epoch);
     }
 
     #[test]
-    fn test_display_with_micros() {
-        let epochs = [
-            (0, 0, "0.000000"),
-            (0, 999_999, "0.999999"),
-            (1, 123_123, "1.123123"),
-            (-1, 123_123, "-1.123123"),
-            (123, 999_999, "123.999999"),
-            (-123, 999_999, "-123.999999"),
-            (i64::MAX, 999_999, "9223372036854775807.999999"),
-            (i64::MIN, 999_999, "-9223372036854775808.999999"),
+    fn test_subsecond_serde_invalid() {
+        for tagged in [
+            (0u8, 1u64),
+            (1, 1_000),
+            (1, u64::MAX),
+            (2, 1_000_000),
+            (3, 1_000_000_000),
+        ] {
+            let serialized = rmp_serde::to_vec(&tagged).unwrap();
+            assert!(
+                rmp_serde::from_slice::<SubSecond>(&serialized).is_err(),
+                "{tagged:?}"
+            );
+        }
+
+        let serialized = rmp_serde::to_vec(&(4u8, 0u64)).unwrap();
+        let error = rmp_serde::from_slice::<SubSecond>(&serialized).unwrap_err();
+        assert!(error.to_string().contains("unknown precision tag 4"));
+    }
+
+    #[test]
+    fn test_hash() {
+        use std::collections::HashSet;
+
+        let epochs: HashSet<Epoch> = [
+            Epoch::new(5).with_millis(1),
+            Epoch::new(5).with_micros(1_000),
+            Epoch::new(5).with_nanos(1_000_000),
+        ]
+        .into();
+        assert_eq!(epochs.len(), 1);
+
+        let epochs: HashSet<Epoch> = [Epoch::new(5), Epoch::new(5).with_millis(0)].into();
+        assert_eq!(epochs.len(), 1);
+
+        let subseconds: HashSet<SubSecond> =
+            [SubSecond::None, SubSecond::Nano(0), SubSecond::Micro(1)].into();
+        assert_eq!(subseconds.len(), 2);
+    }
+
+    #[test]
+    fn test_truncate_to() {
+        let epoch = Epoch::new(1).with_nanos(123_456_789);
+
+        let milli = epoch.truncate_to(Precision::Milli);
+        assert_eq!(milli.epoch(), 1);
+        assert!(matches!(milli.subsecond(), SubSecond::Milli(123)));
+        assert!(matches!(
+            epoch.truncate_to(Precision::Micro).subsecond(),
+            SubSecond::Micro(123_456)
+        ));
+        assert!(matches!(
+            epoch.truncate_to(Precision::Nano).subsecond(),
+            SubSecond::Nano(123_456_789)
+        ));
+
+        let second = Epoch::new(-5)
+            .with_nanos(999_999_999)
+            .truncate_to(Precision::Second);
+        assert_eq!(second.epoch(), -5);
+        assert!(matches!(second.subsecond(), SubSecond::None));
+
+        let finer = Epoch::new(7).with_millis(5).truncate_to(Precision::Nano);
+        assert!(matches!(finer.subsecond(), SubSecond::Nano(5_000_000)));
+    }
+
+    #[test]
+    fn test_round_to() {
+        let expected = [
+            (123_456_789, Precision::Milli, 1, 123_000_000),
+            (123_900_000, Precision::Milli, 1, 124_000_000),
+            (123_500_000, Precision::Milli, 1, 124_000_000),
+            (123_499_999, Precision::Milli, 1, 123_000_000),
+            (999_500_000, Precision::Milli, 2, 0),
+            (999_999_500, Precision::Micro, 2, 0),
+            (499_999_999, Precision::Second, 1, 0),
+            (500_000_000, Precision::Second, 2, 0),
+            (123_456_789, Precision::Nano, 1, 123_456_789),
         ];
 
-        for (epoch, ms, expected) in epochs {
-            let epoch = Epoch::new(epoch).with_micros(ms);
-            assert_eq!(epoch.to_string(), expected);
+        for (nanos, precision, epoch, subsecond) in expected {
+            let rounded = Epoch::new(1).with_nanos(nanos).round_to(precision);
+            assert_eq!(rounded.epoch(), epoch, "{nanos} {precision:?}");
+            assert_eq!(
+                rounded.subsecond().as_nanos(),
+                subsecond,
+                "{nanos} {precision:?}"
+            );
+            assert_eq!(
+                rounded.subsecond().unit_nanos(),
+                precision.unit_nanos(),
+                "{nanos} {precision:?}"
+            );
         }
     }
 
     #[test]
-    fn test_display_with_nanos() {
+    fn test_round_to_carry() {
+        let carried = Epoch::new(-1)
+            .with_nanos(999_999_999)
+            .round_to(Precision::Milli);
+        assert_eq!(carried.epoch(), 0);
+        assert!(matches!(carried.subsecond(), SubSecond::Milli(0)));
+
+        let max = Epoch::new(i64::MAX)
+            .with_nanos(999_999_999)
+            .round_to(Precision::Milli);
+        assert_eq!(max.epoch(), i64::MAX);
+        assert!(matches!(max.subsecond(), SubSecond::Milli(999)));
+    }
+
+    #[test]
+    fn test_now() {
+        let before = Epoch::try_from(SystemTime::now()).unwrap();
+        let now = Epoch::now();
+        let after = Epoch::try_from(SystemTime::now()).unwrap();
+
+        assert!(matches!(now.subsecond(), SubSecond::Nano(_)));
+        assert!(before.total_cmp(&now).is_le() && now.total_cmp(&after).is_le());
+    }
+
+    #[test]
+    fn test_now_truncated() {
+        let before = Epoch::now();
+        let millis = Epoch::now_millis();
+        let micros = Epoch::now_micros();
+
+        assert!(matches!(millis.subsecond(), SubSecond::Milli(ms) if *ms < 1_000));
+        assert!(matches!(micros.subsecond(), SubSecond::Micro(us) if *us < 1_000_000));
+        assert!(millis.as_nanos() > before.as_nanos() - 1_000_000);
+        assert!(micros.as_nanos() > before.as_nanos() - 1_000);
+        assert!(micros.total_cmp(&Epoch::now()).is_le());
+    }
+
+    #[test]
+    fn test_as_nanos() {
+        let expected = [
+            (Epoch::new(0), 0),
+            (Epoch::new(1).with_millis(1), 1_001_000_000),
+            (
+                Epoch::new(-1).with_millis(999),
+                -1_000_000_000 + 999_000_000,
+            ),
+            (Epoch::new(-2).with_micros(500_000), -1_500_000_000),
+            (Epoch::new(-1).with_nanos(1), -999_999_999),
+            (
+                Epoch::new(i64::MAX).with_nanos(999_999_999),
+                i128::from(i64::MAX) * 1_000_000_000 + 999_999_999,
+            ),
+            (Epoch::new(i64::MIN), i128::from(i64::MIN) * 1_000_000_000),
+        ];
+
+        for (epoch, nanos) in expected {
+            assert_eq!(epoch.as_nanos(), nanos, "{epoch}");
+            assert_eq!(Epoch::checked_from_nanos(nanos).unwrap().as_nanos(), nanos);
+        }
+    }
+
+    #[test]
+    fn test_system_time_round_trip() {
+        for _ in 0..10_000 {
+            let nanos = rand::random_range(-10i128.pow(21)..10i128.pow(21));
+            let offset = duration_from_nanos(nanos.unsigned_abs());
+            let time = if nanos < 0 {
+                UNIX_EPOCH - offset
+            } else {
+                UNIX_EPOCH + offset
+            };
+
+            let epoch = Epoch::try_from(time).unwrap();
+            assert_eq!(epoch.as_nanos(), nanos);
+            assert_eq!(SystemTime::try_from(epoch.clone()).unwrap(), time);
+
+            let epoch = Epoch::checked_from_nanos(nanos).unwrap();
+            let time = SystemTime::try_from(epoch.clone()).unwrap();
+            assert_eq!(Epoch::try_from(time).unwrap(), epoch);
+        }
+    }
+
+    #[test]
+    fn test_system_time_sign_convention() {
+        let before = UNIX_EPOCH - Duration::from_millis(250);
+        let epoch = Epoch::try_from(before).unwrap();
+        assert_eq!(epoch, Epoch::new(-1).with_nanos(750_000_000));
+        assert_eq!(epoch.to_string(), "-1.750000000");
+
+        assert_eq!(
+            Epoch::try_from(UNIX_EPOCH).unwrap(),
+            Epoch::new(0).with_nanos(0)
+        );
+        assert_eq!(
+            SystemTime::try_from(Epoch::new(-1).with_millis(750)).unwrap(),
+            before
+        );
+        assert_eq!(
+            SystemTime::try_from(Epoch::new(1_700_000_000)).unwrap(),
+            UNIX_EPOCH + Duration::from_secs(1_700_000_000)
+        );
+    }
+
+    #[test]
+    fn test_system_time_extremes() {
+        let extremes = [
+            Epoch::new(i64::MAX).with_nanos(999_999_999),
+            Epoch::new(i64::MIN).with_nanos(0),
+            Epoch::new(i64::MIN).with_millis(1),
+        ];
+
+        for epoch in extremes {
+            match SystemTime::try_from(epoch.clone()) {
+                Ok(time) => assert!(Epoch::try_from(time).unwrap().value_eq(&epoch)),
+                Err(err) => {
+                    assert!(matches!(err, EpochError::OutOfRange(s) if s == epoch.epoch()));
+                }
+            }
+        }
+
+        // Unix platforms store `SystemTime` as a signed 64-bit second count, like an Epoch.
+        #[cfg(unix)]
+        assert!(SystemTime::try_from(Epoch::new(i64::MIN)).is_ok());
+    }
+
+    #[test]
+    fn test_system_time_recent() {
+        let epoch = Epoch::new(1_700_000_000).with_nanos(123_456_789);
+        let time = SystemTime::try_from(epoch.clone()).unwrap();
+
+        assert_eq!(
+            time.duration_since(UNIX_EPOCH).unwrap(),
+            Duration::new(1_700_000_000, 123_456_789)
+        );
+        assert_eq!(Epoch::try_from(time).unwrap(), epoch);
+    }
+
+    #[test]
+    fn test_add_duration() {
+        let expected = [
+            (
+                Epoch::new(0).with_millis(900),
+                Duration::from_millis(200),
+                Epoch::new(1).with_millis(100),
+            ),
+            (Epoch::new(1), Duration::from_secs(2), Epoch::new(3)),
+            (
+                Epoch::new(1),
+                Duration::from_millis(1),
+                Epoch::new(1).with_millis(1),
+            ),
+            (
+                Epoch::new(0).with_millis(1),
+                Duration::from_nanos(1),
+                Epoch::new(0).with_nanos(1_000_001),
+            ),
+            (
+                Epoch::new(0).with_nanos(1),
+                Duration::from_secs(1),
+                Epoch::new(1).with_nanos(1),
+            ),
+            (
+                Epoch::new(-1).with_millis(500),
+                Duration::from_millis(500),
+                Epoch::new(0).with_millis(0),
+            ),
+            (
+                Epoch::new(-3).with_micros(1),
+                Duration::from_micros(2_999_999),
+                Epoch::new(0).with_micros(0),
+            ),
+        ];
+
+        for (epoch, duration, sum) in expected {
+            assert_eq!(epoch.clone() + duration, sum, "{epoch} + {duration:?}");
+        }
+    }
+
+    #[test]
+    fn test_sub_duration() {
+        let expected = [
+            (
+                Epoch::new(1).with_millis(100),
+                Duration::from_millis(200),
+                Epoch::new(0).with_millis(900),
+            ),
+            (
+                Epoch::new(0).with_millis(100),
+                Duration::from_millis(200),
+                Epoch::new(-1).with_millis(900),
+            ),
+            (
+                Epoch::new(0),
+                Duration::from_nanos(1),
+                Epoch::new(-1).with_nanos(999_999_999),
+            ),
+            (Epoch::new(1), Duration::from_secs(3), Epoch::new(-2)),
+            (
+                Epoch::new(-1).with_micros(5),
+                Duration::from_millis(1_500),
+                Epoch::new(-3).with_micros(500_005),
+            ),
+        ];
+
+        for (epoch, duration, difference) in expected {
+            assert_eq!(
+                epoch.clone() - duration,
+                difference,
+                "{epoch} - {duration:?}"
+            );
+            assert!((difference + duration).value_eq(&epoch));
+        }
+    }
+
+    #[test]
+    #[should_panic(expected = "overflow when adding duration to epoch")]
+    fn test_add_duration_overflow() {
+        let _ = Epoch::new(i64::MAX) + Duration::from_secs(1);
+    }
+
+    #[test]
+    #[should_panic(expected = "overflow when subtracting duration from epoch")]
+    fn test_sub_duration_overflow() {
+        let _ = Epoch::new(i64::MIN) - Duration::from_nanos(1);
+    }
+
+    #[test]
+    fn test_signed_nanos_since() {
+        let expected = [
+            (
+                Epoch::new(0).with_millis(1),
+                Epoch::new(0).with_micros(1_000),
+                0,
+            ),
+            (
+                Epoch::new(0).with_nanos(1_000_000),
+                Epoch::new(0).with_millis(1),
+                0,
+            ),
+            (
+                Epoch::new(2).with_millis(100),
+                Epoch::new(1).with_nanos(900_000_000),
+                200_000_000,
+            ),
+            (Epoch::new(0), Epoch::new(-1).with_micros(999_999), 1_000),
+            (Epoch::new(1), Epoch::new(-1), 2_000_000_000),
+            (
+                Epoch::new(i64::MAX),
+                Epoch::new(i64::MIN),
+                i128::from(u64::MAX) * 1_000_000_000,
+            ),
+        ];
+
+        for (later, earlier, nanos) in expected {
+            assert_eq!(
+                later.signed_nanos_since(&earlier),
+                nanos,
+                "{later} - {earlier}"
+            );
+            assert_eq!(
+                earlier.signed_nanos_since(&later),
+                -nanos,
+                "{earlier} - {later}"
+            );
+        }
+    }
+
+    #[test]
+    fn test_duration_since() {
+        let later = Epoch::new(2).with_micros(100_000);
+        let earlier = Epoch::new(1).with_millis(900);
+
+        assert_eq!(
+            later.duration_since(&earlier).unwrap(),
+            Duration::from_millis(200)
+        );
+        assert_eq!(
+            later
+                .duration_since(&Epoch::new(2).with_millis(100))
+                .unwrap(),
+            Duration::ZERO
+        );
+        assert!(matches!(
+            earlier.duration_since(&later),
+            Err(EpochError::NegativeDuration(-200_000_000))
+        ));
+    }
+
+    #[test]
+    fn test_nearest_in() {
+        let references = [Epoch::new(10), Epoch::new(20), Epoch::new(30)];
+
+        assert_eq!(Epoch::new(18).nearest_in(&references), Some(&references[1]));
+        assert_eq!(Epoch::new(21).nearest_in(&references), Some(&references[1]));
+        assert_eq!(Epoch::new(20).nearest_in(&references), Some(&references[1]));
+        assert_eq!(Epoch::new(-5).nearest_in(&references), Some(&references[0]));
+        assert_eq!(Epoch::new(99).nearest_in(&references), Some(&references[2]));
+        assert_eq!(Epoch::new(0).nearest_in(&[]), None);
+    }
+
+    #[test]
+    fn test_nearest_in_tie() {
+        let references = [Epoch::new(10), Epoch::new(11)];
+
+        let halfway = Epoch::new(10).with_millis(500);
+        assert_eq!(halfway.nearest_in(&references), Some(&references[0]));
+
+        let past_halfway = Epoch::new(10).with_nanos(500_000_001);
+        assert_eq!(past_halfway.nearest_in(&references), Some(&references[1]));
+    }
+
+    #[test]
+    fn test_nearest_in_mixed_precision() {
+        let references = [
+            Epoch::new(1).with_millis(100),
+            Epoch::new(1).with_micros(300_000),
+        ];
+
+        let epoch = Epoch::new(1).with_nanos(210_000_000);
+        assert_eq!(epoch.nearest_in(&references), Some(&references[1]));
+    }
+
+    #[test]
+    fn test_to_rfc3339() {
+        let expected = [
+            (Epoch::new(1_700_000_000), "2023-11-14T22:13:20Z"),
+            (
+                Epoch::new(1_700_000_000).with_millis(123),
+                "2023-11-14T22:13:20.123Z",
+            ),
+            (
+                Epoch::new(1_700_000_000).with_micros(123_456),
+                "2023-11-14T22:13:20.123456Z",
+            ),
+            (
+                Epoch::new(1_700_000_000).with_nanos(123_456_789),
+                "2023-11-14T22:13:20.123456789Z",
+            ),
+            (Epoch::new(0).with_millis(0), "1970-01-01T00:00:00.000Z"),
+            (Epoch::new(-1).with_micros(1), "1969-12-31T23:59:59.000001Z"),
+            (Epoch::new(951_825_599), "2000-02-29T11:59:59Z"),
+            (Epoch::new(1_234_567_890), "2009-02-13T23:31:30Z"),
+            (Epoch::new(RFC3339_MIN), "0000-01-01T00:00:00Z"),
+            (
+                Epoch::new(RFC3339_MAX).with_nanos(999_999_999),
+                "9999-12-31T23:59:59.999999999Z",
+            ),
+        ];
+
+        for (epoch, expected) in expected {
+            assert_eq!(epoch.to_rfc3339().unwrap(), expected);
+        }
+    }
+
+    #[test]
+    fn test_to_rfc3339_out_of_range() {
+        for epoch in [i64::MIN, RFC3339_MIN - 1, RFC3339_MAX + 1, i64::MAX] {
+            assert!(matches!(
+                Epoch::new(epoch).with_millis(1).to_rfc3339(),
+                Err(EpochError::OutOfRange(e)) if e == epoch
+            ));
+        }
+    }
+
+    #[test]
+    fn test_from_rfc3339() {
+        let expected = [
+            ("1970-01-01T00:00:00Z", Epoch::new(0)),
+            ("1969-12-31T23:59:59.500Z", Epoch::new(-1).with_millis(500)),
+            ("2000-02-29T12:00:00z", Epoch::new(951_825_600)),
+            ("2009-02-13t23:31:30Z", Epoch::new(1_234_567_890)),
+            (
+                "2023-11-14T23:13:20.000001+01:00",
+                Epoch::new(1_700_000_000).with_micros(1),
+            ),
+            (
+                "2023-11-14T19:43:20.123456789-02:30",
+                Epoch::new(1_700_000_000).with_nanos(123_456_789),
+            ),
+            (
+                "2023-11-14T22:13:20.5Z",
+                Epoch::new(1_700_000_000).with_millis(500),
+            ),
+            (
+                "2023-11-14T22:13:20.1234Z",
+                Epoch::new(1_700_000_000).with_micros(123_400),
+            ),
+            ("0000-01-01T00:00:00Z", Epoch::new(RFC3339_MIN)),
+            ("9999-12-31T23:59:59Z", Epoch::new(RFC3339_MAX)),
+        ];
+
+        for (rfc3339, expected) in expected {
+            let epoch = Epoch::from_rfc3339(rfc3339).unwrap();
+            assert_eq!(epoch, expected, "{rfc3339}");
+            assert_eq!(
+                epoch.subsecond().precision(),
+                expected.subsecond().precision()
+            );
+        }
+    }
+
+    #[test]
+    fn test_from_rfc3339_round_trip() {
         let epochs = [
-            (0, 0, "0.000000000"),
-            (0, 999_999_999, "0.999999999"),
-            (1, 123_123_123, "1.123123123"),
-            (-1, 123_123_123, "-1.123123123"),
-            (123, 999_999_999, "123.999999999"),
-            (-123, 999_999_999, "-123.999999999"),
-            (i64::MAX, 999_999_999, "9223372036854775807.999999999"),
-            (i64::MIN, 999_999_999, "-9223372036854775808.999999999"),
+            Epoch::new(0),
+            Epoch::new(-1).with_millis(1),
+            Epoch::new(1_700_000_000).with_micros(123_456),
+            Epoch::new(-1_234_567_890).with_nanos(999_999_999),
+            Epoch::new(951_868_799).with_millis(0),
+            Epoch::new(RFC3339_MIN).with_nanos(1),
+            Epoch::new(RFC3339_MAX).with_micros(999_999),
         ];
 
-        for (epoch, ms, expected) in epochs {
-            let epoch = Epoch::new(epoch).with_nanos(ms);
-            assert_eq!(epoch.to_string(), expected);
+        for epoch in epochs {
+            let parsed = Epoch::from_rfc3339(&epoch.to_rfc3339().unwrap()).unwrap();
+            assert_eq!(parsed, epoch);
+            assert_eq!(
+                parsed.subsecond().precision(),
+                epoch.subsecond().precision()
+            );
+        }
+
+        for days in (-719_528..2_932_897).step_by(997) {
+            let epoch = Epoch::new(days * SECONDS_PER_DAY + 45_296);
+            let parsed = Epoch::from_rfc3339(&epoch.to_rfc3339().unwrap()).unwrap();
+            assert_eq!(parsed, epoch);
         }
     }
 
     #[test]
-    fn test_display_with_delimiter() {
+    fn test_from_rfc3339_invalid() {
+        let invalid = [
+            "",
+            "2023-11-14",
+            "2023-11-14T22:13:20",
+            "2023-11-14 22:13:20Z",
+            "2023/11/14T22:13:20Z",
+            "2023-11-14T22:13:20.Z",
+            "2023-11-14T22:13:20.123",
+            "2023-11-14T22:13:20.123ZZ",
+            "2023-11-14T22:13:20+0200",
+            "2023-11-14T22:13:20+24:00",
+            "2023-11-14T22:13:20+02:60",
+            "+023-11-14T22:13:20Z",
+            "2023-1a-14T22:13:20Z",
+            "2023-00-14T22:13:20Z",
+            "2023-13-14T22:13:20Z",
+            "2023-02-29T22:13:20Z",
+            "1900-02-29T22:13:20Z",
+            "2023-04-31T22:13:20Z",
+            "2023-11-00T22:13:20Z",
+            "2023-11-14T24:00:00Z",
+            "2023-11-14T22:60:20Z",
+            "2016-12-31T23:59:60Z",
+            "2023-11-14T22:13:20Z\u{e9}",
+        ];
+
+        for s in invalid {
+            assert!(
+                matches!(Epoch::from_rfc3339(s), Err(EpochError::InvalidRfc3339(e)) if e == s),
+                "{s}"
+            );
+        }
+
+        assert!(matches!(
+            Epoch::from_rfc3339("2023-11-14T22:13:20.1234567890Z"),
+            Err(EpochError::InvalidFractionDigits(10))
+        ));
+    }
+
+    #[test]
+    fn test_to_rfc3339_with_digits() {
+        let epoch = Epoch::new(1_700_000_000).with_nanos(123_456_789);
+        let expected = [
+            (0, "2023-11-14T22:13:20Z"),
+            (3, "2023-11-14T22:13:20.123Z"),
+            (6, "2023-11-14T22:13:20.123456Z"),
+            (9, "2023-11-14T22:13:20.123456789Z"),
+        ];
+
+        for (digits, expected) in expected {
+            assert_eq!(epoch.to_rfc3339_with_digits(digits).unwrap(), expected);
+        }
+    }
+
+    #[test]
+    fn test_to_rfc3339_with_digits_pads_coarser_precision() {
         let epochs = [
-            (0, 0, '-', "0-000"),
-            (0, 0, ':', "0:000"),
-            (1, 0, ':', "1:000"),
-            (-1, 0, ':', "-1:000"),
-            (1, 999, ':', "1:999"),
-            (-1, 999, ':', "-1:999"),
+            (Epoch::new(0), 3, "1970-01-01T00:00:00.000Z"),
+            (
+                Epoch::new(0).with_millis(5),
+                6,
+                "1970-01-01T00:00:00.005000Z",
+            ),
+            (
+                Epoch::new(-1).with_millis(500),
+                3,
+                "1969-12-31T23:59:59.500Z",
+            ),
+            (Epoch::new(951_782_400), 0, "2000-02-29T00:00:00Z"),
+            (Epoch::new(RFC3339_MIN), 0, "0000-01-01T00:00:00Z"),
+            (Epoch::new(RFC3339_MAX), 0, "9999-12-31T23:59:59Z"),
         ];
 
-        for (epoch, ms, delimiter, expected) in epochs {
-            let epoch = Epoch::new(epoch).with_millis(ms);
-            assert_eq!(epoch.format_with_delimiter(delimiter), expected);
+        for (epoch, digits, expected) in epochs {
+            assert_eq!(epoch.to_rfc3339_with_digits(digits).unwrap(), expected);
+        }
+    }
+
+    #[test]
+    fn test_to_rfc3339_with_digits_error() {
+        assert!(matches!(
+            Epoch::new(0).to_rfc3339_with_digits(10),
+            Err(EpochError::InvalidFractionDigits(10))
+        ));
+
+        for epoch in [i64::MIN, RFC3339_MIN - 1, RFC3339_MAX + 1, i64::MAX] {
+            assert!(matches!(
+                Epoch::new(epoch).to_rfc3339_with_digits(0),
+                Err(EpochError::OutOfRange(e)) if e == epoch
+            ));
+        }
+    }
+
+    #[test]
+    fn test_subsecond_milli() {
+        for ms in TEST_MS {
+            assert_eq!(SubSecond::milli(ms), Some(SubSecond::Milli(ms)));
+        }
+        assert_eq!(SubSecond::milli(1000), None);
+        assert_eq!(SubSecond::milli(u16::MAX), None);
+    }
+
+    #[test]
+    fn test_subsecond_micro() {
+        for us in TEST_US {
+            assert_eq!(SubSecond::micro(us), Some(SubSecond::Micro(us)));
+        }
+        assert_eq!(SubSecond::micro(1_000_000), None);
+        assert_eq!(SubSecond::micro(u32::MAX), None);
+    }
+
+    #[test]
+    fn test_subsecond_nano() {
+        for ns in TEST_NS {
+            assert_eq!(SubSecond::nano(ns), Some(SubSecond::Nano(ns)));
         }
+        assert_eq!(SubSecond::nano(1_000_000_000), None);
+        assert_eq!(SubSecond::nano(u64::MAX), None);
     }
 
     #[test]
@@ -389,6 +4555,48 @@ mod tests {
         }
     }
 
+    #[test]
+    fn test_subsecond_from_fraction() {
+        let fractions = [
+            ("0", SubSecond::Milli(0)),
+            ("1", SubSecond::Milli(100)),
+            ("12", SubSecond::Milli(120)),
+            ("123", SubSecond::Milli(123)),
+            ("1234", SubSecond::Micro(123_400)),
+            ("12345", SubSecond::Micro(123_450)),
+            ("000001", SubSecond::Micro(1)),
+            ("1234567", SubSecond::Nano(123_456_700)),
+            ("12345678", SubSecond::Nano(123_456_780)),
+            ("999999999", SubSecond::Nano(999_999_999)),
+        ];
+
+        for (fraction, expected) in fractions {
+            let subsecond = SubSecond::from_fraction(fraction).unwrap();
+            assert_eq!(subsecond, expected, "{fraction}");
+            assert_eq!(subsecond.precision(), expected.precision(), "{fraction}");
+
+            let subsecond = SubSecond::try_from(fraction).unwrap();
+            assert_eq!(subsecond.precision(), expected.precision(), "{fraction}");
+        }
+    }
+
+    #[test]
+    fn test_subsecond_from_fraction_error() {
+        for fraction in ["", "1234567890"] {
+            assert!(matches!(
+                SubSecond::from_fraction(fraction),
+                Err(EpochError::InvalidFractionDigits(len)) if len == fraction.len()
+            ));
+        }
+
+        for fraction in ["-1", "+12", " 1", "1.5", "1a", "١"] {
+            assert!(matches!(
+                SubSecond::try_from(fraction),
+                Err(EpochError::InvalidSubSecond(s)) if s == fraction
+            ));
+        }
+    }
+
     #[test]
     fn test_subsecond_from_str_error() {
         let epochs = [
@@ -411,6 +4619,9 @@ mod tests {
             " ",
             "00a",
             "000.000.000",
+            "+12",
+            "+00000",
+            "+99999999",
         ];
 
         for epoch in epochs {
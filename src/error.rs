@@ -1,5 +1,9 @@
+use alloc::string::String;
+#[cfg(feature = "std")]
+use alloc::vec::Vec;
 use thiserror::Error;
 
+#[cfg(feature = "std")]
 #[derive(Error, Debug)]
 pub enum Codec {
     #[error("IO Error")]
@@ -8,11 +12,79 @@ pub enum Codec {
     SerdeError(#[from] rmp_serde::encode::Error),
     #[error("RMP Decode Error")]
     SerdeDecodeError(#[from] rmp_serde::decode::Error),
+    #[error("RMP Decode Error: could not decode {type_name} from {preview:02x?}")]
+    DecodeContext {
+        type_name: &'static str,
+        preview: Vec<u8>,
+        source: rmp_serde::decode::Error,
+    },
+    #[error("Format Error: {0}")]
+    FormatError(String),
+    #[error("Invalid UTF-8: {0}")]
+    InvalidUtf8(#[from] alloc::string::FromUtf8Error),
+    #[error("Invalid varint length prefix")]
+    InvalidVarint,
+    #[error("Input is empty")]
+    EmptyInput,
+    #[error("Frame is too short")]
+    TruncatedFrame,
+    #[error("Invalid MessagePack timestamp extension")]
+    InvalidTimestamp,
+    #[error("Invalid compression level: {0}")]
+    InvalidLevel(i32),
+    #[error("Unknown format: the frame header is missing or corrupted")]
+    UnknownFormat,
+    #[error("Version mismatch: expected frame version {expected}, found {found}")]
+    VersionMismatch { expected: u8, found: u8 },
+    #[error("Unknown algorithm id: {0}")]
+    UnknownAlgorithm(u8),
+    #[error("Duplicate archive entry: {0}")]
+    DuplicateEntry(String),
+    #[error("Invalid archive entry name: {0}")]
+    InvalidEntryName(String),
+    #[error("Too many elements: a collection declares {found} entries, at most {max} are allowed")]
+    TooManyElements { max: usize, found: u64 },
+    #[error("Invalid page sequence at page {0}")]
+    InvalidPageSequence(u32),
+    #[error("Length mismatch: expected {expected} bytes, got {got}")]
+    LengthMismatch { expected: usize, got: usize },
+    #[error("Size limit exceeded: the data decompresses to more than {max} bytes")]
+    SizeLimitExceeded { max: usize },
+    #[error("Digest mismatch: expected {expected:#018x}, got {actual:#018x}")]
+    DigestMismatch { expected: u64, actual: u64 },
+    #[error("Checksum mismatch: expected {expected}, got {actual}")]
+    ChecksumMismatch { expected: String, actual: String },
+    #[error("Unsupported format: {0:?} is not available here, only MessagePack is")]
+    UnsupportedFormat(crate::Format),
+    #[error("Encoder is unusable: a checkpoint failed to start the next frame")]
+    EncoderPoisoned,
+    #[error("Unsupported framing: a header, algorithm tag or checksum cannot be written here")]
+    UnsupportedFraming,
 }
 #[derive(Error, Debug)]
 pub enum Epoch {
     #[error("Invalid SubSecond: {0}")]
     InvalidSubSecond(String),
+    #[error("SubSecond out of range: {value}{unit} is a second or more")]
+    SubSecondOutOfRange { value: u64, unit: &'static str },
     #[error("ParseIntError")]
-    InvalidEpoch(#[from] std::num::ParseIntError),
+    InvalidEpoch(#[from] core::num::ParseIntError),
+    #[error("Invalid number of fractional digits: {0}")]
+    InvalidFractionDigits(usize),
+    #[error("Ambiguous delimiter in: {0:?}")]
+    AmbiguousDelimiter(String),
+    #[error("Invalid RFC 3339 timestamp: {0:?}")]
+    InvalidRfc3339(String),
+    #[error("Invalid unit: {0:?}")]
+    InvalidUnit(String),
+    #[error("Epoch out of range: {0}")]
+    OutOfRange(i64),
+    #[error("Denominator is zero")]
+    ZeroDenominator,
+    #[error("Invalid Excel serial date: {0}")]
+    InvalidSerial(f64),
+    #[error("Arithmetic overflow")]
+    Overflow,
+    #[error("Negative duration: {0} nanoseconds")]
+    NegativeDuration(i128),
 }
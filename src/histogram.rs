@@ -0,0 +1,170 @@
+use crate::Epoch;
+
+use std::time::Duration;
+
+/// Tallies epochs into `count` consecutive buckets of equal width, starting at `start`.
+///
+/// Epochs before `start` are counted as underflow and epochs past the last bucket as overflow.
+/// Each bucket includes its start and excludes its end.
+///
+/// # Examples
+///
+/// ```
+/// use epoch_archive::{Epoch, EpochHistogram};
+/// use std::time::Duration;
+///
+/// let mut histogram = EpochHistogram::new(Epoch::new(0), Duration::from_secs(10), 3);
+/// for seconds in [-1, 0, 5, 12, 29, 30] {
+///     histogram.add(&Epoch::new(seconds));
+/// }
+///
+/// assert_eq!(histogram.counts(), [2, 1, 1]);
+/// assert_eq!(histogram.underflow(), 1);
+/// assert_eq!(histogram.overflow(), 1);
+/// ```
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct EpochHistogram {
+    start: Epoch,
+    bucket: Duration,
+    counts: Vec<u64>,
+    underflow: u64,
+    overflow: u64,
+}
+
+impl EpochHistogram {
+    /// Creates an empty histogram with `count` buckets of width `bucket`, the first starting at
+    /// `start`.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `bucket` is zero.
+    #[must_use]
+    pub fn new(start: Epoch, bucket: Duration, count: usize) -> Self {
+        assert!(!bucket.is_zero(), "bucket should be greater than zero");
+
+        Self {
+            start,
+            bucket,
+            counts: vec![0; count],
+            underflow: 0,
+            overflow: 0,
+        }
+    }
+
+    /// Counts `epoch` in the bucket it falls in, or as underflow or overflow.
+    pub fn add(&mut self, epoch: &Epoch) {
+        let offset = epoch.as_nanos() - self.start.as_nanos();
+        if offset < 0 {
+            self.underflow += 1;
+            return;
+        }
+
+        let index = offset / self.bucket.as_nanos().cast_signed();
+        match usize::try_from(index)
+            .ok()
+            .and_then(|index| self.counts.get_mut(index))
+        {
+            Some(count) => *count += 1,
+            None => self.overflow += 1,
+        }
+    }
+
+    /// Returns the tally of every bucket, in order.
+    #[must_use]
+    pub fn counts(&self) -> &[u64] {
+        &self.counts
+    }
+
+    /// Returns how many epochs fell before the first bucket.
+    #[must_use]
+    pub fn underflow(&self) -> u64 {
+        self.underflow
+    }
+
+    /// Returns how many epochs fell past the last bucket.
+    #[must_use]
+    pub fn overflow(&self) -> u64 {
+        self.overflow
+    }
+
+    /// Returns the start of the first bucket.
+    #[must_use]
+    pub fn start(&self) -> &Epoch {
+        &self.start
+    }
+
+    /// Returns the width of every bucket.
+    #[must_use]
+    pub fn bucket(&self) -> Duration {
+        self.bucket
+    }
+}
+
+impl Extend<Epoch> for EpochHistogram {
+    fn extend<I: IntoIterator<Item = Epoch>>(&mut self, epochs: I) {
+        for epoch in epochs {
+            self.add(&epoch);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_histogram() {
+        let start = Epoch::new(1_700_000_000);
+        let mut histogram = EpochHistogram::new(start, Duration::from_secs(60), 4);
+
+        let offsets = [
+            -3600, -1, 0, 1, 59, 60, 119, 125, 130, 180, 239, 240, 86_400,
+        ];
+        for offset in offsets {
+            histogram.add(&Epoch::new(1_700_000_000 + offset));
+        }
+
+        assert_eq!(histogram.counts(), [3, 2, 2, 2]);
+        assert_eq!(histogram.underflow(), 2);
+        assert_eq!(histogram.overflow(), 2);
+    }
+
+    #[test]
+    fn test_histogram_subsecond() {
+        let start = Epoch::new(10).with_millis(500);
+        let mut histogram = EpochHistogram::new(start, Duration::from_millis(250), 2);
+
+        histogram.extend([
+            Epoch::new(10).with_nanos(499_999_999),
+            Epoch::new(10).with_micros(500_000),
+            Epoch::new(10).with_millis(749),
+            Epoch::new(10).with_millis(750),
+            Epoch::new(10).with_nanos(999_999_999),
+            Epoch::new(11),
+        ]);
+
+        assert_eq!(histogram.counts(), [2, 2]);
+        assert_eq!(histogram.underflow(), 1);
+        assert_eq!(histogram.overflow(), 1);
+    }
+
+    #[test]
+    fn test_histogram_extremes() {
+        let mut histogram = EpochHistogram::new(Epoch::new(i64::MIN), Duration::from_secs(1), 1);
+
+        histogram.extend([Epoch::new(i64::MIN), Epoch::new(i64::MAX)]);
+        assert_eq!(histogram.counts(), [1]);
+        assert_eq!(histogram.overflow(), 1);
+
+        let mut empty = EpochHistogram::new(Epoch::new(0), Duration::from_secs(1), 0);
+        empty.add(&Epoch::new(0));
+        assert_eq!(empty.counts(), [0u64; 0]);
+        assert_eq!(empty.overflow(), 1);
+    }
+
+    #[test]
+    #[should_panic(expected = "bucket should be greater than zero")]
+    fn test_histogram_zero_bucket() {
+        let _ = EpochHistogram::new(Epoch::new(0), Duration::ZERO, 1);
+    }
+}
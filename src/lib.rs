@@ -1,16 +1,69 @@
 #![crate_name = "epoch_archive"]
+#![cfg_attr(not(feature = "std"), no_std)]
 #![deny(clippy::all)]
 #![warn(clippy::cargo)]
 #![warn(clippy::pedantic)]
 #![warn(clippy::perf)]
 
+extern crate alloc;
+
+#[cfg(feature = "std")]
+mod adaptive;
+#[cfg(feature = "std")]
+mod archive;
+#[cfg(feature = "std")]
+mod clock;
+#[cfg(feature = "std")]
 mod codec;
+#[cfg(feature = "std")]
+mod continuous;
 mod epoch;
 mod error;
+#[cfg(feature = "std")]
+mod histogram;
+#[cfg(feature = "std")]
+mod msgpack;
+#[cfg(feature = "std")]
+mod seq_epoch;
+#[cfg(feature = "std")]
+mod window;
 
+#[cfg(feature = "std")]
+pub use adaptive::AdaptiveCodec;
+#[cfg(feature = "std")]
+pub use adaptive::AdaptiveProfile;
+#[cfg(feature = "std")]
+pub use adaptive::LevelSample;
+#[cfg(feature = "std")]
+pub use archive::ArchiveReader;
+#[cfg(feature = "std")]
+pub use clock::Clock;
+#[cfg(feature = "std")]
 pub use codec::Codec;
+#[cfg(feature = "std")]
+pub use codec::CodecBuilder;
+#[cfg(feature = "std")]
+pub use codec::CompressionStats;
+#[cfg(feature = "std")]
+pub use codec::DynCodec;
+#[cfg(feature = "std")]
+pub use codec::Format;
+#[cfg(feature = "std")]
+pub use continuous::ContinuousEncoder;
 pub use epoch::Epoch;
+pub use epoch::PadPolicy;
+pub use epoch::ParseOptions;
+pub use epoch::Precision;
 pub use epoch::SubSecond;
+#[cfg(feature = "std")]
+pub use histogram::EpochHistogram;
+#[cfg(feature = "std")]
+pub use seq_epoch::SeqEpoch;
+#[cfg(feature = "std")]
+pub use seq_epoch::SeqEpochGen;
+#[cfg(feature = "std")]
+pub use window::window;
 
+#[cfg(feature = "std")]
 pub use error::Codec as CodecError;
 pub use error::Epoch as EpochError;
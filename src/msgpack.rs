@@ -0,0 +1,213 @@
+use crate::CodecError;
+
+type Result<T, E = CodecError> = std::result::Result<T, E>;
+
+/// The start of a `MessagePack` value, as read by [`read_marker`].
+enum Marker {
+    /// An array or map of `entries`, each made of `per_entry` values.
+    Collection { entries: u64, per_entry: u64 },
+    /// Any other value, followed by `len` bytes of payload.
+    Scalar { len: usize },
+}
+
+/// Walks the first `MessagePack` value in `data`, failing if an array or map declares more than
+/// `max` entries.
+pub(crate) fn check_collection_lengths(data: &[u8], max: usize) -> Result<()> {
+    let mut rest = data;
+
+    // The number of values still to be walked, every one of them takes at least one byte.
+    let mut pending: u64 = 1;
+    while pending > 0 {
+        pending -= 1;
+
+        match read_marker(&mut rest)? {
+            Marker::Scalar { len } => {
+                take(&mut rest, len)?;
+            }
+            Marker::Collection { entries, .. } if entries > max as u64 => {
+                return Err(CodecError::TooManyElements {
+                    max,
+                    found: entries,
+                });
+            }
+            Marker::Collection { entries, per_entry } => {
+                pending += entries * per_entry;
+                if pending > rest.len() as u64 {
+                    return Err(CodecError::TruncatedFrame);
+                }
+            }
+        }
+    }
+
+    Ok(())
+}
+
+/// Rewrites the first `MessagePack` value in `data` with the entries of every map, at any depth,
+/// sorted by the bytes of their encoded keys. Everything else is copied as it is.
+pub(crate) fn sort_maps(data: &[u8]) -> Result<Vec<u8>> {
+    let mut out = Vec::with_capacity(data.len());
+    sort_value(&mut &data[..], &mut out)?;
+
+    Ok(out)
+}
+
+fn sort_value(rest: &mut &[u8], out: &mut Vec<u8>) -> Result<()> {
+    let start = *rest;
+    let marker = read_marker(rest)?;
+    out.extend_from_slice(&start[..start.len() - rest.len()]);
+
+    match marker {
+        Marker::Scalar { len } => out.extend_from_slice(take(rest, len)?),
+        Marker::Collection {
+            entries,
+            per_entry: 1,
+        } => {
+            for _ in 0..entries {
+                sort_value(rest, out)?;
+            }
+        }
+        Marker::Collection { entries, .. } => {
+            let mut pairs = Vec::new();
+            for _ in 0..entries {
+                let (mut key, mut value) = (Vec::new(), Vec::new());
+                sort_value(rest, &mut key)?;
+                sort_value(rest, &mut value)?;
+                pairs.push((key, value));
+            }
+
+            pairs.sort_unstable_by(|a, b| a.0.cmp(&b.0));
+            for (key, value) in pairs {
+                out.extend(key);
+                out.extend(value);
+            }
+        }
+    }
+
+    Ok(())
+}
+
+/// Reads the marker of the next value, along with the length that follows it for the formats
+/// that have one.
+fn read_marker(rest: &mut &[u8]) -> Result<Marker> {
+    let read_len = |bytes: &[u8]| bytes.iter().fold(0u64, |len, b| len << 8 | u64::from(*b));
+    let collection = |entries, per_entry| Marker::Collection { entries, per_entry };
+
+    let marker = take(rest, 1)?[0];
+    let len = match marker {
+        0x80..=0x8f => return Ok(collection(u64::from(marker & 0x0f), 2)),
+        0x90..=0x9f => return Ok(collection(u64::from(marker & 0x0f), 1)),
+        0xdc => return Ok(collection(read_len(take(rest, 2)?), 1)),
+        0xdd => return Ok(collection(read_len(take(rest, 4)?), 1)),
+        0xde => return Ok(collection(read_len(take(rest, 2)?), 2)),
+        0xdf => return Ok(collection(read_len(take(rest, 4)?), 2)),
+        0xa0..=0xbf => u64::from(marker & 0x1f),
+        0xc4 | 0xd9 => read_len(take(rest, 1)?),
+        0xc5 | 0xda => read_len(take(rest, 2)?),
+        0xc6 | 0xdb => read_len(take(rest, 4)?),
+        0xc7 => read_len(take(rest, 1)?) + 1,
+        0xc8 => read_len(take(rest, 2)?) + 1,
+        0xc9 => read_len(take(rest, 4)?) + 1,
+        0xcc | 0xd0 => 1,
+        0xcd | 0xd1 | 0xd4 => 2,
+        0xd5 => 3,
+        0xca | 0xce | 0xd2 => 4,
+        0xd6 => 5,
+        0xcb | 0xcf | 0xd3 => 8,
+        0xd7 => 9,
+        0xd8 => 17,
+        _ => 0,
+    };
+
+    Ok(Marker::Scalar {
+        len: usize::try_from(len).map_err(|_| CodecError::TruncatedFrame)?,
+    })
+}
+
+/// Splits the next `len` bytes off the front of `rest`.
+fn take<'a>(rest: &mut &'a [u8], len: usize) -> Result<&'a [u8]> {
+    let (bytes, remaining) = rest
+        .split_at_checked(len)
+        .ok_or(CodecError::TruncatedFrame)?;
+    *rest = remaining;
+
+    Ok(bytes)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::Codec;
+
+    use std::collections::{BTreeMap, HashMap};
+
+    fn scalars() -> Vec<u8> {
+        let mut values = Codec::serialize(&(
+            (-1i8, 200u8, -300i16, 70_000u32, -5_000_000_000i64, u64::MAX),
+            (1.5f32, 2.5f64, true, (), "a".repeat(40), "b".repeat(300)),
+            Some(vec![Some(1u8), None]),
+        ))
+        .unwrap();
+        values[0] += 3;
+        values.extend([0xc4, 2, 0, 0, 0xc5, 0x01, 0x00]);
+        values.extend([0; 0x100]);
+        values.extend([0xc7, 12, 0xff]);
+        values.extend([0; 12]);
+
+        values
+    }
+
+    #[test]
+    fn test_check_collection_lengths_scalars() {
+        let values = scalars();
+
+        assert!(check_collection_lengths(&values, 6).is_ok());
+        for len in 0..values.len() {
+            assert!(check_collection_lengths(&values[..len], 6).is_err());
+        }
+    }
+
+    #[test]
+    fn test_sort_maps() {
+        let map: HashMap<String, Vec<u32>> = (0..50)
+            .map(|i| (format!("key-{i:02}"), vec![i; 3]))
+            .collect();
+        let sorted: BTreeMap<_, _> = map.clone().into_iter().collect();
+
+        let expected = Codec::serialize(&sorted).unwrap();
+        assert_eq!(
+            sort_maps(&Codec::serialize(&map).unwrap()).unwrap(),
+            expected
+        );
+        assert_eq!(sort_maps(&expected).unwrap(), expected);
+    }
+
+    #[test]
+    fn test_sort_maps_nested() {
+        let inner: HashMap<u32, &str> = [(3, "c"), (1, "a"), (2, "b")].into();
+        let outer: HashMap<_, _> = [
+            ("y", (inner.clone(), vec![inner.clone()])),
+            ("x", (inner.clone(), vec![])),
+        ]
+        .into();
+
+        let inner: BTreeMap<_, _> = inner.into_iter().collect();
+        let expected: BTreeMap<_, _> = [
+            ("x", (inner.clone(), vec![])),
+            ("y", (inner.clone(), vec![inner])),
+        ]
+        .into();
+
+        let sorted = sort_maps(&Codec::serialize(&outer).unwrap()).unwrap();
+        assert_eq!(sorted, Codec::serialize(&expected).unwrap());
+    }
+
+    #[test]
+    fn test_sort_maps_scalars() {
+        let values = scalars();
+        assert_eq!(sort_maps(&values).unwrap(), values);
+
+        for len in 0..values.len() {
+            assert!(sort_maps(&values[..len]).is_err());
+        }
+    }
+}
@@ -0,0 +1,108 @@
+use crate::Epoch;
+
+/// An [`Epoch`] paired with a sequence counter, a unique and ordered key for events that share
+/// the same nanosecond.
+///
+/// Keys compare by epoch first and by sequence second.
+#[derive(Debug, Clone, PartialEq, Eq, PartialOrd, Ord)]
+pub struct SeqEpoch {
+    epoch: Epoch,
+    seq: u32,
+}
+
+impl SeqEpoch {
+    /// Creates a key from an epoch and its sequence counter.
+    #[must_use]
+    pub fn new(epoch: Epoch, seq: u32) -> Self {
+        Self { epoch, seq }
+    }
+
+    /// Returns the epoch of the key.
+    #[must_use]
+    pub fn epoch(&self) -> &Epoch {
+        &self.epoch
+    }
+
+    /// Returns the sequence counter of the key.
+    #[must_use]
+    pub fn seq(&self) -> u32 {
+        self.seq
+    }
+}
+
+/// Generates strictly increasing [`SeqEpoch`] keys from [`Epoch::now`].
+///
+/// Keys generated within the same nanosecond share their epoch and count up their sequence,
+/// which starts over at `0` once the clock advances. If the system clock goes backwards, the
+/// last epoch is kept and the sequence keeps counting, so the keys still increase. Should the
+/// sequence run out, the epoch is moved forward by a nanosecond.
+///
+/// # Examples
+///
+/// ```
+/// use epoch_archive::SeqEpochGen;
+///
+/// let mut keys = SeqEpochGen::new();
+/// let first = keys.next_key();
+/// let second = keys.next_key();
+/// assert!(first < second);
+/// ```
+#[derive(Debug, Clone, Default)]
+pub struct SeqEpochGen {
+    last: Option<SeqEpoch>,
+}
+
+impl SeqEpochGen {
+    /// Creates a generator that has not produced any keys yet.
+    #[must_use]
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Returns the next key, strictly greater than every key returned before it.
+    pub fn next_key(&mut self) -> SeqEpoch {
+        let now = Epoch::now();
+        let key = match self.last.take() {
+            Some(last) if now.as_nanos() > last.epoch.as_nanos() => SeqEpoch::new(now, 0),
+            Some(SeqEpoch { epoch, seq }) => match seq.checked_add(1) {
+                Some(seq) => SeqEpoch::new(epoch, seq),
+                None => SeqEpoch::new(Epoch::saturating_from_nanos(epoch.as_nanos() + 1), 0),
+            },
+            None => SeqEpoch::new(now, 0),
+        };
+
+        self.last = Some(key.clone());
+        key
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use std::collections::BTreeSet;
+
+    #[test]
+    fn test_next_key() {
+        let mut generator = SeqEpochGen::new();
+        let keys: Vec<_> = (0..100_000).map(|_| generator.next_key()).collect();
+
+        assert!(keys.windows(2).all(|pair| pair[0] < pair[1]));
+        assert_eq!(keys.iter().collect::<BTreeSet<_>>().len(), keys.len());
+    }
+
+    #[test]
+    fn test_next_key_clock_behind() {
+        let future = Epoch::now().add_epoch(&Epoch::new(3_600)).unwrap();
+        let mut generator = SeqEpochGen {
+            last: Some(SeqEpoch::new(future.clone(), 5)),
+        };
+
+        assert_eq!(generator.next_key(), SeqEpoch::new(future.clone(), 6));
+
+        generator.last = Some(SeqEpoch::new(future.clone(), u32::MAX));
+        let key = generator.next_key();
+        assert_eq!(key.seq(), 0);
+        assert_eq!(key.epoch().as_nanos(), future.as_nanos() + 1);
+    }
+}
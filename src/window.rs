@@ -0,0 +1,138 @@
+use crate::Epoch;
+
+use std::time::Duration;
+
+/// Groups a stream of timestamped items into fixed windows aligned to the Unix epoch.
+///
+/// Consecutive items whose epochs fall in the same `window` wide slot are collected together,
+/// and each group is yielded with the start of its window. Empty windows are skipped. The start
+/// always has `SubSecond::Nano` precision.
+///
+/// The input is expected to be sorted. An out-of-order item is not an error: it closes the
+/// current window and starts a new one of its own, so a window can be yielded more than once
+/// when the input jumps back in time.
+///
+/// # Panics
+///
+/// Panics if `window` is zero.
+///
+/// # Examples
+///
+/// ```
+/// use epoch_archive::{window, Epoch};
+/// use std::time::Duration;
+///
+/// let events = (0..25).map(|i| (Epoch::new(i), i));
+/// let windows: Vec<_> = window(events, Duration::from_secs(10)).collect();
+///
+/// assert_eq!(windows.len(), 3);
+/// assert_eq!(windows[1].0.epoch(), 10);
+/// assert_eq!(windows[2].1.len(), 5);
+/// ```
+pub fn window<T, I>(iter: I, window: Duration) -> impl Iterator<Item = (Epoch, Vec<(Epoch, T)>)>
+where
+    I: Iterator<Item = (Epoch, T)>,
+{
+    let width = window.as_nanos().cast_signed();
+    assert!(width > 0, "window should be greater than zero");
+
+    let mut iter = iter.peekable();
+    std::iter::from_fn(move || {
+        let first = iter.next()?;
+        let start = first.0.as_nanos().div_euclid(width) * width;
+        let slot = start..start + width;
+
+        let mut items = vec![first];
+        while let Some(item) = iter.next_if(|(epoch, _)| slot.contains(&epoch.as_nanos())) {
+            items.push(item);
+        }
+
+        Some((Epoch::saturating_from_nanos(start), items))
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_window() {
+        let events = [0, 3, 9, 10, 11, 19, 42, 45].map(|s| (Epoch::new(1_700_000_000 + s), s));
+
+        let windows: Vec<_> = window(events.into_iter(), Duration::from_secs(10)).collect();
+        let expected = [
+            (1_700_000_000, vec![0, 3, 9]),
+            (1_700_000_010, vec![10, 11, 19]),
+            (1_700_000_040, vec![42, 45]),
+        ];
+
+        assert_eq!(windows.len(), expected.len());
+        for ((start, items), (expected_start, expected_items)) in windows.iter().zip(expected) {
+            assert!(start.value_eq(&Epoch::new(expected_start)));
+            let items: Vec<i64> = items.iter().map(|(_, s)| *s).collect();
+            assert_eq!(items, expected_items);
+        }
+    }
+
+    #[test]
+    fn test_window_subsecond() {
+        let events = [
+            Epoch::new(0).with_millis(100),
+            Epoch::new(0).with_micros(249_999),
+            Epoch::new(0).with_nanos(250_000_000),
+            Epoch::new(0).with_millis(999),
+            Epoch::new(1),
+        ]
+        .map(|epoch| (epoch, ()));
+
+        let windows: Vec<_> = window(events.into_iter(), Duration::from_millis(250)).collect();
+        let starts: Vec<i128> = windows.iter().map(|(start, _)| start.as_nanos()).collect();
+        let lens: Vec<usize> = windows.iter().map(|(_, items)| items.len()).collect();
+
+        assert_eq!(starts, [0, 250_000_000, 750_000_000, 1_000_000_000]);
+        assert_eq!(lens, [2, 1, 1, 1]);
+    }
+
+    #[test]
+    fn test_window_negative() {
+        let events = [-11, -10, -1, 0].map(|s| (Epoch::new(s), s));
+
+        let windows: Vec<_> = window(events.into_iter(), Duration::from_secs(10)).collect();
+        let starts: Vec<i64> = windows.iter().map(|(start, _)| start.epoch()).collect();
+
+        assert_eq!(starts, [-20, -10, 0]);
+        assert_eq!(windows[1].1.len(), 2);
+    }
+
+    #[test]
+    fn test_window_out_of_order() {
+        let events = [1, 2, 15, 3, 4].map(|s| (Epoch::new(s), s));
+
+        let windows: Vec<_> = window(events.into_iter(), Duration::from_secs(10)).collect();
+        let starts: Vec<i64> = windows.iter().map(|(start, _)| start.epoch()).collect();
+
+        assert_eq!(starts, [0, 10, 0]);
+        assert_eq!(windows[2].1.len(), 2);
+    }
+
+    #[test]
+    fn test_window_empty() {
+        let events = std::iter::empty::<(Epoch, ())>();
+        assert_eq!(window(events, Duration::from_secs(1)).count(), 0);
+    }
+
+    #[test]
+    fn test_window_extreme() {
+        let events = [(Epoch::new(i64::MIN), ()), (Epoch::new(i64::MAX), ())];
+
+        let windows: Vec<_> = window(events.into_iter(), Duration::MAX).collect();
+        assert_eq!(windows.len(), 2);
+        assert_eq!(windows[0].0.epoch(), i64::MIN);
+    }
+
+    #[test]
+    #[should_panic(expected = "window should be greater than zero")]
+    fn test_window_zero() {
+        let _ = window(std::iter::empty::<(Epoch, ())>(), Duration::ZERO);
+    }
+}
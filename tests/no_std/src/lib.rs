@@ -0,0 +1,31 @@
+//! Builds the `no_std` subset of `epoch_archive`, run with `cargo build` from this directory.
+
+#![no_std]
+
+extern crate alloc;
+
+use alloc::string::String;
+use core::time::Duration;
+use epoch_archive::{Epoch, EpochError, Precision, SubSecond};
+
+/// Parses an epoch, moves it forward and formats it back, touching the parsing, arithmetic and
+/// formatting of `Epoch` and `SubSecond`.
+///
+/// # Errors
+///
+/// Return `epoch_archive::EpochError` if `s` is not a valid epoch.
+pub fn shift(s: &str, by: Duration) -> Result<String, EpochError> {
+    let epoch: Epoch = s.parse()?;
+    let shifted = epoch.checked_add(by).ok_or(EpochError::Overflow)?;
+
+    Ok(shifted.truncate_to(Precision::Milli).format())
+}
+
+/// Formats an epoch as an RFC 3339 timestamp.
+///
+/// # Errors
+///
+/// Return `epoch_archive::EpochError::OutOfRange` if the year does not fit in four digits.
+pub fn to_rfc3339(seconds: i64, subsecond: &str) -> Result<String, EpochError> {
+    Epoch::from((seconds, SubSecond::try_from(subsecond)?)).to_rfc3339()
+}
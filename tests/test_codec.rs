@@ -1,10 +1,11 @@
 extern crate epoch_archive;
 mod test_helpers;
 
-use epoch_archive::Codec;
+use epoch_archive::{ArchiveReader, Codec, CodecError, DynCodec, Epoch, Format};
 
 #[cfg(test)]
 mod tests {
+    use test_helpers::enums::{EventV1, EventV2};
     use test_helpers::structs::{Complex, Simple, SimpleOrComplex};
 
     use super::*;
@@ -19,6 +20,300 @@ mod tests {
         assert!(data.len() > compressed.len());
     }
 
+    #[test]
+    fn test_negative_levels() {
+        let data = std::fs::read_to_string("./tests/data/string.txt").unwrap();
+        let min = *zstd::compression_level_range().start();
+
+        for level in [-1, -5, -100, min] {
+            let codec = Codec::new(level);
+            let compressed = codec.encode(&data).unwrap();
+            assert_eq!(
+                codec.decode::<String>(&compressed).unwrap(),
+                data,
+                "{level}"
+            );
+
+            let compressed = codec.compress(data.as_bytes()).unwrap();
+            assert_eq!(codec.decompress(&compressed).unwrap(), data.as_bytes());
+        }
+    }
+
+    #[test]
+    fn test_formats() {
+        let formats = [
+            Format::MessagePack,
+            #[cfg(feature = "json")]
+            Format::Json,
+            #[cfg(feature = "cbor")]
+            Format::Cbor,
+        ];
+
+        for format in formats {
+            let codec = Codec::new(3).with_format(format);
+            let encoded = codec.encode(&Complex::default()).unwrap();
+            assert_eq!(
+                codec.decode::<Complex>(&encoded).unwrap(),
+                Complex::default(),
+                "{format:?}"
+            );
+            assert!(codec.decode::<Simple>(&encoded).is_err(), "{format:?}");
+
+            let serialized = codec.serialize_format(&Complex::default()).unwrap();
+            assert_eq!(
+                codec.decompress(&encoded).unwrap(),
+                serialized,
+                "{format:?}"
+            );
+            assert_eq!(
+                codec.deserialize::<Complex>(&serialized).unwrap(),
+                Complex::default(),
+                "{format:?}"
+            );
+            if format != Format::MessagePack {
+                let message_pack = Codec::serialize(&Complex::default()).unwrap();
+                assert!(
+                    codec.deserialize::<Complex>(&message_pack).is_err(),
+                    "{format:?}"
+                );
+            }
+        }
+    }
+
+    #[cfg(feature = "tokio")]
+    #[tokio::test]
+    async fn test_encode_decode_async() {
+        /// Serializes as the id of the thread it is serialized on.
+        struct ThreadId;
+
+        impl serde::Serialize for ThreadId {
+            fn serialize<S: serde::Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+                serializer.serialize_str(&format!("{:?}", std::thread::current().id()))
+            }
+        }
+
+        let codec = Codec::new(3);
+        let encoded = codec.encode_async(Complex::default()).await.unwrap();
+        assert_eq!(
+            codec.decode_async::<Complex>(encoded).await.unwrap(),
+            Complex::default()
+        );
+
+        let encoded = codec.encode_async(ThreadId).await.unwrap();
+        let thread = codec.decode_async::<String>(encoded).await.unwrap();
+        assert_ne!(thread, format!("{:?}", std::thread::current().id()));
+
+        assert!(matches!(
+            codec.decode_async::<Complex>(Vec::new()).await,
+            Err(CodecError::EmptyInput)
+        ));
+    }
+
+    #[cfg(feature = "zstdmt")]
+    #[test]
+    fn test_with_workers() {
+        let data: Vec<u64> = (0..1_000_000)
+            .map(|_| rand::random_range(0..1000))
+            .collect();
+        let codec = Codec::with_workers(3, 4);
+
+        let encoded = codec.encode(&data).unwrap();
+        assert_eq!(codec.decode::<Vec<u64>>(&encoded).unwrap(), data);
+        assert_eq!(Codec::new(3).decode::<Vec<u64>>(&encoded).unwrap(), data);
+
+        let (mut scratch, mut out) = (Vec::new(), Vec::new());
+        codec.encode_using(&data, &mut scratch, &mut out).unwrap();
+        assert_eq!(codec.decode::<Vec<u64>>(&out).unwrap(), data);
+
+        for workers in [0, 1] {
+            let codec = Codec::with_workers(3, workers);
+            assert_eq!(
+                codec.encode(&data).unwrap(),
+                Codec::new(3).encode(&data).unwrap()
+            );
+        }
+    }
+
+    #[test]
+    fn test_encode_with_stats() {
+        for codec in [Codec::new(1), Codec::new(19).with_algorithm_tag(true)] {
+            let (frame, stats) = codec.encode_with_stats(&Complex::default()).unwrap();
+
+            assert_eq!(frame, codec.encode(&Complex::default()).unwrap());
+            assert_eq!(stats.compressed_len(), frame.len());
+            assert_eq!(
+                stats.serialized_len(),
+                Codec::serialize(&Complex::default()).unwrap().len()
+            );
+            #[allow(clippy::cast_precision_loss)]
+            let ratio = stats.serialized_len() as f64 / frame.len() as f64;
+            assert!((stats.ratio() - ratio).abs() < f64::EPSILON);
+            assert!(stats.ratio() > 1.0);
+        }
+    }
+
+    #[test]
+    fn test_encode_frames() {
+        let codec = Codec::new(3);
+        for count in [0, 1, 100] {
+            let items: Vec<Simple> = (0..count)
+                .map(|i| Simple {
+                    numbers: vec![i; 5],
+                    ..Simple::default()
+                })
+                .collect();
+
+            let frames = codec.encode_frames(&items).unwrap();
+            assert_eq!(codec.decode_frames::<Simple>(&frames).unwrap(), items);
+        }
+    }
+
+    #[test]
+    fn test_decode_frames_truncated() {
+        let codec = Codec::new(3);
+        let frames = codec
+            .encode_frames(&[Simple::default(), Simple::default()])
+            .unwrap();
+        let first_len =
+            8 + usize::try_from(u64::from_le_bytes(frames[..8].try_into().unwrap())).unwrap();
+
+        for len in (first_len + 1)..frames.len() {
+            assert!(
+                matches!(
+                    codec.decode_frames::<Simple>(&frames[..len]),
+                    Err(CodecError::TruncatedFrame)
+                ),
+                "{len}"
+            );
+        }
+        assert_eq!(
+            codec.decode_frames::<Simple>(&frames[..first_len]).unwrap(),
+            [Simple::default()]
+        );
+    }
+
+    #[test]
+    fn test_decode_frame_iter() {
+        let codec = Codec::new(3);
+        let items: Vec<Simple> = (0..1000)
+            .map(|i| Simple {
+                numbers: vec![i; 5],
+                ..Simple::default()
+            })
+            .collect();
+        let frames = codec.encode_frames(&items).unwrap();
+
+        let mut iter = codec.decode_frame_iter::<Simple>(&frames);
+        assert_eq!(iter.next().unwrap().unwrap(), items[0]);
+        assert_eq!(iter.next().unwrap().unwrap(), items[1]);
+
+        let first = codec
+            .decode_frame_iter::<Simple>(&frames)
+            .map(Result::unwrap)
+            .find(|item| item.numbers[0] == 3);
+        assert_eq!(first.as_ref(), Some(&items[3]));
+
+        let decoded: Vec<Simple> = codec
+            .decode_frame_iter(&frames)
+            .collect::<Result<_, _>>()
+            .unwrap();
+        assert_eq!(decoded, items);
+    }
+
+    #[test]
+    fn test_decode_frame_iter_corrupt() {
+        let codec = Codec::new(3);
+        let items = [1u32, 2, 3, 4, 5];
+        let mut frames = Vec::new();
+        for (i, item) in items.iter().enumerate() {
+            let mut frame = codec.encode(item).unwrap();
+            if i == 2 {
+                frame[0] ^= 0xff;
+            }
+            frames.extend((frame.len() as u64).to_le_bytes());
+            frames.extend(frame);
+        }
+
+        let decoded: Vec<_> = codec.decode_frame_iter::<u32>(&frames).collect();
+        assert_eq!(decoded.len(), 3);
+        assert_eq!(decoded[0].as_ref().unwrap(), &1);
+        assert_eq!(decoded[1].as_ref().unwrap(), &2);
+        assert!(decoded[2].is_err());
+
+        let frames = codec.encode_frames(&items).unwrap();
+        let truncated: Vec<_> = codec
+            .decode_frame_iter::<u32>(&frames[..frames.len() - 1])
+            .collect();
+        assert_eq!(truncated.len(), items.len());
+        assert!(matches!(
+            truncated.last(),
+            Some(Err(CodecError::TruncatedFrame))
+        ));
+    }
+
+    #[test]
+    fn test_train_dictionary() {
+        let records: Vec<Simple> = (0..1000)
+            .map(|i| Simple {
+                numbers: vec![i, i % 13, i % 7],
+                ..Simple::default()
+            })
+            .collect();
+        let samples: Vec<Vec<u8>> = records
+            .iter()
+            .map(|record| Codec::serialize(record).unwrap())
+            .collect();
+        let samples: Vec<&[u8]> = samples.iter().map(Vec::as_slice).collect();
+
+        let dictionary = Codec::train_dictionary(&samples, 4096).unwrap();
+        assert!(!dictionary.is_empty() && dictionary.len() <= 4096);
+
+        let codec = Codec::with_dictionary(3, dictionary);
+        let plain = Codec::new(3);
+        for record in records.iter().step_by(97) {
+            let encoded = codec.encode(record).unwrap();
+            assert!(encoded.len() < plain.encode(record).unwrap().len());
+            assert_eq!(codec.decode::<Simple>(&encoded).unwrap(), *record);
+            assert!(plain.decode::<Simple>(&encoded).is_err());
+        }
+    }
+
+    #[test]
+    fn test_train_dictionary_too_few_samples() {
+        let sample = Codec::serialize(&Simple::default()).unwrap();
+
+        let trained = Codec::train_dictionary(&[sample.as_slice()], 4096);
+        assert!(matches!(trained, Err(CodecError::IOError(_))));
+    }
+
+    #[test]
+    fn test_compress_str() {
+        let text: String = (0..20_000)
+            .map(|i| {
+                format!(
+                    "2023-11-14T22:13:{:02}Z INFO request {i} served in {}ms ✓\n",
+                    i % 60,
+                    i % 97
+                )
+            })
+            .collect();
+
+        let codec = Codec::new(3);
+        let compressed = codec.compress_str(&text).unwrap();
+        assert!(compressed.len() * 10 < text.len());
+        assert_eq!(codec.decompress(&compressed).unwrap(), text.as_bytes());
+        assert_eq!(codec.decompress_str(&compressed).unwrap(), text);
+    }
+
+    #[test]
+    fn test_decompress_str_invalid_utf8() {
+        let codec = Codec::new(3);
+        let compressed = codec.compress(&[b'a', 0xff, b'b']).unwrap();
+
+        let decompressed = codec.decompress_str(&compressed);
+        assert!(matches!(decompressed, Err(CodecError::InvalidUtf8(_))));
+    }
+
     #[test]
     fn test_simple_struct() {
         let codec = Codec::new(1);
@@ -60,4 +355,313 @@ mod tests {
 
         assert!(matches!(decompressed, SimpleOrComplex::Simple(_)));
     }
+
+    #[test]
+    fn test_option_none() {
+        let codec = Codec::new(1);
+
+        let compressed = codec.encode(&None::<Simple>).unwrap();
+        assert_eq!(codec.decompress(&compressed).unwrap(), [0xc0]);
+        assert_eq!(codec.decode::<Option<Simple>>(&compressed).unwrap(), None);
+
+        let empty = codec.compress(&[]).unwrap();
+        assert!(codec.decode::<Option<Simple>>(&empty).is_err());
+    }
+
+    #[test]
+    fn test_option_some() {
+        let codec = Codec::new(1);
+
+        let compressed = codec.encode(&Some(Simple::default())).unwrap();
+        let decompressed = codec.decode::<Option<Simple>>(&compressed).unwrap();
+        assert_eq!(decompressed, Some(Simple::default()));
+    }
+
+    #[test]
+    fn test_option_nested() {
+        let codec = Codec::new(1);
+
+        let compressed = codec.encode(&Some(Some(Simple::default()))).unwrap();
+        let decompressed = codec.decode::<Option<Option<Simple>>>(&compressed).unwrap();
+        assert_eq!(decompressed, Some(Some(Simple::default())));
+
+        let compressed = codec.encode(&None::<Option<Simple>>).unwrap();
+        let decompressed = codec.decode::<Option<Option<Simple>>>(&compressed).unwrap();
+        assert_eq!(decompressed, None);
+
+        // MessagePack has a single nil, so the inner `None` can't be told apart from the outer one.
+        let compressed = codec.encode(&Some(None::<Simple>)).unwrap();
+        let decompressed = codec.decode::<Option<Option<Simple>>>(&compressed).unwrap();
+        assert_eq!(decompressed, None);
+    }
+
+    #[test]
+    fn test_decode_wrong_type_error_message() {
+        let codec = Codec::new(1);
+        let compressed = codec.encode(&Simple::default()).unwrap();
+
+        let err = codec.decode::<Complex>(&compressed).unwrap_err();
+        let message = err.to_string();
+        assert!(message.contains("Complex"), "{message}");
+        assert!(message.contains("[92, 95, 01, 02"), "{message}");
+    }
+
+    #[test]
+    fn test_decode_seq_visit() {
+        let codec = Codec::new(1);
+        let numbers: Vec<u32> = (0..10_000).collect();
+        let encoded = codec.encode(&numbers).unwrap();
+
+        let mut count = 0;
+        let mut sum = 0u64;
+        codec
+            .decode_seq_visit(&encoded, |n: u32| {
+                count += 1;
+                sum += u64::from(n);
+            })
+            .unwrap();
+
+        assert_eq!(count, numbers.len());
+        assert_eq!(sum, numbers.iter().copied().map(u64::from).sum::<u64>());
+    }
+
+    #[test]
+    fn test_decode_seq_visit_structs() {
+        let codec = Codec::new(1);
+        let encoded = codec.encode(&Complex::default().simples).unwrap();
+
+        let mut visited = Vec::new();
+        codec
+            .decode_seq_visit(&encoded, |simple: Simple| visited.push(simple))
+            .unwrap();
+
+        assert_eq!(visited, Complex::default().simples);
+    }
+
+    #[test]
+    fn test_encode_chunked_by_bytes() {
+        let codec = Codec::new(1);
+        let budget = 1024;
+        let record_len = Codec::serialize(&Simple::default()).unwrap().len();
+        let records = (0..500).map(|_| Simple::default());
+
+        let frames = codec
+            .encode_chunked_by_bytes(records, budget)
+            .collect::<Result<Vec<_>, _>>()
+            .unwrap();
+        assert!(frames.len() > 1);
+
+        let (last, full) = frames.split_last().unwrap();
+        for frame in full {
+            let len = codec.decompress(frame).unwrap().len();
+            assert!(len >= budget && len < budget + record_len);
+        }
+        assert!(codec.decompress(last).unwrap().len() < budget + record_len);
+
+        let decoded = codec.decode_chunked::<Simple, _>(&frames).unwrap();
+        assert_eq!(decoded.len(), 500);
+        assert!(decoded.iter().all(|simple| *simple == Simple::default()));
+    }
+
+    #[test]
+    fn test_encode_chunked_by_bytes_preserves_order() {
+        let codec = Codec::new(1);
+
+        let frames = codec
+            .encode_chunked_by_bytes(0..10_000u64, 100)
+            .collect::<Result<Vec<_>, _>>()
+            .unwrap();
+        let decoded = codec.decode_chunked::<u64, _>(&frames).unwrap();
+
+        assert_eq!(decoded, (0..10_000).collect::<Vec<_>>());
+    }
+
+    #[test]
+    fn test_encode_chunked_by_bytes_empty() {
+        let codec = Codec::new(1);
+
+        let mut frames = codec.encode_chunked_by_bytes(std::iter::empty::<u32>(), 100);
+        assert!(frames.next().is_none());
+    }
+
+    #[test]
+    fn test_archive_entries() {
+        let codec = Codec::new(1);
+        let simple = Codec::serialize(&Simple::default()).unwrap();
+        let complex = Codec::serialize(&Complex::default()).unwrap();
+        let text = std::fs::read("./tests/data/string.txt").unwrap();
+        let entries = [
+            ("simple", simple.as_slice()),
+            ("complex", complex.as_slice()),
+            ("text", text.as_slice()),
+        ];
+
+        let archive = codec.archive_entries(entries.into_iter()).unwrap();
+        let reader = ArchiveReader::new(&codec, &archive).unwrap();
+        assert_eq!(reader.len(), 3);
+
+        let entry = reader.get("complex").unwrap().unwrap();
+        assert_eq!(
+            codec.deserialize::<Complex>(&entry).unwrap(),
+            Complex::default()
+        );
+        assert_eq!(reader.get("missing").unwrap(), None);
+    }
+
+    #[test]
+    fn test_encode_paged() {
+        use rand::seq::SliceRandom;
+
+        let codec = Codec::new(1);
+        let complex = Complex {
+            simples: (0..2_000)
+                .map(|_| Simple {
+                    numbers: (0..20).map(|_| rand::random()).collect(),
+                    ..Simple::default()
+                })
+                .collect(),
+            ..Complex::default()
+        };
+
+        let mut pages = codec.encode_paged::<4096, _>(&complex).unwrap();
+        assert!(pages.len() > 10);
+
+        pages.shuffle(&mut rand::rng());
+        let decoded = codec.decode_paged::<4096, Complex>(&pages).unwrap();
+        assert_eq!(decoded, complex);
+    }
+
+    #[test]
+    fn test_enum_stable_tags() {
+        let codec = Codec::new(1);
+
+        let old = vec![EventV1::Start(1), EventV1::Stop(7)];
+        let decoded = codec
+            .decode::<Vec<EventV2>>(&codec.encode(&old).unwrap())
+            .unwrap();
+        assert_eq!(decoded, [EventV2::Start(1), EventV2::Stop(7)]);
+
+        let new = vec![EventV2::Pause(30), EventV2::Stop(7), EventV2::Start(1)];
+        let encoded = codec.encode(&new).unwrap();
+        assert_eq!(codec.decode::<Vec<EventV2>>(&encoded).unwrap(), new);
+        assert!(codec.decode::<Vec<EventV1>>(&encoded).is_err());
+    }
+
+    #[test]
+    fn test_epoch_round_trip() {
+        let codec = Codec::new(1);
+        let epochs = vec![
+            Epoch::new(1_700_000_000),
+            Epoch::new(-1).with_millis(999),
+            Epoch::new(0).with_micros(1),
+            Epoch::new(i64::MIN).with_nanos(999_999_999),
+        ];
+
+        let decoded = codec
+            .decode::<Vec<Epoch>>(&codec.encode(&epochs).unwrap())
+            .unwrap();
+        assert_eq!(decoded, epochs);
+
+        let serialized = Codec::serialize(&Epoch::new(1)).unwrap();
+        assert_eq!(serialized, Codec::serialize(&(1, (0, 0))).unwrap());
+    }
+
+    #[test]
+    fn test_encode_shared() {
+        let codec = Codec::new(1);
+        let frame = codec.encode_shared(&Complex::default()).unwrap();
+
+        let consumers: Vec<_> = (0..3).map(|_| std::sync::Arc::clone(&frame)).collect();
+        assert_eq!(std::sync::Arc::strong_count(&frame), 4);
+
+        for consumer in &consumers {
+            let decoded = codec.decode::<Complex>(consumer).unwrap();
+            assert_eq!(decoded, Complex::default());
+        }
+        assert_eq!(*frame, *codec.encode(&Complex::default()).unwrap());
+    }
+
+    #[test]
+    fn test_dyn_codec() {
+        let codecs: Vec<Box<dyn DynCodec>> = vec![
+            Box::new(Codec::new(1)),
+            Box::new(Codec::default()),
+            Box::new(Codec::new(22)),
+        ];
+        let serialized = Codec::serialize(&Complex::default()).unwrap();
+
+        for codec in &codecs {
+            let compressed = codec.encode_bytes(&serialized).unwrap();
+            assert!(compressed.len() < serialized.len());
+
+            let decompressed = codec.decode_bytes(&compressed).unwrap();
+            assert_eq!(decompressed, serialized);
+        }
+    }
+
+    #[test]
+    #[cfg(feature = "rayon")]
+    fn test_encode_batch_par() {
+        let codec = Codec::new(1);
+        let items: Vec<Simple> = (0..1000)
+            .map(|i| Simple {
+                numbers: vec![i; 10],
+                letters: vec!['a'; i as usize % 7],
+            })
+            .collect();
+
+        let frames = codec.encode_batch_par(&items).unwrap();
+        assert_eq!(frames.len(), items.len());
+        for (frame, item) in frames.iter().zip(&items) {
+            assert_eq!(frame, &codec.encode(item).unwrap());
+            assert_eq!(&codec.decode::<Simple>(frame).unwrap(), item);
+        }
+    }
+
+    #[test]
+    #[cfg(feature = "sha256")]
+    fn test_files() {
+        let path = std::env::temp_dir().join(format!("epoch_archive_{}_files", std::process::id()));
+        let sidecar = path.with_extension("sha256");
+        let codec = Codec::new(1);
+
+        codec.encode_to_files(&Complex::default(), &path).unwrap();
+        assert!(sidecar.exists());
+
+        let decoded = codec.decode_from_files::<Complex, _>(&path).unwrap();
+        assert_eq!(decoded, Complex::default());
+
+        let mut frame = std::fs::read(&path).unwrap();
+        let last = frame.len() - 1;
+        frame[last] ^= 0xFF;
+        std::fs::write(&path, frame).unwrap();
+
+        let corrupted = codec.decode_from_files::<Complex, _>(&path);
+        assert!(matches!(
+            corrupted,
+            Err(epoch_archive::CodecError::ChecksumMismatch { .. })
+        ));
+
+        std::fs::remove_file(path).unwrap();
+        std::fs::remove_file(sidecar).unwrap();
+    }
+
+    #[test]
+    #[cfg(feature = "xxhash")]
+    fn test_digest() {
+        let codec = Codec::new(1);
+
+        let (frame, digest) = codec.encode_with_digest(&Complex::default()).unwrap();
+        let (_, same) = codec.encode_with_digest(&Complex::default()).unwrap();
+        assert_eq!(digest, same);
+
+        let mut changed = Complex::default();
+        changed.number += 1;
+        let (_, different) = codec.encode_with_digest(&changed).unwrap();
+        assert_ne!(digest, different);
+
+        let (decoded, verified) = codec.decode_with_digest::<Complex>(&frame).unwrap();
+        assert_eq!(decoded, Complex::default());
+        assert_eq!(verified, digest);
+    }
 }
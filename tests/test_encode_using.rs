@@ -0,0 +1,58 @@
+extern crate epoch_archive;
+
+use epoch_archive::Codec;
+use std::alloc::{GlobalAlloc, Layout, System};
+use std::sync::atomic::{AtomicUsize, Ordering};
+
+/// Counts every allocation and reallocation made through the global allocator.
+struct CountingAllocator;
+
+static ALLOCATIONS: AtomicUsize = AtomicUsize::new(0);
+
+unsafe impl GlobalAlloc for CountingAllocator {
+    unsafe fn alloc(&self, layout: Layout) -> *mut u8 {
+        ALLOCATIONS.fetch_add(1, Ordering::SeqCst);
+        unsafe { System.alloc(layout) }
+    }
+
+    unsafe fn dealloc(&self, ptr: *mut u8, layout: Layout) {
+        unsafe { System.dealloc(ptr, layout) }
+    }
+
+    unsafe fn realloc(&self, ptr: *mut u8, layout: Layout, new_size: usize) -> *mut u8 {
+        ALLOCATIONS.fetch_add(1, Ordering::SeqCst);
+        unsafe { System.realloc(ptr, layout, new_size) }
+    }
+}
+
+#[global_allocator]
+static GLOBAL: CountingAllocator = CountingAllocator;
+
+#[test]
+fn test_encode_using_no_allocations_after_warmup() {
+    let codec = Codec::new(3);
+    let records: Vec<(u64, Vec<u32>, String)> = (0..10_000u32)
+        .map(|i| (u64::from(i), vec![i % 97; 16], format!("record-{:05}", i)))
+        .collect();
+    let (mut scratch, mut out) = (Vec::new(), Vec::new());
+
+    // Warm up with the largest record so neither buffer has to grow afterwards.
+    let largest = records.iter().max_by_key(|record| record.2.len()).unwrap();
+    codec.encode_using(largest, &mut scratch, &mut out).unwrap();
+    let capacities = (scratch.capacity(), out.capacity());
+
+    let before = ALLOCATIONS.load(Ordering::SeqCst);
+    for record in &records {
+        codec.encode_using(record, &mut scratch, &mut out).unwrap();
+    }
+    let after = ALLOCATIONS.load(Ordering::SeqCst);
+
+    assert_eq!(after - before, 0);
+    assert_eq!((scratch.capacity(), out.capacity()), capacities);
+
+    for record in records.iter().step_by(1000) {
+        codec.encode_using(record, &mut scratch, &mut out).unwrap();
+        let decoded = codec.decode::<(u64, Vec<u32>, String)>(&out).unwrap();
+        assert_eq!(&decoded, record);
+    }
+}
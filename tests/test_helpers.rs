@@ -63,3 +63,29 @@ pub mod structs {
         Complex(Complex),
     }
 }
+
+pub mod enums {
+    use serde::{Deserialize, Serialize};
+
+    /// The first version of an archived enum, with stable tags pinned by `rename`.
+    #[derive(Debug, Serialize, Deserialize, PartialEq)]
+    #[serde(tag = "t", content = "c")]
+    pub enum EventV1 {
+        #[serde(rename = "0")]
+        Start(i64),
+        #[serde(rename = "1")]
+        Stop(u32),
+    }
+
+    /// The second version of [`EventV1`], with its variants reordered and a new one appended.
+    #[derive(Debug, Serialize, Deserialize, PartialEq)]
+    #[serde(tag = "t", content = "c")]
+    pub enum EventV2 {
+        #[serde(rename = "1")]
+        Stop(u32),
+        #[serde(rename = "0")]
+        Start(i64),
+        #[serde(rename = "2")]
+        Pause(u64),
+    }
+}